@@ -0,0 +1,510 @@
+//! Rust port of ImCurveEdit, part of the ImGuizmo library (MIT License)
+//! https://github.com/CedricGuillemet/ImGuizmo/blob/master/ImCurveEdit.h
+//!
+//! As with the sequencer, the C++ `Delegate` class becomes a trait that the
+//! application implements over its own curve storage. View range, selection
+//! and the current mouse interaction live in [`CurveEditState`] instead of
+//! static locals.
+
+use glam::Vec2;
+use imgui::{ DrawListMut, Key, MouseButton, Ui };
+
+/// Radius in pixels around a point or curve that counts as hovering it
+const HOVER_DISTANCE: f32 = 6.;
+/// Line segments drawn between two points of a smooth or bezier curve
+const CURVE_SUBDIVISIONS: usize = 20;
+/// Smallest visible range on either axis that zooming in can reach
+const MIN_VIEW_SPAN: f32 = 1e-3;
+/// Most grid lines drawn along either axis
+const MAX_GRID_LINES: usize = 256;
+
+/// Interpolation between consecutive points of a curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CurveType {
+    /// Hold the value of a point until the next one (`CurveDiscrete` in ImCurveEdit)
+    Step,
+    #[default]
+    Linear,
+    /// Ease in and out of every point
+    Smooth,
+    /// Cubic curve with tangents derived from the neighbouring points
+    Bezier
+}
+
+/// A single point of a single curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EditPoint {
+    pub curve_index: usize,
+    pub point_index: usize
+}
+
+/// Key bindings used while the curve editor is focused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurveEditKeymap {
+    /// Fit the view to the points of every visible curve
+    pub frame_all: Key
+}
+
+impl Default for CurveEditKeymap {
+    fn default() -> Self {
+        Self { frame_all: Key::F }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+enum Interaction {
+    #[default]
+    None,
+    /// Selected points being dragged, with their position when the drag started
+    MovingPoints { origin: Vec2, points: Vec<(EditPoint, Vec2)> },
+    /// Every point of a curve being dragged together
+    MovingCurve { origin: Vec2, curve: usize, points: Vec<Vec2> },
+    /// Box selection started at a screen position
+    Selecting { origin: Vec2 },
+    Panning
+}
+
+/// View range, selection and mouse interaction of a curve editor
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurveEditState {
+    min: Vec2,
+    max: Vec2,
+    selection: Vec<EditPoint>,
+    focused: bool,
+    interaction: Interaction
+}
+
+impl Default for CurveEditState {
+    fn default() -> Self {
+        Self {
+            min: Vec2::ZERO,
+            max: Vec2::ONE,
+            selection: vec![],
+            focused: false,
+            interaction: Interaction::None
+        }
+    }
+}
+
+impl CurveEditState {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max, ..Default::default() }
+    }
+
+    /// Lower left corner of the visible range
+    pub fn get_min(&self) -> Vec2 { self.min }
+    /// Upper right corner of the visible range
+    pub fn get_max(&self) -> Vec2 { self.max }
+    pub fn get_selection(&self) -> &[EditPoint] { &self.selection }
+    pub fn get_focused(&self) -> bool { self.focused }
+    /// Whether points are being dragged
+    pub fn is_editing(&self) -> bool {
+        matches!(self.interaction, Interaction::MovingPoints { .. } | Interaction::MovingCurve { .. })
+    }
+
+    pub fn set_range(&mut self, min: Vec2, max: Vec2) {
+        self.min = min;
+        self.max = max;
+    }
+    pub fn set_selection(&mut self, selection: Vec<EditPoint>) { self.selection = selection; }
+
+    fn select(&mut self, point: EditPoint) {
+        if !self.selection.contains(&point) {
+            self.selection.push(point);
+        }
+    }
+}
+
+pub trait CurveEditDelegate {
+    fn get_curve_count(&self) -> usize;
+    fn get_curve_color(&self, curve: usize) -> u32;
+    /// Points of a curve, sorted by x
+    fn get_points(&self, curve: usize) -> &[Vec2];
+    /// Move a point, keeping the curve sorted. Returns the point's new index.
+    fn edit_point(&mut self, curve: usize, point: usize, value: Vec2) -> usize;
+    fn add_point(&mut self, curve: usize, value: Vec2);
+
+    fn get_state(&self) -> &CurveEditState;
+    fn get_state_mut(&mut self) -> &mut CurveEditState;
+
+    fn is_visible(&self, _curve: usize) -> bool { true }
+    fn get_curve_type(&self, _curve: usize) -> CurveType { CurveType::Linear }
+    fn get_background_color(&self) -> u32 { 0xff202020 }
+    /// Called before the first change to a curve during a drag or point insertion
+    fn begin_edit(&mut self, _curve: usize) {}
+    fn end_edit(&mut self) {}
+
+    /// Fit the view to the points of every visible curve, with some padding
+    fn frame_all(&mut self) {
+        let points = (0..self.get_curve_count())
+            .filter(|c| self.is_visible(*c))
+            .flat_map(|c| self.get_points(c).iter().copied());
+        let (min, max) = points.fold((Vec2::MAX, Vec2::MIN), |(lo, hi), p| (lo.min(p), hi.max(p)));
+        if min.x > max.x {
+            return;
+        }
+        // a single point or a flat curve still gets a usable range
+        let extent = (max - min).max(Vec2::splat(f32::EPSILON));
+        let padding = Vec2::new(
+            if max.x > min.x { extent.x * 0.05 } else { 1. },
+            if max.y > min.y { extent.y * 0.1 } else { 1. }
+        );
+        self.get_state_mut().set_range(min - padding, max + padding);
+    }
+
+    /// Draw the editor at the cursor position. A zero or negative size component
+    /// uses the available content region. Returns true if any curve was changed.
+    fn draw(&mut self, ui: &Ui, size: [f32; 2], keymap: &CurveEditKeymap) -> bool {
+        draw_curve_edit(self, ui, size, keymap)
+    }
+}
+
+fn smoothstep(x: f32) -> f32 {
+    let x = x.clamp(0., 1.);
+    x * x * (3. - 2. * x)
+}
+
+/// Slope at a point of a bezier curve, taken from its neighbours
+fn bezier_slope(points: &[Vec2], i: usize) -> f32 {
+    let prev = points[i.saturating_sub(1)];
+    let next = points[(i + 1).min(points.len() - 1)];
+    if next.x > prev.x { (next.y - prev.y) / (next.x - prev.x) } else { 0. }
+}
+
+/// Value between points `i` and `i + 1` at `t` in `0.0..=1.0`
+fn interpolate(points: &[Vec2], curve_type: CurveType, i: usize, t: f32) -> f32 {
+    let (p0, p1) = (points[i], points[i + 1]);
+    match curve_type {
+        CurveType::Step => if t < 1. { p0.y } else { p1.y },
+        CurveType::Linear => p0.y + (p1.y - p0.y) * t,
+        CurveType::Smooth => p0.y + (p1.y - p0.y) * smoothstep(t),
+        CurveType::Bezier => {
+            // control points sit a third of the way along the segment, which keeps x linear in t
+            let dx = (p1.x - p0.x) / 3.;
+            let c0 = p0.y + bezier_slope(points, i) * dx;
+            let c1 = p1.y - bezier_slope(points, i + 1) * dx;
+            let s = 1. - t;
+            s * s * s * p0.y + 3. * s * s * t * c0 + 3. * s * t * t * c1 + t * t * t * p1.y
+        }
+    }
+}
+
+/// Sample a curve at `x`. Values before the first or after the last point hold
+/// that point's value, and an empty curve evaluates to zero.
+pub fn evaluate(points: &[Vec2], curve_type: CurveType, x: f32) -> f32 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else { return 0. };
+    if x <= first.x {
+        return first.y;
+    }
+    if x >= last.x {
+        return last.y;
+    }
+    let i = points.partition_point(|p| p.x <= x).saturating_sub(1).min(points.len() - 2);
+    let width = points[i + 1].x - points[i].x;
+    let t = if width > 0. { (x - points[i].x) / width } else { 1. };
+    interpolate(points, curve_type, i, t)
+}
+
+/// Points of the line drawn for a curve, in value space
+fn curve_polyline(points: &[Vec2], curve_type: CurveType) -> Vec<Vec2> {
+    let mut line = points.first().copied().into_iter().collect::<Vec<_>>();
+    for i in 0..points.len().saturating_sub(1) {
+        let (p0, p1) = (points[i], points[i + 1]);
+        match curve_type {
+            CurveType::Step => line.extend([Vec2::new(p1.x, p0.y), p1]),
+            CurveType::Linear => line.push(p1),
+            CurveType::Smooth | CurveType::Bezier => {
+                line.extend((1..=CURVE_SUBDIVISIONS).map(|s| {
+                    let t = s as f32 / CURVE_SUBDIVISIONS as f32;
+                    Vec2::new(p0.x + (p1.x - p0.x) * t, interpolate(points, curve_type, i, t))
+                }));
+            }
+        }
+    }
+    line
+}
+
+fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_squared() > 0. { ((p - a).dot(ab) / ab.length_squared()).clamp(0., 1.) } else { 0. };
+    p.distance(a + ab * t)
+}
+
+/// Mapping between value space and the editor's screen rectangle. Values grow upwards.
+#[derive(Debug, Clone, Copy)]
+struct CurveView {
+    pos: Vec2,
+    size: Vec2,
+    min: Vec2,
+    range: Vec2
+}
+
+impl CurveView {
+    fn new(pos: Vec2, size: Vec2, min: Vec2, max: Vec2) -> Self {
+        let range = (max - min).max(Vec2::splat(f32::EPSILON));
+        Self { pos, size, min, range }
+    }
+
+    fn value_to_screen(&self, value: Vec2) -> Vec2 {
+        let n = (value - self.min) / self.range;
+        Vec2::new(self.pos.x + n.x * self.size.x, self.pos.y + (1. - n.y) * self.size.y)
+    }
+
+    fn screen_to_value(&self, screen: Vec2) -> Vec2 {
+        let n = Vec2::new((screen.x - self.pos.x) / self.size.x, 1. - (screen.y - self.pos.y) / self.size.y);
+        self.min + n * self.range
+    }
+
+    /// Value-space size of a screen-space offset
+    fn screen_to_value_delta(&self, delta: Vec2) -> Vec2 {
+        Vec2::new(delta.x, -delta.y) / self.size * self.range
+    }
+}
+
+fn draw_point(draw_list: &DrawListMut, pos: Vec2, fill: u32, outline: u32) {
+    let offsets = [Vec2::new(-4., 0.), Vec2::new(0., 4.), Vec2::new(4., 0.), Vec2::new(0., -4.)];
+    let diamond = |scale: f32| offsets.iter().map(|o| Into::<[f32; 2]>::into(pos + *o * scale)).collect::<Vec<_>>();
+    draw_list.add_polyline(diamond(1.), fill).filled(true).build();
+    let mut outline_points = diamond(1.);
+    outline_points.push(outline_points[0]);
+    draw_list.add_polyline(outline_points, outline).thickness(1.5).build();
+}
+
+/// Index of a point after another point of the same curve moved from `from` to `to`
+fn shifted_index(index: usize, from: usize, to: usize) -> usize {
+    match index {
+        i if i == from => to,
+        i if from < i && i <= to => i - 1,
+        i if to <= i && i < from => i + 1,
+        i => i
+    }
+}
+
+/// Spacing between grid lines that keeps them at least `min_pixels` apart,
+/// or `None` if the range is too small or too large to draw a grid for
+fn grid_step(range: f32, pixels: f32, min_pixels: f32) -> Option<f32> {
+    let raw = range * min_pixels / pixels.max(1.);
+    if !raw.is_finite() || raw <= 0. {
+        return None;
+    }
+    let magnitude = 10f32.powf(raw.log10().floor());
+    let step = [1., 2., 5., 10.].into_iter().map(|m| m * magnitude).find(|s| *s >= raw).unwrap_or(magnitude * 10.);
+    (step.is_finite() && step > 0.).then_some(step)
+}
+
+/// Values of the grid lines between `min` and `max`
+fn grid_lines(min: f32, max: f32, step: Option<f32>) -> impl Iterator<Item = f32> {
+    let (first, step, count) = match step {
+        Some(step) => ((min / step).ceil() * step, step, MAX_GRID_LINES),
+        None => (0., 0., 0)
+    };
+    (0..count).map(move |i| first + i as f32 * step).take_while(move |v| *v <= max)
+}
+
+fn draw_curve_edit<D: CurveEditDelegate + ?Sized>(delegate: &mut D, ui: &Ui, size: [f32; 2],
+    keymap: &CurveEditKeymap) -> bool {
+    let draw_list = ui.get_window_draw_list();
+    let pos = Vec2::from(ui.cursor_screen_pos());
+    let avail = Vec2::from(ui.content_region_avail());
+    let size = Vec2::new(
+        if size[0] > 0. { size[0] } else { avail.x },
+        if size[1] > 0. { size[1] } else { avail.y }
+    ).max(Vec2::ONE);
+    ui.invisible_button("curveEdit", Into::<[f32; 2]>::into(size));
+    let hovered = ui.is_item_hovered();
+    delegate.get_state_mut().focused = ui.is_window_focused();
+    let mouse = Vec2::from(ui.io().mouse_pos);
+    let mut changed = false;
+
+    // zoom around the mouse and pan with the middle button
+    {
+        let state = delegate.get_state_mut();
+        let view = CurveView::new(pos, size, state.min, state.max);
+        let wheel = ui.io().mouse_wheel;
+        if hovered && wheel.abs() > f32::EPSILON {
+            let center = view.screen_to_value(mouse);
+            let scale = (1. - wheel * 0.05).max(0.05);
+            state.min = center + (state.min - center) * scale;
+            state.max = center + (state.max - center) * scale;
+            // keep enough range to draw a grid and map the mouse back to values
+            let middle = (state.min + state.max) * 0.5;
+            let half = ((state.max - state.min) * 0.5).max(Vec2::splat(MIN_VIEW_SPAN * 0.5));
+            state.min = middle - half;
+            state.max = middle + half;
+        }
+        if hovered && state.interaction == Interaction::None && ui.is_mouse_clicked(MouseButton::Middle) {
+            state.interaction = Interaction::Panning;
+        }
+        if state.interaction == Interaction::Panning {
+            if ui.is_mouse_down(MouseButton::Middle) {
+                let delta = view.screen_to_value_delta(Vec2::from(ui.io().mouse_delta));
+                state.min -= delta;
+                state.max -= delta;
+            } else {
+                state.interaction = Interaction::None;
+            }
+        }
+    }
+    if delegate.get_state().focused && !ui.io().want_text_input && ui.is_key_pressed(keymap.frame_all) {
+        delegate.frame_all();
+    }
+    let view = CurveView::new(pos, size, delegate.get_state().min, delegate.get_state().max);
+
+    // hovered point and curve
+    let visible: Vec<usize> = (0..delegate.get_curve_count()).filter(|c| delegate.is_visible(*c)).collect();
+    let over_point = hovered.then(|| visible.iter().rev().find_map(|c| {
+        delegate.get_points(*c).iter()
+            .position(|p| view.value_to_screen(*p).distance(mouse) < HOVER_DISTANCE)
+            .map(|p| EditPoint { curve_index: *c, point_index: p })
+    })).flatten();
+    let lines: Vec<(usize, Vec<Vec2>)> = visible.iter()
+        .map(|c| {
+            let line = curve_polyline(delegate.get_points(*c), delegate.get_curve_type(*c));
+            (*c, line.into_iter().map(|p| view.value_to_screen(p)).collect())
+        })
+        .collect();
+    let over_curve = if hovered && over_point.is_none() {
+        lines.iter().rev()
+            .find(|(_, line)| line.windows(2).any(|s| distance_to_segment(mouse, s[0], s[1]) < HOVER_DISTANCE))
+            .map(|(c, _)| *c)
+    } else { None };
+
+    // start an interaction
+    let shift = ui.io().key_shift;
+    if hovered && delegate.get_state().interaction == Interaction::None && ui.is_mouse_clicked(MouseButton::Left) {
+        let origin = view.screen_to_value(mouse);
+        if let Some(point) = over_point {
+            let state = delegate.get_state_mut();
+            if shift && state.selection.contains(&point) {
+                state.selection.retain(|p| *p != point);
+            } else {
+                if !shift && !state.selection.contains(&point) {
+                    state.selection.clear();
+                }
+                state.select(point);
+                let points = state.selection.clone().into_iter()
+                    .filter_map(|p| delegate.get_points(p.curve_index).get(p.point_index).map(|v| (p, *v)))
+                    .collect();
+                delegate.get_state_mut().interaction = Interaction::MovingPoints { origin, points };
+                delegate.begin_edit(point.curve_index);
+            }
+        } else if let Some(curve) = over_curve {
+            if ui.is_mouse_double_clicked(MouseButton::Left) {
+                delegate.begin_edit(curve);
+                delegate.add_point(curve, origin);
+                delegate.end_edit();
+                changed = true;
+            } else {
+                let points = delegate.get_points(curve).to_vec();
+                delegate.get_state_mut().interaction = Interaction::MovingCurve { origin, curve, points };
+                delegate.begin_edit(curve);
+            }
+        } else {
+            let state = delegate.get_state_mut();
+            if !shift {
+                state.selection.clear();
+            }
+            state.interaction = Interaction::Selecting { origin: mouse };
+        }
+    }
+
+    // continue or finish the current interaction
+    let mouse_down = ui.is_mouse_down(MouseButton::Left);
+    let mut selection_rect = None;
+    match std::mem::take(&mut delegate.get_state_mut().interaction) {
+        Interaction::MovingPoints { origin, mut points } if mouse_down => {
+            let delta = view.screen_to_value(mouse) - origin;
+            if delta != Vec2::ZERO {
+                // points move one at a time, so follow every selected point as the curve is re-sorted
+                for i in 0..points.len() {
+                    let (point, original) = points[i];
+                    let index = delegate.edit_point(point.curve_index, point.point_index, original + delta);
+                    for (other, _) in points.iter_mut().filter(|(p, _)| p.curve_index == point.curve_index) {
+                        other.point_index = shifted_index(other.point_index, point.point_index, index);
+                    }
+                }
+                delegate.get_state_mut().selection = points.iter().map(|p| p.0).collect();
+                changed = true;
+            }
+            delegate.get_state_mut().interaction = Interaction::MovingPoints { origin, points };
+        },
+        Interaction::MovingCurve { origin, curve, points } if mouse_down => {
+            let delta = view.screen_to_value(mouse) - origin;
+            if delta != Vec2::ZERO {
+                // the curve can be re-sorted after every point, so track where each one went
+                let mut indices: Vec<usize> = (0..points.len()).collect();
+                for (i, original) in points.iter().enumerate() {
+                    let from = indices[i];
+                    let to = delegate.edit_point(curve, from, *original + delta);
+                    for index in indices.iter_mut() {
+                        *index = shifted_index(*index, from, to);
+                    }
+                }
+                changed = true;
+            }
+            delegate.get_state_mut().interaction = Interaction::MovingCurve { origin, curve, points };
+        },
+        Interaction::MovingPoints { .. } | Interaction::MovingCurve { .. } => delegate.end_edit(),
+        Interaction::Selecting { origin } => {
+            let (min, max) = (origin.min(mouse), origin.max(mouse));
+            if mouse_down {
+                selection_rect = Some((min, max));
+                delegate.get_state_mut().interaction = Interaction::Selecting { origin };
+            } else {
+                let inside: Vec<EditPoint> = visible.iter()
+                    .flat_map(|c| delegate.get_points(*c).iter().enumerate()
+                        .filter(|(_, p)| {
+                            let s = view.value_to_screen(**p);
+                            s.cmpge(min).all() && s.cmple(max).all()
+                        })
+                        .map(|(i, _)| EditPoint { curve_index: *c, point_index: i }))
+                    .collect();
+                let state = delegate.get_state_mut();
+                for point in inside {
+                    state.select(point);
+                }
+            }
+        },
+        other => delegate.get_state_mut().interaction = other
+    }
+
+    // draw
+    let rect_max = pos + size;
+    draw_list.with_clip_rect_intersect(Into::<[f32; 2]>::into(pos), Into::<[f32; 2]>::into(rect_max), || {
+        draw_list.add_rect(Into::<[f32; 2]>::into(pos), Into::<[f32; 2]>::into(rect_max), delegate.get_background_color())
+            .filled(true).build();
+        let state = delegate.get_state();
+        let step_x = grid_step(state.max.x - state.min.x, size.x, 50.);
+        let step_y = grid_step(state.max.y - state.min.y, size.y, 30.);
+        for x in grid_lines(state.min.x, state.max.x, step_x) {
+            let sx = view.value_to_screen(Vec2::new(x, 0.)).x;
+            let color = if x.abs() < step_x.unwrap_or(0.) * 0.5 { 0x60ffffff } else { 0x20ffffff };
+            draw_list.add_line([sx, pos.y], [sx, rect_max.y], color).build();
+            draw_list.add_text([sx + 2., rect_max.y - 14.], 0x80ffffff, format!("{}", x));
+        }
+        for y in grid_lines(state.min.y, state.max.y, step_y) {
+            let sy = view.value_to_screen(Vec2::new(0., y)).y;
+            let color = if y.abs() < step_y.unwrap_or(0.) * 0.5 { 0x60ffffff } else { 0x20ffffff };
+            draw_list.add_line([pos.x, sy], [rect_max.x, sy], color).build();
+            draw_list.add_text([pos.x + 2., sy - 14.], 0x80ffffff, format!("{}", y));
+        }
+        for (curve, line) in &lines {
+            if line.len() > 1 {
+                let thickness = if over_curve == Some(*curve) { 2.5 } else { 1.3 };
+                let line = line.iter().map(|p| Into::<[f32; 2]>::into(*p)).collect();
+                draw_list.add_polyline(line, delegate.get_curve_color(*curve)).thickness(thickness).build();
+            }
+            for (i, p) in delegate.get_points(*curve).iter().enumerate() {
+                let point = EditPoint { curve_index: *curve, point_index: i };
+                let fill = if state.selection.contains(&point) { 0xff80b0ff } else { 0xff000000 };
+                let outline = if over_point == Some(point) { 0xffffffff } else { delegate.get_curve_color(*curve) };
+                draw_point(&draw_list, view.value_to_screen(*p), fill, outline);
+            }
+        }
+        if let Some((min, max)) = selection_rect {
+            draw_list.add_rect(Into::<[f32; 2]>::into(min), Into::<[f32; 2]>::into(max), 0x40ff0000).filled(true).build();
+            draw_list.add_rect(Into::<[f32; 2]>::into(min), Into::<[f32; 2]>::into(max), 0xffff0000).build();
+        }
+    });
+    changed
+}
@@ -0,0 +1,1047 @@
+//! Rust port of ImGuizmo's transform manipulator (`ImGuizmo::Manipulate`),
+//! part of the ImGuizmo library (MIT License)
+//! https://github.com/CedricGuillemet/ImGuizmo/blob/master/ImGuizmo.h
+//!
+//! ImGuizmo works on row-major `float[16]` matrices and keeps its state in a
+//! global context. This port uses column-major [`glam::Mat4`] (so `a * b`
+//! applies `b` first) and keeps the state in a [`Gizmo`] owned by the caller.
+//! Any projection works: left or right handed, orthographic, reversed or
+//! OpenGL-style depth.
+
+use bitflags::bitflags;
+use glam::{ Mat4, Vec2, Vec3, Vec4Swizzles };
+use imgui::{ DrawListMut, MouseButton, Ui };
+
+pub mod debug_draw;
+pub mod transform;
+pub mod view_cube;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct GizmoOperation : u32 {
+        const TranslateX = 1 << 0;
+        const TranslateY = 1 << 1;
+        const TranslateZ = 1 << 2;
+        const RotateX = 1 << 3;
+        const RotateY = 1 << 4;
+        const RotateZ = 1 << 5;
+        /// Rotation around the view direction
+        const RotateScreen = 1 << 6;
+        const ScaleX = 1 << 7;
+        const ScaleY = 1 << 8;
+        const ScaleZ = 1 << 9;
+        /// Scale with the corners and edges of the local bounds, see [`GizmoConfig::set_local_bounds`]
+        const Bounds = 1 << 10;
+        /// Scale handles drawn beyond the translation arrows, for the universal gizmo
+        const ScaleXU = 1 << 11;
+        const ScaleYU = 1 << 12;
+        const ScaleZU = 1 << 13;
+
+        const Translate = Self::TranslateX.bits() | Self::TranslateY.bits() | Self::TranslateZ.bits();
+        const Rotate = Self::RotateX.bits() | Self::RotateY.bits() | Self::RotateZ.bits() | Self::RotateScreen.bits();
+        const Scale = Self::ScaleX.bits() | Self::ScaleY.bits() | Self::ScaleZ.bits();
+        const ScaleU = Self::ScaleXU.bits() | Self::ScaleYU.bits() | Self::ScaleZU.bits();
+        const Universal = Self::Translate.bits() | Self::Rotate.bits() | Self::ScaleU.bits();
+    }
+}
+
+impl GizmoOperation {
+    fn translate_axis(axis: usize) -> Self { Self::from_bits_truncate(Self::TranslateX.bits() << axis) }
+    fn rotate_axis(axis: usize) -> Self { Self::from_bits_truncate(Self::RotateX.bits() << axis) }
+    fn scale_axis(axis: usize) -> Self { Self::from_bits_truncate(Self::ScaleX.bits() << axis) }
+    fn scale_u_axis(axis: usize) -> Self { Self::from_bits_truncate(Self::ScaleXU.bits() << axis) }
+}
+
+/// Space the gizmo's axes are aligned to. Scaling is always done in local space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GizmoMode {
+    Local,
+    #[default]
+    World
+}
+
+/// Multiplier applied to the gizmo size for the rotation circles
+const ROTATION_DISPLAY_FACTOR: f32 = 1.2;
+/// Radius of the screen rotation circle, relative to the viewport height
+const SCREEN_ROTATE_SIZE: f32 = 0.06;
+/// Extent of the translation plane handles, relative to the gizmo size
+const QUAD_MIN: f32 = 0.5;
+const QUAD_MAX: f32 = 0.8;
+/// Smallest clip-space length of an axis, and area of a plane, that is still drawn
+const AXIS_LIMIT: f32 = 0.0025;
+const PLANE_LIMIT: f32 = 0.02;
+/// Pixel distance for picking axis lines and rotation circles
+const AXIS_PICK_DISTANCE: f32 = 12.;
+const ROTATION_PICK_DISTANCE: f32 = 8.;
+const MIN_SCALE: f32 = 0.001;
+const ANCHOR_BIG_RADIUS: f32 = 8.;
+const ANCHOR_SMALL_RADIUS: f32 = 6.;
+const ARC_SEGMENTS: usize = 64;
+
+/// Colors and line sizes, matching ImGuizmo's `Style`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GizmoStyle {
+    pub translation_line_thickness: f32,
+    pub translation_line_arrow_size: f32,
+    pub rotation_line_thickness: f32,
+    pub rotation_outer_line_thickness: f32,
+    pub scale_line_thickness: f32,
+    pub scale_line_circle_size: f32,
+    pub center_circle_size: f32,
+    /// X, Y and Z axis colors
+    pub direction_color: [u32; 3],
+    /// Colors of the YZ, ZX and XY planes
+    pub plane_color: [u32; 3],
+    pub selection_color: u32,
+    pub inactive_color: u32,
+    pub translation_line_color: u32,
+    pub scale_line_color: u32,
+    pub rotation_using_border_color: u32,
+    pub rotation_using_fill_color: u32,
+    pub text_color: u32,
+    pub text_shadow_color: u32
+}
+
+impl Default for GizmoStyle {
+    fn default() -> Self {
+        Self {
+            translation_line_thickness: 3.,
+            translation_line_arrow_size: 6.,
+            rotation_line_thickness: 2.,
+            rotation_outer_line_thickness: 3.,
+            scale_line_thickness: 3.,
+            scale_line_circle_size: 6.,
+            center_circle_size: 6.,
+            direction_color: [0xff0000aa, 0xff00aa00, 0xffaa0000],
+            plane_color: [0x610000aa, 0x6100aa00, 0x61aa0000],
+            selection_color: 0x8a1080ff,
+            inactive_color: 0x99999999,
+            translation_line_color: 0xaaaaaaaa,
+            scale_line_color: 0xff404040,
+            rotation_using_border_color: 0xff1080ff,
+            rotation_using_fill_color: 0x801080ff,
+            text_color: 0xffffffff,
+            text_shadow_color: 0xff000000
+        }
+    }
+}
+
+/// Per-call options for [`Gizmo::manipulate`], covering ImGuizmo's `SetRect`,
+/// `SetGizmoSizeClipSpace`, `AllowAxisFlip`, `Enable` and the optional
+/// parameters of `Manipulate`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GizmoConfig {
+    rect: Option<[Vec2; 2]>,
+    snap: Option<Vec3>,
+    local_bounds: Option<[Vec3; 2]>,
+    bounds_snap: Option<Vec3>,
+    size: f32,
+    allow_axis_flip: bool,
+    enabled: bool
+}
+
+impl Default for GizmoConfig {
+    fn default() -> Self {
+        Self {
+            rect: None,
+            snap: None,
+            local_bounds: None,
+            bounds_snap: None,
+            size: 0.1,
+            allow_axis_flip: true,
+            enabled: true
+        }
+    }
+}
+
+impl GizmoConfig {
+    /// Screen rectangle the projection maps to. Defaults to the current window.
+    pub fn set_rect(mut self, pos: Vec2, size: Vec2) -> Self {
+        self.rect = Some([pos, pos + size]);
+        self
+    }
+
+    /// Snap translation to multiples of each component, rotation to multiples of
+    /// `x` degrees and scale factors to multiples of each component. Zero
+    /// components don't snap.
+    pub fn set_snap(mut self, snap: Vec3) -> Self {
+        self.snap = Some(snap);
+        self
+    }
+
+    /// Box in the object's local space that is scaled from its corners and
+    /// edges when the operation includes [`GizmoOperation::Bounds`]
+    pub fn set_local_bounds(mut self, min: Vec3, max: Vec3) -> Self {
+        self.local_bounds = Some([min, max]);
+        self
+    }
+
+    /// Snap the size of the local bounds to multiples of each component
+    pub fn set_bounds_snap(mut self, snap: Vec3) -> Self {
+        self.bounds_snap = Some(snap);
+        self
+    }
+
+    /// Gizmo size in clip space
+    pub fn set_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Flip axes that point away from the camera
+    pub fn set_allow_axis_flip(mut self, allow: bool) -> Self {
+        self.allow_axis_flip = allow;
+        self
+    }
+
+    /// A disabled gizmo is drawn in the inactive color and can't be grabbed
+    pub fn set_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+}
+
+/// Part of the gizmo under the mouse or being dragged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Handle {
+    Translate(usize),
+    /// Plane with the given axis as its normal
+    TranslatePlane(usize),
+    TranslateScreen,
+    Rotate(usize),
+    RotateScreen,
+    Scale(usize),
+    ScaleUniform
+}
+
+impl Handle {
+    fn is_translate(&self) -> bool {
+        matches!(self, Self::Translate(_) | Self::TranslatePlane(_) | Self::TranslateScreen)
+    }
+
+    fn is_rotate(&self) -> bool {
+        matches!(self, Self::Rotate(_) | Self::RotateScreen)
+    }
+
+    fn is_scale(&self) -> bool {
+        matches!(self, Self::Scale(_) | Self::ScaleUniform)
+    }
+}
+
+/// State of a translation, rotation or scale drag
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Drag {
+    handle: Handle,
+    /// Plane the mouse ray is intersected with
+    plane_point: Vec3,
+    plane_normal: Vec3,
+    /// Where the mouse ray first hit the plane
+    plane_origin: Vec3,
+    /// Gizmo position when the drag started
+    matrix_origin: Vec3,
+    /// Offset of `plane_origin` from the gizmo position, in gizmo sizes
+    relative_origin: Vec3,
+    translation_last: Vec3,
+    rotation_vector: Vec3,
+    rotation_angle: f32,
+    scale: Vec3,
+    scale_origin: Vec3,
+    mouse_x_origin: f32,
+    /// Axis directions after flipping, kept for the whole drag
+    axis_dirs: [Vec3; 3]
+}
+
+/// State of a drag on the local bounds
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BoundsDrag {
+    /// World position of the anchor opposite the dragged one
+    pivot: Vec3,
+    anchor: Vec3,
+    plane_normal: Vec3,
+    best_axis: usize,
+    /// One or two local axes being resized
+    axes: [Option<usize>; 2],
+    local_pivot: Vec3,
+    matrix: Mat4
+}
+
+/// Everything ImGuizmo's `ComputeContext` derives from the matrices for one call
+struct Frame {
+    rect: [Vec2; 2],
+    view_proj: Mat4,
+    view: Mat4,
+    /// Gizmo space: the normalized object matrix, or just its position in world mode
+    model: Mat4,
+    model_inverse: Mat4,
+    model_source: Mat4,
+    model_local: Mat4,
+    mvp: Mat4,
+    scale_origin: Vec3,
+    screen_factor: f32,
+    display_ratio: f32,
+    orthographic: bool,
+    eye: Vec3,
+    ray_origin: Vec3,
+    ray_dir: Vec3,
+    /// Direction from the camera towards the gizmo
+    camera_dir: Vec3,
+    mouse: Vec2,
+    center: Vec2,
+    radius_square_center: f32,
+    axis_dirs: [Vec3; 3],
+    axis_visible: [bool; 3],
+    plane_visible: [bool; 3]
+}
+
+fn orthonormalize(matrix: &Mat4) -> Mat4 {
+    Mat4::from_cols(
+        matrix.x_axis.xyz().normalize_or_zero().extend(0.),
+        matrix.y_axis.xyz().normalize_or_zero().extend(0.),
+        matrix.z_axis.xyz().normalize_or_zero().extend(0.),
+        matrix.w_axis
+    )
+}
+
+fn point_on_segment(point: Vec2, a: Vec2, b: Vec2) -> Vec2 {
+    let ab = b - a;
+    let length = ab.length_squared();
+    if length <= f32::EPSILON {
+        return a;
+    }
+    a + ab * ((point - a).dot(ab) / length).clamp(0., 1.)
+}
+
+fn snap_value(value: f32, snap: f32) -> f32 {
+    if snap <= f32::EPSILON { value } else { (value / snap).round() * snap }
+}
+
+fn snap_vec(value: Vec3, snap: Vec3) -> Vec3 {
+    Vec3::new(snap_value(value.x, snap.x), snap_value(value.y, snap.y), snap_value(value.z, snap.z))
+}
+
+impl Frame {
+    fn new(ui: &Ui, view: &Mat4, projection: &Mat4, matrix: &Mat4, mode: GizmoMode,
+        config: &GizmoConfig, axis_dirs: Option<[Vec3; 3]>) -> Self {
+        let rect = config.rect.unwrap_or_else(|| {
+            let pos = Vec2::from(ui.window_pos());
+            [pos, pos + Vec2::from(ui.window_size())]
+        });
+        let model_local = orthonormalize(matrix);
+        let model = match mode {
+            GizmoMode::Local => model_local,
+            GizmoMode::World => Mat4::from_translation(matrix.w_axis.xyz())
+        };
+        let size = (rect[1] - rect[0]).max(Vec2::ONE);
+        let view_proj = *projection * *view;
+        let view_inverse = view.inverse();
+        let mut frame = Self {
+            rect,
+            view_proj,
+            view: *view,
+            model,
+            model_inverse: model.inverse(),
+            model_source: *matrix,
+            model_local,
+            mvp: view_proj * model,
+            scale_origin: Vec3::new(
+                matrix.x_axis.xyz().length(),
+                matrix.y_axis.xyz().length(),
+                matrix.z_axis.xyz().length()
+            ),
+            screen_factor: 1.,
+            display_ratio: size.x / size.y,
+            orthographic: projection.w_axis.w == 1. && projection.z_axis.w == 0.,
+            eye: view_inverse.w_axis.xyz(),
+            ray_origin: Vec3::ZERO,
+            ray_dir: Vec3::Z,
+            camera_dir: Vec3::Z,
+            mouse: Vec2::from(ui.io().mouse_pos),
+            center: Vec2::ZERO,
+            radius_square_center: SCREEN_ROTATE_SIZE * size.y,
+            axis_dirs: [Vec3::X, Vec3::Y, Vec3::Z],
+            axis_visible: [true; 3],
+            plane_visible: [true; 3]
+        };
+        let right = frame.model_inverse.transform_vector3(view_inverse.x_axis.xyz()).normalize_or_zero();
+        let right_length = frame.segment_length(Vec3::ZERO, right);
+        frame.screen_factor = if right_length > f32::EPSILON { config.size / right_length } else { 1. };
+        frame.center = frame.project_local(Vec3::ZERO);
+        (frame.ray_origin, frame.ray_dir) = frame.ray_at(frame.mouse);
+        frame.camera_dir = frame.ray_at(frame.center).1;
+
+        frame.axis_dirs = axis_dirs.unwrap_or_else(|| {
+            std::array::from_fn(|i| {
+                let dir = Vec3::AXES[i];
+                let length = frame.segment_length(Vec3::ZERO, dir);
+                let length_minus = frame.segment_length(Vec3::ZERO, -dir);
+                if config.allow_axis_flip && length < length_minus && (length - length_minus).abs() > f32::EPSILON {
+                    -dir
+                } else { dir }
+            })
+        });
+        for i in 0..3 {
+            let sf = frame.screen_factor;
+            frame.axis_visible[i] = frame.segment_length(Vec3::ZERO, frame.axis_dirs[i] * sf) > AXIS_LIMIT;
+            frame.plane_visible[i] = frame.parallelogram(
+                frame.axis_dirs[(i + 1) % 3] * sf,
+                frame.axis_dirs[(i + 2) % 3] * sf
+            ) > PLANE_LIMIT;
+        }
+        frame
+    }
+
+    fn position(&self) -> Vec3 { self.model.w_axis.xyz() }
+    fn axis(&self, i: usize) -> Vec3 { self.model.col(i).xyz() }
+    /// World direction of a flipped gizmo axis
+    fn axis_dir(&self, i: usize) -> Vec3 { self.model.transform_vector3(self.axis_dirs[i]).normalize_or_zero() }
+
+    fn project(&self, point: Vec3, matrix: &Mat4) -> Vec2 {
+        let clip = *matrix * point.extend(1.);
+        let ndc = if clip.w.abs() > f32::EPSILON { clip.xy() / clip.w } else { clip.xy() };
+        let size = self.rect[1] - self.rect[0];
+        Vec2::new(
+            self.rect[0].x + (ndc.x * 0.5 + 0.5) * size.x,
+            self.rect[0].y + (0.5 - ndc.y * 0.5) * size.y
+        )
+    }
+
+    fn project_world(&self, point: Vec3) -> Vec2 { self.project(point, &self.view_proj) }
+    fn project_local(&self, point: Vec3) -> Vec2 { self.project(point, &self.mvp) }
+
+    /// Clip-space position with the aspect ratio taken out
+    fn clip_xy(&self, point: Vec3) -> Vec2 {
+        let clip = self.mvp * point.extend(1.);
+        let xy = if clip.w.abs() > f32::EPSILON { clip.xy() / clip.w } else { clip.xy() };
+        if self.display_ratio < 1. {
+            Vec2::new(xy.x * self.display_ratio, xy.y)
+        } else {
+            Vec2::new(xy.x, xy.y / self.display_ratio)
+        }
+    }
+
+    fn segment_length(&self, start: Vec3, end: Vec3) -> f32 {
+        (self.clip_xy(end) - self.clip_xy(start)).length()
+    }
+
+    /// Clip-space area of the parallelogram spanned by two local vectors
+    fn parallelogram(&self, a: Vec3, b: Vec3) -> f32 {
+        let origin = self.clip_xy(Vec3::ZERO);
+        let (a, b) = (self.clip_xy(a) - origin, self.clip_xy(b) - origin);
+        a.perp_dot(b).abs()
+    }
+
+    /// World-space ray under a screen position, pointing away from the camera
+    fn ray_at(&self, pos: Vec2) -> (Vec3, Vec3) {
+        let size = self.rect[1] - self.rect[0];
+        let ndc = Vec2::new(
+            (pos.x - self.rect[0].x) / size.x * 2. - 1.,
+            (1. - (pos.y - self.rect[0].y) / size.y) * 2. - 1.
+        );
+        let inverse = self.view_proj.inverse();
+        // both depths are inside the clip range of every depth convention
+        let a = inverse.project_point3(ndc.extend(0.1));
+        let b = inverse.project_point3(ndc.extend(0.9));
+        let (near, far) = if a.distance_squared(self.eye) <= b.distance_squared(self.eye) { (a, b) } else { (b, a) };
+        (near, (far - near).normalize_or_zero())
+    }
+
+    /// Point where the mouse ray crosses a plane
+    fn ray_plane(&self, point: Vec3, normal: Vec3) -> Vec3 {
+        let denom = normal.dot(self.ray_dir);
+        let t = if denom.abs() < f32::EPSILON { -1. } else { normal.dot(point - self.ray_origin) / denom };
+        self.ray_origin + self.ray_dir * t
+    }
+
+    fn in_rect(&self, pos: Vec2) -> bool {
+        pos.cmpge(self.rect[0]).all() && pos.cmple(self.rect[1]).all()
+    }
+
+    /// Normal of a plane containing `axis` that faces the camera as much as possible
+    fn facing_plane_normal(&self, axis: Vec3) -> Vec3 {
+        let normal = axis.cross(axis.cross(self.camera_dir)).normalize_or_zero();
+        if normal == Vec3::ZERO { self.camera_dir } else { normal }
+    }
+
+    fn over_center(&self) -> bool {
+        let offset = (self.mouse - self.center).abs();
+        offset.x <= 10. && offset.y <= 10.
+    }
+
+    fn move_type(&self, op: GizmoOperation) -> Option<Handle> {
+        if op.contains(GizmoOperation::Translate) && self.over_center() {
+            return Some(Handle::TranslateScreen);
+        }
+        let (pos, sf) = (self.position(), self.screen_factor);
+        for i in 0..3 {
+            let dir = self.axis_dir(i);
+            let start = self.project_world(pos + dir * sf * 0.1);
+            let end = self.project_world(pos + dir * sf);
+            if self.axis_visible[i] && op.contains(GizmoOperation::translate_axis(i))
+                && point_on_segment(self.mouse, start, end).distance(self.mouse) < AXIS_PICK_DISTANCE {
+                return Some(Handle::Translate(i));
+            }
+            let plane_ops = GizmoOperation::translate_axis((i + 1) % 3) | GizmoOperation::translate_axis((i + 2) % 3);
+            if self.plane_visible[i] && op.contains(plane_ops) {
+                let local = (self.ray_plane(pos, dir) - pos) / sf;
+                let dx = self.axis_dir((i + 1) % 3).dot(local);
+                let dy = self.axis_dir((i + 2) % 3).dot(local);
+                if (QUAD_MIN..=QUAD_MAX).contains(&dx) && (QUAD_MIN..=QUAD_MAX).contains(&dy) {
+                    return Some(Handle::TranslatePlane(i));
+                }
+            }
+        }
+        None
+    }
+
+    fn scale_type(&self, op: GizmoOperation) -> Option<Handle> {
+        if op.contains(GizmoOperation::Scale) && !op.intersects(GizmoOperation::Translate) && self.over_center() {
+            return Some(Handle::ScaleUniform);
+        }
+        let (pos, sf) = (self.position(), self.screen_factor);
+        (0..3).find(|i| {
+            if !self.axis_visible[*i] || !op.intersects(GizmoOperation::scale_axis(*i) | GizmoOperation::scale_u_axis(*i)) {
+                return false;
+            }
+            let (start, end) = self.scale_handle_range(op, *i);
+            let dir = self.axis_dir(*i);
+            let start = self.project_world(pos + dir * sf * start);
+            let end = self.project_world(pos + dir * sf * end);
+            point_on_segment(self.mouse, start, end).distance(self.mouse) < AXIS_PICK_DISTANCE
+        }).map(Handle::Scale)
+    }
+
+    /// Start and end of a scale handle along its axis, in gizmo sizes. Handles
+    /// sharing an axis with a translation arrow sit beyond its tip.
+    fn scale_handle_range(&self, op: GizmoOperation, axis: usize) -> (f32, f32) {
+        if op.contains(GizmoOperation::translate_axis(axis)) { (1., 1.4) } else { (0.1, 1.) }
+    }
+
+    fn rotate_type(&self, op: GizmoOperation) -> Option<Handle> {
+        if op.contains(GizmoOperation::RotateScreen) {
+            let distance = self.mouse.distance(self.center);
+            if (distance - self.radius_square_center).abs() < 4. {
+                return Some(Handle::RotateScreen);
+            }
+        }
+        let pos = self.position();
+        let model_view_depth = self.view.transform_point3(pos).z.abs();
+        (0..3).find(|i| {
+            if !op.contains(GizmoOperation::rotate_axis(*i)) {
+                return false;
+            }
+            let hit = self.ray_plane(pos, self.axis(*i));
+            // only the half of the circle facing the camera can be picked
+            if !self.orthographic && model_view_depth - self.view.transform_point3(hit).z.abs() < -f32::EPSILON {
+                return false;
+            }
+            let on_circle = self.model_inverse.transform_vector3((hit - pos).normalize_or_zero());
+            let screen = self.project_local(on_circle * ROTATION_DISPLAY_FACTOR * self.screen_factor);
+            screen.distance(self.mouse) < ROTATION_PICK_DISTANCE
+        }).map(Handle::Rotate)
+    }
+
+    /// Signed angle between the drag's start vector and the mouse, around the drag plane's normal
+    fn angle_on_plane(&self, drag: &Drag) -> f32 {
+        let current = (self.ray_plane(drag.plane_point, drag.plane_normal) - self.position()).normalize_or_zero();
+        let source = drag.rotation_vector;
+        drag.plane_normal.dot(source.cross(current)).atan2(source.dot(current))
+    }
+}
+
+/// 3D transform manipulator. Keep one per edited object, or share one between
+/// objects that are never edited at the same time.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Gizmo {
+    style: GizmoStyle,
+    drag: Option<Drag>,
+    bounds_drag: Option<BoundsDrag>,
+    hovered: Option<Handle>,
+    bounds_hovered: bool
+}
+
+impl Gizmo {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn with_style(mut self, style: GizmoStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn get_style(&self) -> &GizmoStyle { &self.style }
+    pub fn get_style_mut(&mut self) -> &mut GizmoStyle { &mut self.style }
+
+    /// Whether any part of the gizmo is being dragged
+    pub fn is_using(&self) -> bool { self.drag.is_some() || self.bounds_drag.is_some() }
+    /// Whether the mouse was over a handle during the last call
+    pub fn is_over(&self) -> bool { self.hovered.is_some() || self.bounds_hovered }
+
+    /// Draw the gizmo for `matrix` and apply any drag to it. Returns the change
+    /// made during this call as a world-space matrix, so that the new matrix is
+    /// `delta * old`, or `None` if the matrix wasn't changed or no delta
+    /// leads to it because the old matrix is singular.
+    #[allow(clippy::too_many_arguments)]
+    pub fn manipulate(&mut self, ui: &Ui, view: &Mat4, projection: &Mat4, operation: GizmoOperation,
+        mode: GizmoMode, matrix: &mut Mat4, config: &GizmoConfig) -> Option<Mat4> {
+        // scaling along world axes would skew the matrix
+        let mode = if operation.intersects(GizmoOperation::Scale) { GizmoMode::Local } else { mode };
+        let frame = Frame::new(ui, view, projection, matrix, mode, config, self.drag.map(|d| d.axis_dirs));
+        // nothing to draw for an object behind a perspective camera
+        let clip = frame.mvp * Vec3::ZERO.extend(1.);
+        if !frame.orthographic && clip.w < 0.001 && !self.is_using() {
+            self.hovered = None;
+            return None;
+        }
+        let old = *matrix;
+        let mut changed = false;
+        self.hovered = None;
+        if config.enabled && self.bounds_drag.is_none() {
+            if self.drag.is_none() {
+                let mouse_over = frame.in_rect(frame.mouse) && ui.is_window_hovered();
+                self.hovered = mouse_over.then(|| {
+                    frame.move_type(operation)
+                        .or_else(|| frame.scale_type(operation))
+                        .or_else(|| frame.rotate_type(operation))
+                }).flatten();
+                let can_activate = ui.is_mouse_clicked(MouseButton::Left)
+                    && !ui.is_any_item_hovered() && !ui.is_any_item_active();
+                if let (Some(handle), true) = (self.hovered, can_activate) {
+                    self.drag = Some(self.begin_drag(&frame, handle));
+                }
+            }
+            if let Some(mut drag) = self.drag {
+                changed = if drag.handle.is_translate() {
+                    self.drag_translation(&frame, &mut drag, mode, matrix, config)
+                } else if drag.handle.is_rotate() {
+                    self.drag_rotation(&frame, &mut drag, matrix, config)
+                } else {
+                    self.drag_scale(&frame, &mut drag, matrix, config)
+                };
+                self.drag = ui.is_mouse_down(MouseButton::Left).then_some(drag);
+            }
+        }
+        let draw_list = ui.get_window_draw_list();
+        let rect = (Into::<[f32; 2]>::into(frame.rect[0]), Into::<[f32; 2]>::into(frame.rect[1]));
+        draw_list.with_clip_rect_intersect(rect.0, rect.1, || {
+            if let (Some(bounds), true, None) = (config.local_bounds, operation.contains(GizmoOperation::Bounds), self.drag) {
+                changed |= self.handle_bounds(ui, &draw_list, &frame, bounds, matrix, config);
+            }
+            if self.bounds_drag.is_none() {
+                self.draw_rotation(&draw_list, &frame, operation, config);
+                self.draw_translation(&draw_list, &frame, operation, config);
+                self.draw_scale(&draw_list, &frame, operation, config);
+            }
+        });
+        // the frame the mouse is released on can rewrite the matrix without moving it
+        if !changed || *matrix == old {
+            return None;
+        }
+        // a singular matrix has no inverse to build the delta from
+        let delta = *matrix * old.inverse();
+        (delta.is_finite() && !delta.abs_diff_eq(Mat4::IDENTITY, f32::EPSILON)).then_some(delta)
+    }
+
+    fn begin_drag(&self, frame: &Frame, handle: Handle) -> Drag {
+        let pos = frame.position();
+        let plane_normal = match handle {
+            Handle::Translate(i) | Handle::Scale(i) => frame.facing_plane_normal(frame.axis(i)),
+            Handle::TranslatePlane(i) | Handle::Rotate(i) => frame.axis(i),
+            Handle::TranslateScreen | Handle::RotateScreen | Handle::ScaleUniform => -frame.camera_dir
+        };
+        let plane_origin = frame.ray_plane(pos, plane_normal);
+        Drag {
+            handle,
+            plane_point: pos,
+            plane_normal,
+            plane_origin,
+            matrix_origin: pos,
+            relative_origin: (plane_origin - pos) / frame.screen_factor,
+            translation_last: Vec3::ZERO,
+            rotation_vector: (plane_origin - pos).normalize_or_zero(),
+            rotation_angle: 0.,
+            scale: Vec3::ONE,
+            scale_origin: frame.scale_origin,
+            mouse_x_origin: frame.mouse.x,
+            axis_dirs: frame.axis_dirs
+        }
+    }
+
+    fn drag_translation(&self, frame: &Frame, drag: &mut Drag, mode: GizmoMode, matrix: &mut Mat4,
+        config: &GizmoConfig) -> bool {
+        let pos = frame.position();
+        let new_origin = frame.ray_plane(drag.plane_point, drag.plane_normal) - drag.relative_origin * frame.screen_factor;
+        let mut delta = new_origin - pos;
+        if let Handle::Translate(i) = drag.handle {
+            let axis = frame.axis(i);
+            delta = axis * axis.dot(delta);
+        }
+        if let Some(snap) = config.snap {
+            let mut cumulative = pos + delta - drag.matrix_origin;
+            if mode == GizmoMode::Local {
+                let basis = orthonormalize(&frame.model_source);
+                cumulative = basis.transform_vector3(snap_vec(basis.inverse().transform_vector3(cumulative), snap));
+            } else {
+                cumulative = snap_vec(cumulative, snap);
+            }
+            delta = drag.matrix_origin + cumulative - pos;
+        }
+        let changed = delta != drag.translation_last;
+        drag.translation_last = delta;
+        if changed {
+            *matrix = Mat4::from_translation(delta) * frame.model_source;
+        }
+        changed
+    }
+
+    fn drag_rotation(&self, frame: &Frame, drag: &mut Drag, matrix: &mut Mat4, config: &GizmoConfig) -> bool {
+        let mut angle = frame.angle_on_plane(drag);
+        if let Some(snap) = config.snap {
+            angle = snap_value(angle, snap.x.to_radians());
+        }
+        let delta_angle = angle - drag.rotation_angle;
+        if delta_angle == 0. {
+            return false;
+        }
+        drag.rotation_angle = angle;
+        let pos = frame.model_source.w_axis.xyz();
+        let rotation = Mat4::from_axis_angle(drag.plane_normal.normalize(), delta_angle);
+        *matrix = Mat4::from_translation(pos) * rotation * Mat4::from_translation(-pos) * frame.model_source;
+        true
+    }
+
+    fn drag_scale(&self, frame: &Frame, drag: &mut Drag, matrix: &mut Mat4, config: &GizmoConfig) -> bool {
+        let pos = frame.position();
+        let new_origin = frame.ray_plane(drag.plane_point, drag.plane_normal) - drag.relative_origin * frame.screen_factor;
+        let mut scale = match drag.handle {
+            Handle::Scale(i) => {
+                let axis = frame.axis(i);
+                let base = drag.plane_origin - pos;
+                let moved = axis * axis.dot(new_origin - pos);
+                let reference = axis.dot(base);
+                let mut scale = drag.scale;
+                if reference.abs() > f32::EPSILON {
+                    scale[i] = axis.dot(base + moved) / reference;
+                }
+                scale
+            },
+            _ => Vec3::splat(1. + (frame.mouse.x - drag.mouse_x_origin) * 0.01)
+        };
+        if let Some(snap) = config.snap {
+            scale = snap_vec(scale, snap);
+        }
+        let scale = scale.max(Vec3::splat(MIN_SCALE));
+        if scale == drag.scale {
+            return false;
+        }
+        drag.scale = scale;
+        *matrix = frame.model_local * Mat4::from_scale(scale * drag.scale_origin);
+        true
+    }
+
+    fn handle_color(&self, handle: Handle, normal: u32, config: &GizmoConfig) -> u32 {
+        if !config.enabled {
+            self.style.inactive_color
+        } else if self.hovered == Some(handle) || self.drag.is_some_and(|d| d.handle == handle) {
+            self.style.selection_color
+        } else { normal }
+    }
+
+    fn draw_text(&self, draw_list: &DrawListMut, pos: Vec2, text: &str) {
+        draw_list.add_text(Into::<[f32; 2]>::into(pos + Vec2::new(16., 16.)), self.style.text_shadow_color, text);
+        draw_list.add_text(Into::<[f32; 2]>::into(pos + Vec2::new(15., 15.)), self.style.text_color, text);
+    }
+
+    fn draw_translation(&self, draw_list: &DrawListMut, frame: &Frame, op: GizmoOperation, config: &GizmoConfig) {
+        if !op.intersects(GizmoOperation::Translate) {
+            return;
+        }
+        let sf = frame.screen_factor;
+        let style = &self.style;
+        for i in 0..3 {
+            let plane_ops = GizmoOperation::translate_axis((i + 1) % 3) | GizmoOperation::translate_axis((i + 2) % 3);
+            if frame.plane_visible[i] && op.contains(plane_ops) {
+                let (u, v) = (frame.axis_dirs[(i + 1) % 3], frame.axis_dirs[(i + 2) % 3]);
+                let quad: Vec<[f32; 2]> = [(QUAD_MIN, QUAD_MIN), (QUAD_MIN, QUAD_MAX), (QUAD_MAX, QUAD_MAX), (QUAD_MAX, QUAD_MIN)]
+                    .into_iter()
+                    .map(|(a, b)| frame.project_local((u * a + v * b) * sf).into())
+                    .collect();
+                let color = self.handle_color(Handle::TranslatePlane(i), style.plane_color[i], config);
+                let mut outline = quad.clone();
+                outline.push(quad[0]);
+                draw_list.add_polyline(quad, color).filled(true).build();
+                draw_list.add_polyline(outline, style.direction_color[i] | 0xff000000).thickness(1.).build();
+            }
+        }
+        for i in 0..3 {
+            if !frame.axis_visible[i] || !op.contains(GizmoOperation::translate_axis(i)) {
+                continue;
+            }
+            let color = self.handle_color(Handle::Translate(i), style.direction_color[i], config);
+            let base = frame.project_local(frame.axis_dirs[i] * sf * 0.1);
+            let end = frame.project_local(frame.axis_dirs[i] * sf);
+            draw_list.add_line(Into::<[f32; 2]>::into(base), Into::<[f32; 2]>::into(end), color)
+                .thickness(style.translation_line_thickness).build();
+            let dir = (frame.center - end).normalize_or_zero() * style.translation_line_arrow_size;
+            let ortho = Vec2::new(dir.y, -dir.x);
+            let a = end + dir;
+            draw_list.add_triangle(
+                Into::<[f32; 2]>::into(end - dir),
+                Into::<[f32; 2]>::into(a + ortho),
+                Into::<[f32; 2]>::into(a - ortho),
+                color
+            ).filled(true).build();
+        }
+        let center_color = self.handle_color(Handle::TranslateScreen, 0xffffffff, config);
+        draw_list.add_circle(Into::<[f32; 2]>::into(frame.center), style.center_circle_size, center_color)
+            .filled(true).num_segments(32).build();
+        if let Some(drag) = self.drag.filter(|d| d.handle.is_translate()) {
+            let source = frame.project_world(drag.matrix_origin);
+            let destination = frame.project_world(frame.position());
+            let offset = (destination - source).normalize_or_zero() * 5.;
+            draw_list.add_circle(Into::<[f32; 2]>::into(source), 6., style.translation_line_color).build();
+            draw_list.add_circle(Into::<[f32; 2]>::into(destination), 6., style.translation_line_color).build();
+            draw_list.add_line(Into::<[f32; 2]>::into(source + offset), Into::<[f32; 2]>::into(destination - offset),
+                style.translation_line_color).thickness(2.).build();
+            let moved = frame.position() - drag.matrix_origin;
+            self.draw_text(draw_list, destination, &format!("X : {:5.3} Y : {:5.3} Z : {:5.3}", moved.x, moved.y, moved.z));
+        }
+    }
+
+    fn draw_rotation(&self, draw_list: &DrawListMut, frame: &Frame, op: GizmoOperation, config: &GizmoConfig) {
+        if !op.intersects(GizmoOperation::Rotate) {
+            return;
+        }
+        let style = &self.style;
+        let radius = frame.screen_factor * ROTATION_DISPLAY_FACTOR;
+        let has_screen = op.contains(GizmoOperation::RotateScreen);
+        let towards_camera = -frame.model_inverse.transform_vector3(frame.camera_dir).normalize_or_zero();
+        for i in 0..3 {
+            if !op.contains(GizmoOperation::rotate_axis(i)) {
+                continue;
+            }
+            let (u, v) = ((i + 1) % 3, (i + 2) % 3);
+            let using = self.drag.is_some_and(|d| d.handle == Handle::Rotate(i));
+            // half circles facing the camera, unless there's nothing else to pick or this one is in use
+            let turns = if has_screen && !using { 1 } else { 2 };
+            let start = towards_camera[v].atan2(towards_camera[u]) - std::f32::consts::FRAC_PI_2;
+            let segments = turns * ARC_SEGMENTS;
+            let points: Vec<[f32; 2]> = (0..=segments)
+                .map(|k| {
+                    let angle = start + turns as f32 * std::f32::consts::PI * k as f32 / segments as f32;
+                    let local = (Vec3::AXES[u] * angle.cos() + Vec3::AXES[v] * angle.sin()) * radius;
+                    frame.project_local(local).into()
+                })
+                .collect();
+            let color = self.handle_color(Handle::Rotate(i), style.direction_color[i], config);
+            draw_list.add_polyline(points, color).thickness(style.rotation_line_thickness).build();
+        }
+        if has_screen {
+            let color = self.handle_color(Handle::RotateScreen, 0xffffffff, config);
+            draw_list.add_circle(Into::<[f32; 2]>::into(frame.center), frame.radius_square_center, color)
+                .num_segments(ARC_SEGMENTS as u32).thickness(style.rotation_outer_line_thickness).build();
+        }
+        if let Some(drag) = self.drag.filter(|d| d.handle.is_rotate()) {
+            let pos = frame.position();
+            let mut fan: Vec<[f32; 2]> = vec![frame.center.into()];
+            fan.extend((0..ARC_SEGMENTS).map(|k| {
+                let angle = drag.rotation_angle * k as f32 / (ARC_SEGMENTS - 1) as f32;
+                let rotated = Mat4::from_axis_angle(drag.plane_normal.normalize(), angle).transform_vector3(drag.rotation_vector);
+                Into::<[f32; 2]>::into(frame.project_world(pos + rotated * radius))
+            }));
+            let mut outline = fan.clone();
+            outline.push(fan[0]);
+            draw_list.add_polyline(fan, style.rotation_using_fill_color).filled(true).build();
+            draw_list.add_polyline(outline, style.rotation_using_border_color).thickness(2.).build();
+            let axis = match drag.handle {
+                Handle::Rotate(0) => "X",
+                Handle::Rotate(1) => "Y",
+                Handle::Rotate(_) => "Z",
+                _ => "Screen"
+            };
+            let degrees = drag.rotation_angle.to_degrees();
+            self.draw_text(draw_list, frame.center, &format!("{} : {:5.2} deg {:5.2} rad", axis, degrees, drag.rotation_angle));
+        }
+    }
+
+    fn draw_scale(&self, draw_list: &DrawListMut, frame: &Frame, op: GizmoOperation, config: &GizmoConfig) {
+        if !op.intersects(GizmoOperation::Scale | GizmoOperation::ScaleU) {
+            return;
+        }
+        let sf = frame.screen_factor;
+        let style = &self.style;
+        let scaling = self.drag.filter(|d| d.handle.is_scale());
+        for i in 0..3 {
+            if !frame.axis_visible[i] || !op.intersects(GizmoOperation::scale_axis(i) | GizmoOperation::scale_u_axis(i)) {
+                continue;
+            }
+            let color = self.handle_color(Handle::Scale(i), style.direction_color[i], config);
+            let (start, end) = frame.scale_handle_range(op, i);
+            let display = scaling.map_or(1., |d| d.scale[i]);
+            let dir = frame.axis_dirs[i];
+            if start < 1. {
+                let base = frame.project_local(dir * sf * start);
+                if scaling.is_some() {
+                    let original = frame.project_local(dir * sf * end);
+                    draw_list.add_line(Into::<[f32; 2]>::into(base), Into::<[f32; 2]>::into(original), style.scale_line_color)
+                        .thickness(style.scale_line_thickness).build();
+                }
+                let tip = frame.project_local(dir * sf * end * display);
+                draw_list.add_line(Into::<[f32; 2]>::into(base), Into::<[f32; 2]>::into(tip), color)
+                    .thickness(style.scale_line_thickness).build();
+                draw_list.add_circle(Into::<[f32; 2]>::into(tip), style.scale_line_circle_size, color).filled(true).build();
+            } else {
+                let tip = frame.project_local(dir * sf * end);
+                draw_list.add_circle(Into::<[f32; 2]>::into(tip), style.scale_line_circle_size, color).filled(true).build();
+            }
+        }
+        if op.contains(GizmoOperation::Scale) && !op.intersects(GizmoOperation::Translate) {
+            let color = self.handle_color(Handle::ScaleUniform, 0xffffffff, config);
+            draw_list.add_circle(Into::<[f32; 2]>::into(frame.center), style.center_circle_size, color)
+                .filled(true).num_segments(32).build();
+        }
+        if let Some(drag) = scaling {
+            let text = match drag.handle {
+                Handle::Scale(0) => format!("X : {:5.2}", drag.scale.x),
+                Handle::Scale(1) => format!("Y : {:5.2}", drag.scale.y),
+                Handle::Scale(_) => format!("Z : {:5.2}", drag.scale.z),
+                _ => format!("XYZ : {:5.2}", drag.scale.x)
+            };
+            self.draw_text(draw_list, frame.center, &text);
+        }
+    }
+
+    /// Port of ImGuizmo's `HandleAndDrawLocalBounds`
+    #[allow(clippy::too_many_arguments)]
+    fn handle_bounds(&mut self, ui: &Ui, draw_list: &DrawListMut, frame: &Frame,
+        bounds: [Vec3; 2], matrix: &mut Mat4, config: &GizmoConfig) -> bool {
+        let source = frame.model_source;
+        let bounds_mvp = frame.view_proj * source;
+        // planes of the box that face the camera, best first
+        let mut axes: Vec<(usize, Vec3)> = match self.bounds_drag {
+            Some(drag) => vec![(drag.best_axis, drag.plane_normal)],
+            None => {
+                let to_eye = (frame.eye - source.w_axis.xyz()).normalize_or_zero();
+                let mut facing: Vec<(usize, Vec3, f32)> = (0..3)
+                    .map(|i| {
+                        let normal = source.transform_vector3(Vec3::AXES[i]).normalize_or_zero();
+                        let facing = if frame.orthographic { frame.camera_dir.dot(normal) } else { to_eye.dot(normal) };
+                        (i, normal, facing.abs())
+                    })
+                    .collect();
+                facing.sort_by(|a, b| b.2.total_cmp(&a.2));
+                let best = facing[0];
+                let mut axes: Vec<(usize, Vec3)> = facing.into_iter().filter(|f| f.2 >= 0.1).map(|f| (f.0, f.1)).collect();
+                if axes.is_empty() {
+                    axes.push((best.0, best.1));
+                }
+                axes
+            }
+        };
+        let gizmo_hovered = self.hovered.is_some();
+        let can_activate = config.enabled && ui.is_mouse_clicked(MouseButton::Left)
+            && !ui.is_any_item_hovered() && !ui.is_any_item_active();
+        let anchor_alpha = if config.enabled { 0xff000000 } else { 0x80000000 };
+        self.bounds_hovered = false;
+        for (best_axis, plane_normal) in axes.drain(..) {
+            let (second, third) = ((best_axis + 1) % 3, (best_axis + 2) % 3);
+            let corners: [Vec3; 4] = std::array::from_fn(|i| {
+                let mut corner = Vec3::ZERO;
+                corner[second] = bounds[i >> 1][second];
+                corner[third] = bounds[(i >> 1) ^ (i & 1)][third];
+                corner
+            });
+            for i in 0..4 {
+                let bound1 = frame.project(corners[i], &bounds_mvp);
+                let bound2 = frame.project(corners[(i + 1) % 4], &bounds_mvp);
+                if !frame.in_rect(bound1) || !frame.in_rect(bound2) {
+                    continue;
+                }
+                // dashed edge
+                let steps = ((bound1.distance(bound2) / 10.) as usize).min(1000);
+                for j in 0..steps {
+                    let t1 = j as f32 / steps as f32;
+                    let t2 = t1 + 0.5 / steps as f32;
+                    draw_list.add_line(
+                        Into::<[f32; 2]>::into(bound1.lerp(bound2, t1)),
+                        Into::<[f32; 2]>::into(bound1.lerp(bound2, t2)),
+                        anchor_alpha
+                    ).thickness(2.).build();
+                }
+                let mid_point = (corners[i] + corners[(i + 1) % 4]) * 0.5;
+                let mid_bound = frame.project(mid_point, &bounds_mvp);
+                let over_big = !gizmo_hovered && bound1.distance(frame.mouse) <= ANCHOR_BIG_RADIUS;
+                let over_small = !gizmo_hovered && mid_bound.distance(frame.mouse) <= ANCHOR_BIG_RADIUS;
+                self.bounds_hovered |= over_big || over_small;
+                let big_color = if over_big { self.style.selection_color } else { 0x00aaaaaa | anchor_alpha };
+                let small_color = if over_small { self.style.selection_color } else { 0x00aaaaaa | anchor_alpha };
+                draw_list.add_circle(Into::<[f32; 2]>::into(bound1), ANCHOR_BIG_RADIUS, 0xff000000).filled(true).build();
+                draw_list.add_circle(Into::<[f32; 2]>::into(bound1), ANCHOR_BIG_RADIUS - 1.2, big_color).filled(true).build();
+                draw_list.add_circle(Into::<[f32; 2]>::into(mid_bound), ANCHOR_SMALL_RADIUS, 0xff000000).filled(true).build();
+                draw_list.add_circle(Into::<[f32; 2]>::into(mid_bound), ANCHOR_SMALL_RADIUS - 1.2, small_color).filled(true).build();
+                if self.bounds_drag.is_some() || !can_activate {
+                    continue;
+                }
+                let opposite = corners[(i + 2) % 4];
+                if over_big {
+                    let mut local_pivot = Vec3::ZERO;
+                    local_pivot[second] = opposite[second];
+                    local_pivot[third] = opposite[third];
+                    let anchor = source.transform_point3(corners[i]);
+                    self.bounds_drag = Some(BoundsDrag {
+                        pivot: source.transform_point3(opposite),
+                        anchor,
+                        plane_normal,
+                        best_axis,
+                        axes: [Some(second), Some(third)],
+                        local_pivot,
+                        matrix: source
+                    });
+                } else if over_small {
+                    let mid_opposite = (opposite + corners[(i + 3) % 4]) * 0.5;
+                    let axis = [second, third][i % 2];
+                    let mut local_pivot = Vec3::ZERO;
+                    local_pivot[axis] = opposite[axis];
+                    self.bounds_drag = Some(BoundsDrag {
+                        pivot: source.transform_point3(mid_opposite),
+                        anchor: source.transform_point3(mid_point),
+                        plane_normal,
+                        best_axis,
+                        axes: [Some(axis), None],
+                        local_pivot,
+                        matrix: source
+                    });
+                }
+            }
+            if self.bounds_drag.is_some() {
+                break;
+            }
+        }
+        let Some(drag) = self.bounds_drag else { return false };
+        let hit = frame.ray_plane(drag.anchor, drag.plane_normal);
+        let delta = (hit - drag.pivot).abs();
+        let reference = (drag.anchor - drag.pivot).abs();
+        let mut scale = Vec3::ONE;
+        for axis in drag.axes.into_iter().flatten() {
+            let axis_dir = drag.matrix.col(axis).xyz().abs();
+            let along = axis_dir.dot(reference);
+            let size = bounds[1][axis] - bounds[0][axis];
+            let mut ratio = if along > f32::EPSILON { axis_dir.dot(delta) / along } else { 1. };
+            if let Some(snap) = config.bounds_snap {
+                let length = snap_value(size * ratio, snap[axis]);
+                if size > f32::EPSILON {
+                    ratio = length / size;
+                }
+            }
+            scale[axis] *= ratio;
+        }
+        let new = drag.matrix * Mat4::from_translation(drag.local_pivot) * Mat4::from_scale(scale)
+            * Mat4::from_translation(-drag.local_pivot);
+        let changed = new != *matrix;
+        *matrix = new;
+        let size = (bounds[1] - bounds[0]) * scale;
+        self.draw_text(draw_list, frame.mouse, &format!("X: {:.2} Y: {:.2} Z: {:.2}", size.x, size.y, size.z));
+        if !ui.is_mouse_down(MouseButton::Left) {
+            self.bounds_drag = None;
+        }
+        changed
+    }
+}
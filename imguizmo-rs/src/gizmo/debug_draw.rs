@@ -0,0 +1,274 @@
+//! Port of ImGuizmo's `DrawGrid` and `DrawCubes`, along with a few more
+//! primitives for drawing world-space debug shapes over a 3D viewport.
+//!
+//! Everything is projected on the CPU and drawn with the window draw list,
+//! so it is always on top of the scene. Lines are clipped against a plane
+//! just in front of the camera so segments passing behind it don't wrap
+//! around the screen.
+
+use glam::{ Mat4, Vec2, Vec3, Vec4, Vec4Swizzles };
+use imgui::{ DrawListMut, Ui };
+use super::GizmoStyle;
+
+/// Default distance of the clipping plane from the camera
+const NEAR_CLIP: f32 = 0.01;
+const CIRCLE_SEGMENTS: usize = 32;
+const ARROW_SIZE: f32 = 6.;
+/// Grid line colors and thicknesses for regular lines, every 10th line and the axes
+const GRID_COLORS: [u32; 3] = [0xff808080, 0xff909090, 0xff404040];
+const GRID_THICKNESS: [f32; 3] = [1., 1.5, 2.3];
+const BOX_EDGES: [(usize, usize); 12] = [
+    (0, 1), (1, 3), (3, 2), (2, 0),
+    (4, 5), (5, 7), (7, 6), (6, 4),
+    (0, 4), (1, 5), (2, 6), (3, 7)
+];
+
+/// Draws world-space shapes for one viewport. Holds the window draw list, so
+/// it should be dropped before drawing anything else in the same window.
+pub struct DebugDraw<'ui> {
+    draw_list: DrawListMut<'ui>,
+    style: GizmoStyle,
+    view_proj: Mat4,
+    eye: Vec3,
+    /// Direction the camera looks towards
+    forward: Vec3,
+    orthographic: bool,
+    rect: [Vec2; 2],
+    near_clip: f32
+}
+
+impl<'ui> DebugDraw<'ui> {
+    /// Draw over the current window, like [`super::Gizmo::manipulate`] does
+    /// when no rectangle is set
+    pub fn new(ui: &'ui Ui, view: &Mat4, projection: &Mat4) -> Self {
+        let pos = Vec2::from(ui.window_pos());
+        let view_proj = *projection * *view;
+        let eye = view.inverse().w_axis.xyz();
+        let mut draw = Self {
+            draw_list: ui.get_window_draw_list(),
+            style: GizmoStyle::default(),
+            view_proj,
+            eye,
+            forward: Vec3::NEG_Z,
+            orthographic: projection.w_axis.w == 1. && projection.z_axis.w == 0.,
+            rect: [pos, pos + Vec2::from(ui.window_size())],
+            near_clip: NEAR_CLIP
+        };
+        let inverse = view_proj.inverse();
+        // both depths are inside the clip range of every depth convention
+        let (a, b) = (inverse.project_point3(Vec3::new(0., 0., 0.1)), inverse.project_point3(Vec3::new(0., 0., 0.9)));
+        let (near, far) = if a.distance_squared(eye) <= b.distance_squared(eye) { (a, b) } else { (b, a) };
+        draw.forward = (far - near).normalize_or_zero();
+        draw
+    }
+
+    /// Viewport rectangle in screen space, for ImGuizmo's `SetRect`
+    pub fn set_rect(mut self, pos: Vec2, size: Vec2) -> Self {
+        self.rect = [pos, pos + size];
+        self
+    }
+
+    /// Cube face colors are taken from the style's axis colors
+    pub fn with_style(mut self, style: GizmoStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Distance in front of the camera at which lines are clipped. Unused
+    /// with orthographic projections.
+    pub fn set_near_clip(mut self, distance: f32) -> Self {
+        self.near_clip = distance;
+        self
+    }
+
+    fn clip(&self, point: Vec3) -> Vec4 { self.view_proj * point.extend(1.) }
+
+    fn is_clipped(&self, clip: Vec4) -> bool { !self.orthographic && clip.w < self.near_clip }
+
+    fn to_screen(&self, clip: Vec4) -> Vec2 {
+        let ndc = if clip.w.abs() > f32::EPSILON { clip.xy() / clip.w } else { clip.xy() };
+        let size = self.rect[1] - self.rect[0];
+        Vec2::new(
+            self.rect[0].x + (ndc.x * 0.5 + 0.5) * size.x,
+            self.rect[0].y + (0.5 - ndc.y * 0.5) * size.y
+        )
+    }
+
+    /// Screen position of a world point, or `None` if it is behind the clipping plane
+    pub fn project(&self, point: Vec3) -> Option<Vec2> {
+        let clip = self.clip(point);
+        (!self.is_clipped(clip)).then(|| self.to_screen(clip))
+    }
+
+    /// Screen positions of a segment after clipping, or `None` if it is
+    /// entirely behind the clipping plane
+    fn project_segment(&self, a: Vec3, b: Vec3) -> Option<(Vec2, Vec2)> {
+        let (mut ca, mut cb) = (self.clip(a), self.clip(b));
+        if !self.orthographic {
+            let (da, db) = (ca.w - self.near_clip, cb.w - self.near_clip);
+            if da < 0. && db < 0. {
+                return None;
+            }
+            if da < 0. {
+                ca = ca.lerp(cb, da / (da - db));
+            } else if db < 0. {
+                cb = cb.lerp(ca, db / (db - da));
+            }
+        }
+        Some((self.to_screen(ca), self.to_screen(cb)))
+    }
+
+    pub fn line(&self, a: Vec3, b: Vec3, col: u32, thickness: f32) {
+        if let Some((a, b)) = self.project_segment(a, b) {
+            self.draw_list.add_line(Into::<[f32; 2]>::into(a), Into::<[f32; 2]>::into(b), col)
+                .thickness(thickness).build();
+        }
+    }
+
+    /// Connected line segments, closed back to the first point if `closed` is set
+    pub fn polyline(&self, points: &[Vec3], closed: bool, col: u32, thickness: f32) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], col, thickness);
+        }
+        if let (true, [first, .., last]) = (closed, points) {
+            self.line(*last, *first, col, thickness);
+        }
+    }
+
+    fn box_edges(&self, corners: [Vec3; 8], col: u32, thickness: f32) {
+        for (a, b) in BOX_EDGES {
+            self.line(corners[a], corners[b], col, thickness);
+        }
+    }
+
+    /// Axis-aligned box between two corners
+    pub fn aabb(&self, min: Vec3, max: Vec3, col: u32, thickness: f32) {
+        self.box_edges(std::array::from_fn(|i| Vec3::select(
+            glam::BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0), max, min
+        )), col, thickness);
+    }
+
+    /// Box spanning -1 to 1 on each axis of `matrix`, scaled by `half_extents`
+    pub fn obb(&self, matrix: &Mat4, half_extents: Vec3, col: u32, thickness: f32) {
+        self.box_edges(std::array::from_fn(|i| matrix.transform_point3(Vec3::new(
+            if i & 1 != 0 { 1. } else { -1. },
+            if i & 2 != 0 { 1. } else { -1. },
+            if i & 4 != 0 { 1. } else { -1. }
+        ) * half_extents)), col, thickness);
+    }
+
+    /// Circle around `center` in the plane with the given normal
+    pub fn circle(&self, center: Vec3, normal: Vec3, radius: f32, col: u32, thickness: f32) {
+        let (u, v) = normal.normalize_or(Vec3::Y).any_orthonormal_pair();
+        let points: Vec<_> = (0..CIRCLE_SEGMENTS).map(|i| {
+            let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + (u * angle.cos() + v * angle.sin()) * radius
+        }).collect();
+        self.polyline(&points, true, col, thickness);
+    }
+
+    /// Circles in the three axis planes, and the sphere's outline as seen from the camera
+    pub fn sphere(&self, center: Vec3, radius: f32, col: u32, thickness: f32) {
+        for axis in Vec3::AXES {
+            self.circle(center, axis, radius, col, thickness);
+        }
+        let (to_center, distance) = if self.orthographic {
+            (self.forward, f32::INFINITY)
+        } else {
+            let offset = center - self.eye;
+            (offset.normalize_or_zero(), offset.length())
+        };
+        // the outline is the circle where the view rays touch the sphere
+        if distance > radius && to_center != Vec3::ZERO {
+            let (offset, outline_radius) = if distance.is_finite() {
+                (radius * radius / distance, radius * (distance * distance - radius * radius).sqrt() / distance)
+            } else { (0., radius) };
+            self.circle(center - to_center * offset, to_center, outline_radius, col, thickness);
+        }
+    }
+
+    /// Line with a filled head at `to`. The head is only drawn when `to` is in front of the camera.
+    pub fn arrow(&self, from: Vec3, to: Vec3, col: u32, thickness: f32) {
+        let Some((start, end)) = self.project_segment(from, to) else { return };
+        self.draw_list.add_line(Into::<[f32; 2]>::into(start), Into::<[f32; 2]>::into(end), col)
+            .thickness(thickness).build();
+        if self.is_clipped(self.clip(to)) {
+            return;
+        }
+        let dir = (start - end).normalize_or_zero() * (ARROW_SIZE + thickness);
+        let ortho = Vec2::new(dir.y, -dir.x) * 0.5;
+        let base = end + dir;
+        self.draw_list.add_triangle(
+            Into::<[f32; 2]>::into(end),
+            Into::<[f32; 2]>::into(base + ortho),
+            Into::<[f32; 2]>::into(base - ortho),
+            col
+        ).filled(true).build();
+    }
+
+    /// Edges of the volume seen by another camera. Uses glam's 0 to 1 depth
+    /// range, so the projection needs a finite far plane.
+    pub fn frustum(&self, view: &Mat4, projection: &Mat4, col: u32, thickness: f32) {
+        let inverse = (*projection * *view).inverse();
+        self.box_edges(std::array::from_fn(|i| inverse.project_point3(Vec3::new(
+            if i & 1 != 0 { 1. } else { -1. },
+            if i & 2 != 0 { 1. } else { -1. },
+            if i & 4 != 0 { 1. } else { 0. }
+        ))), col, thickness);
+    }
+
+    /// Text with its top left corner at a world position
+    pub fn text(&self, pos: Vec3, col: u32, text: impl AsRef<str>) {
+        if let Some(pos) = self.project(pos) {
+            self.draw_list.add_text(Into::<[f32; 2]>::into(pos), col, text);
+        }
+    }
+
+    /// ImGuizmo's `DrawGrid`: lines one unit apart on the XZ plane of
+    /// `matrix`, extending `grid_size` units from its origin
+    pub fn grid(&self, matrix: &Mat4, grid_size: f32) {
+        let lines = grid_size.floor() as i32;
+        for i in -lines..=lines {
+            let f = i as f32;
+            let kind = if i == 0 { 2 } else if i % 10 == 0 { 1 } else { 0 };
+            let point = |x: f32, z: f32| matrix.transform_point3(Vec3::new(x, 0., z));
+            self.line(point(f, -grid_size), point(f, grid_size), GRID_COLORS[kind], GRID_THICKNESS[kind]);
+            self.line(point(-grid_size, f), point(grid_size, f), GRID_COLORS[kind], GRID_THICKNESS[kind]);
+        }
+    }
+
+    /// ImGuizmo's `DrawCubes`: a unit cube for each matrix, with faces
+    /// colored by their axis and drawn back to front
+    pub fn cubes(&self, matrices: &[Mat4]) {
+        let mut faces = Vec::new();
+        for matrix in matrices {
+            for face in 0..6 {
+                let axis = face % 3;
+                let normal = Vec3::AXES[axis] * if face > 2 { -1. } else { 1. };
+                let (u, v) = (Vec3::AXES[(axis + 1) % 3], Vec3::AXES[(axis + 2) % 3]);
+                let center = matrix.transform_point3(normal * 0.5);
+                let outward = matrix.transform_vector3(normal);
+                let world_normal = matrix.transform_vector3(u).cross(matrix.transform_vector3(v));
+                let world_normal = if world_normal.dot(outward) < 0. { -world_normal } else { world_normal };
+                let view_dir = if self.orthographic { self.forward } else { center - self.eye };
+                if world_normal.dot(view_dir) >= 0. {
+                    continue;
+                }
+                let clip: [Vec4; 4] = std::array::from_fn(|i| {
+                    let (su, sv) = ([-1., -1., 1., 1.][i], [-1., 1., 1., -1.][i]);
+                    self.clip(matrix.transform_point3((normal + u * su + v * sv) * 0.5))
+                });
+                if clip.iter().any(|c| self.is_clipped(*c)) {
+                    continue;
+                }
+                let depth = (center - self.eye).dot(self.forward);
+                faces.push((depth, clip, self.style.direction_color[axis] | 0x00808080));
+            }
+        }
+        faces.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, clip, col) in faces {
+            let points = clip.iter().map(|c| Into::<[f32; 2]>::into(self.to_screen(*c))).collect();
+            self.draw_list.add_polyline(points, col).filled(true).build();
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! Ports of ImGuizmo's `DecomposeMatrixToComponents` and
+//! `RecomposeMatrixFromComponents`, and an inspector widget for editing a
+//! matrix as translation, rotation and scale.
+//!
+//! Rotations are Euler angles in degrees, stored as X, Y and Z angles
+//! whichever order they are applied in.
+
+use glam::{ EulerRot, Mat3, Mat4, Quat, Vec3, Vec4Swizzles };
+use imgui::{ ComboBoxFlags, Drag, Ui };
+
+/// Middle angle distance from +-90 degrees below which the first and last
+/// axes line up, and only their combined angle can be recovered
+const GIMBAL_LOCK_EPSILON: f32 = 1e-3;
+/// Largest difference between matrix elements still treated as the same matrix
+const MATRIX_EPSILON: f32 = 1e-5;
+
+/// Order the Euler rotations are applied in, first to last. `XYZ` matches ImGuizmo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EulerOrder {
+    #[default]
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX
+}
+
+impl EulerOrder {
+    pub const ALL: [Self; 6] = [Self::XYZ, Self::XZY, Self::YXZ, Self::YZX, Self::ZXY, Self::ZYX];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::XYZ => "XYZ",
+            Self::XZY => "XZY",
+            Self::YXZ => "YXZ",
+            Self::YZX => "YZX",
+            Self::ZXY => "ZXY",
+            Self::ZYX => "ZYX"
+        }
+    }
+
+    /// Axis indices, first applied to last
+    fn axes(&self) -> [usize; 3] {
+        match self {
+            Self::XYZ => [0, 1, 2],
+            Self::XZY => [0, 2, 1],
+            Self::YXZ => [1, 0, 2],
+            Self::YZX => [1, 2, 0],
+            Self::ZXY => [2, 0, 1],
+            Self::ZYX => [2, 1, 0]
+        }
+    }
+
+    /// glam's intrinsic order, which lists the axes last applied first
+    fn glam_order(&self) -> EulerRot {
+        match self {
+            Self::XYZ => EulerRot::ZYX,
+            Self::XZY => EulerRot::YZX,
+            Self::YXZ => EulerRot::ZXY,
+            Self::YZX => EulerRot::XZY,
+            Self::ZXY => EulerRot::YXZ,
+            Self::ZYX => EulerRot::XYZ
+        }
+    }
+
+    /// Rotation from X, Y and Z angles in degrees
+    pub fn to_quat(&self, rotation: Vec3) -> Quat {
+        let [a, b, c] = self.axes();
+        let r = rotation.to_array().map(f32::to_radians);
+        Quat::from_euler(self.glam_order(), r[c], r[b], r[a])
+    }
+
+    /// X, Y and Z angles in degrees, in the -180 to 180 range
+    pub fn from_quat(&self, rotation: Quat) -> Vec3 {
+        let [a, b, c] = self.axes();
+        let (last, middle, first) = rotation.to_euler(self.glam_order());
+        let mut out = [0.; 3];
+        out[a] = first.to_degrees();
+        out[b] = middle.to_degrees();
+        out[c] = last.to_degrees();
+        Vec3::from_array(out)
+    }
+
+    /// Angles for a rotation as close as possible to `previous`, so values
+    /// don't jump by 180 or 360 degrees while a rotation is being dragged
+    pub fn from_quat_near(&self, rotation: Quat, previous: Vec3) -> Vec3 {
+        let [a, b, c] = self.axes();
+        let wrap_near = |angles: Vec3| Vec3::from_array(std::array::from_fn(|i|
+            angles[i] + ((previous[i] - angles[i]) / 360.).round() * 360.
+        ));
+        let direct = self.from_quat(rotation);
+        if 90. - direct[b].abs() < GIMBAL_LOCK_EPSILON.to_degrees() {
+            // keep the first angle and put the whole remaining rotation on the last axis
+            let mut out = direct;
+            out[a] = previous[a];
+            let remainder = rotation
+                * Quat::from_axis_angle(Vec3::AXES[a], out[a].to_radians()).inverse()
+                * Quat::from_axis_angle(Vec3::AXES[b], out[b].to_radians()).inverse();
+            out[c] = (2. * remainder.xyz()[c].atan2(remainder.w)).to_degrees();
+            return wrap_near(out);
+        }
+        // every rotation has a second solution with the middle angle mirrored
+        let mut flipped = direct;
+        flipped[a] += 180.;
+        flipped[b] = 180. - flipped[b];
+        flipped[c] += 180.;
+        let (direct, flipped) = (wrap_near(direct), wrap_near(flipped));
+        if (direct - previous).abs().element_sum() <= (flipped - previous).abs().element_sum() { direct } else { flipped }
+    }
+}
+
+/// Translation, Euler rotation in degrees, and scale of a matrix
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransformComponents {
+    pub translation: Vec3,
+    pub rotation: Vec3,
+    pub scale: Vec3
+}
+
+impl Default for TransformComponents {
+    fn default() -> Self {
+        Self { translation: Vec3::ZERO, rotation: Vec3::ZERO, scale: Vec3::ONE }
+    }
+}
+
+impl TransformComponents {
+    pub fn from_matrix(matrix: &Mat4, order: EulerOrder) -> Self {
+        Self::from_matrix_near(matrix, order, None)
+    }
+
+    /// Like [`Self::from_matrix`], picking the rotation angles closest to `previous`
+    pub fn from_matrix_near(matrix: &Mat4, order: EulerOrder, previous: Option<Vec3>) -> Self {
+        let mut scale = Vec3::new(
+            matrix.x_axis.xyz().length(),
+            matrix.y_axis.xyz().length(),
+            matrix.z_axis.xyz().length()
+        );
+        // a mirroring matrix can't be represented with a rotation, so flip the X scale
+        if matrix.determinant() < 0. {
+            scale.x = -scale.x;
+        }
+        let basis = Mat3::from_cols(
+            matrix.x_axis.xyz() / scale.x,
+            matrix.y_axis.xyz() / scale.y,
+            matrix.z_axis.xyz() / scale.z
+        );
+        let rotation = if basis.is_finite() { Quat::from_mat3(&basis).normalize() } else { Quat::IDENTITY };
+        Self {
+            translation: matrix.w_axis.xyz(),
+            rotation: match previous {
+                Some(previous) => order.from_quat_near(rotation, previous),
+                None => order.from_quat(rotation)
+            },
+            scale
+        }
+    }
+
+    pub fn to_matrix(&self, order: EulerOrder) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, order.to_quat(self.rotation), self.translation)
+    }
+}
+
+/// ImGuizmo's `DecomposeMatrixToComponents`: translation, rotation in degrees
+/// applied in X, Y, Z order, and scale
+pub fn decompose_matrix_to_components(matrix: &Mat4) -> (Vec3, Vec3, Vec3) {
+    let components = TransformComponents::from_matrix(matrix, EulerOrder::XYZ);
+    (components.translation, components.rotation, components.scale)
+}
+
+/// ImGuizmo's `RecomposeMatrixFromComponents`
+pub fn recompose_matrix_from_components(translation: Vec3, rotation: Vec3, scale: Vec3) -> Mat4 {
+    TransformComponents { translation, rotation, scale }.to_matrix(EulerOrder::XYZ)
+}
+
+/// Translation, rotation and scale fields for a matrix. Keeps the values it
+/// shows between frames, so angles typed in stay as they are and rotations
+/// from a gizmo don't flip between equivalent angles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformEditor {
+    order: EulerOrder,
+    show_order: bool,
+    /// Values shown, and the matrix they were taken from or written to
+    components: Option<(TransformComponents, Mat4)>,
+    translation_speed: f32,
+    rotation_speed: f32,
+    scale_speed: f32
+}
+
+impl Default for TransformEditor {
+    fn default() -> Self {
+        Self {
+            order: EulerOrder::default(),
+            show_order: true,
+            components: None,
+            translation_speed: 0.01,
+            rotation_speed: 0.5,
+            scale_speed: 0.01
+        }
+    }
+}
+
+impl TransformEditor {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn set_order(mut self, order: EulerOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Show a combo box for picking the rotation order. Enabled by default.
+    pub fn set_show_order(mut self, show: bool) -> Self {
+        self.show_order = show;
+        self
+    }
+
+    /// Drag speeds for translation, rotation (in degrees) and scale
+    pub fn set_speeds(mut self, translation: f32, rotation: f32, scale: f32) -> Self {
+        self.translation_speed = translation;
+        self.rotation_speed = rotation;
+        self.scale_speed = scale;
+        self
+    }
+
+    pub fn get_order(&self) -> EulerOrder { self.order }
+
+    /// Values currently shown, if the editor has been drawn
+    pub fn get_components(&self) -> Option<TransformComponents> {
+        self.components.map(|(c, _)| c)
+    }
+
+    /// Draw the fields for `matrix`, writing any edit back to it. Returns true if it was changed.
+    pub fn draw(&mut self, ui: &Ui, label: &str, matrix: &mut Mat4) -> bool {
+        let _id = ui.push_id(label);
+        let mut components = match self.components {
+            Some((components, last)) if last.abs_diff_eq(*matrix, MATRIX_EPSILON) => components,
+            previous => TransformComponents::from_matrix_near(matrix, self.order, previous.map(|(c, _)| c.rotation))
+        };
+        if self.show_order
+            && let Some(_combo) = ui.begin_combo_with_flags("Rotation order", self.order.get_name(), ComboBoxFlags::empty()) {
+            for order in EulerOrder::ALL {
+                if ui.selectable_config(order.get_name()).selected(order == self.order).build() && order != self.order {
+                    // same rotation, expressed in the new order
+                    let rotation = self.order.to_quat(components.rotation);
+                    components.rotation = order.from_quat_near(rotation, components.rotation);
+                    self.order = order;
+                }
+                if order == self.order { ui.set_item_default_focus(); }
+            }
+        }
+        let mut translation = components.translation.to_array();
+        let mut rotation = components.rotation.to_array();
+        let mut scale = components.scale.to_array();
+        let mut changed = Drag::new("Translation").speed(self.translation_speed).display_format("%.3f")
+            .build_array(ui, &mut translation);
+        changed |= Drag::new("Rotation").speed(self.rotation_speed).display_format("%.2f")
+            .build_array(ui, &mut rotation);
+        changed |= Drag::new("Scale").speed(self.scale_speed).display_format("%.3f")
+            .build_array(ui, &mut scale);
+        if changed {
+            components = TransformComponents {
+                translation: Vec3::from_array(translation),
+                rotation: Vec3::from_array(rotation),
+                scale: Vec3::from_array(scale)
+            };
+            *matrix = components.to_matrix(self.order);
+        }
+        self.components = Some((components, *matrix));
+        changed
+    }
+}
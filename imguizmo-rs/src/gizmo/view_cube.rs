@@ -0,0 +1,220 @@
+//! Port of ImGuizmo's `ViewManipulate`: an orientation cube that snaps the
+//! camera to face, edge and corner views when clicked, and orbits it when
+//! dragged.
+
+use glam::{ Mat4, Vec2, Vec3, Vec4Swizzles };
+use imgui::{ MouseButton, Ui };
+use super::GizmoStyle;
+
+/// Distance of the cube's camera from the cube's center
+const CUBE_CAMERA_DISTANCE: f32 = 3.;
+/// Start of the middle band of each face. Clicks outside it pick an edge or corner.
+const PANEL_SPLITS: [f32; 4] = [0., 0.25, 0.75, 1.];
+/// Fraction of the remaining angle covered per 60th of a second while snapping
+const SNAP_SPEED: f32 = 0.2;
+const REFERENCE_UP: Vec3 = Vec3::Y;
+/// Highest and lowest camera elevation reachable by dragging, in radians
+const MAX_PITCH: f32 = 1.5;
+
+/// Orientation cube for a camera orbiting a point in front of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewCube {
+    style: GizmoStyle,
+    left_handed: bool,
+    background_color: u32,
+    hover_color: u32,
+    /// Cell of the 3x3x3 cube under the mouse when the button went down
+    pressed: Option<[i32; 3]>,
+    dragging: bool,
+    /// Direction from the orbit target to the camera, and up vector, being snapped to
+    target: Option<(Vec3, Vec3)>,
+    hovered: bool
+}
+
+impl Default for ViewCube {
+    fn default() -> Self {
+        Self {
+            style: GizmoStyle::default(),
+            left_handed: false,
+            background_color: 0x10101010,
+            hover_color: 0x8060a0f0,
+            pressed: None,
+            dragging: false,
+            target: None,
+            hovered: false
+        }
+    }
+}
+
+impl ViewCube {
+    pub fn new() -> Self { Self::default() }
+
+    /// Face colors are taken from the style's axis colors
+    pub fn with_style(mut self, style: GizmoStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// View matrices built with `Mat4::look_at_lh` look down their +Z axis
+    /// instead of -Z. Defaults to right handed.
+    pub fn set_left_handed(mut self, left_handed: bool) -> Self {
+        self.left_handed = left_handed;
+        self
+    }
+
+    pub fn set_background_color(mut self, col: u32) -> Self {
+        self.background_color = col;
+        self
+    }
+
+    pub fn set_hover_color(mut self, col: u32) -> Self {
+        self.hover_color = col;
+        self
+    }
+
+    /// Whether the view is being dragged or snapped to a new direction
+    pub fn is_using(&self) -> bool { self.dragging || self.target.is_some() }
+    pub fn is_over(&self) -> bool { self.hovered }
+
+    fn look_at(&self, eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
+        if self.left_handed { Mat4::look_at_lh(eye, center, up) } else { Mat4::look_at_rh(eye, center, up) }
+    }
+
+    /// Draw the cube in the rectangle at `position`, for a camera orbiting the
+    /// point `length` units in front of it. Returns the new view matrix while
+    /// the view is dragged or snapping to a clicked face, edge or corner.
+    pub fn manipulate(&mut self, ui: &Ui, view: &Mat4, length: f32, position: Vec2, size: Vec2) -> Option<Mat4> {
+        let draw_list = ui.get_window_draw_list();
+        let view_inverse = view.inverse();
+        let handedness = if self.left_handed { -1. } else { 1. };
+        // direction from the orbit target to the camera
+        let back = view_inverse.z_axis.xyz().normalize() * handedness;
+        let up = view_inverse.y_axis.xyz().normalize();
+        let right = view_inverse.x_axis.xyz().normalize();
+        let eye = view_inverse.w_axis.xyz();
+        let target = eye - back * length;
+
+        // the cube's own camera, looking at it from the same direction
+        let fov = (CUBE_CAMERA_DISTANCE / (CUBE_CAMERA_DISTANCE * CUBE_CAMERA_DISTANCE + 3.).sqrt()).acos() / 2f32.sqrt();
+        let aspect = size.x / size.y.max(1.);
+        let cube_projection = if self.left_handed {
+            Mat4::perspective_lh(fov, aspect, 0.01, 1000.)
+        } else {
+            Mat4::perspective_rh(fov, aspect, 0.01, 1000.)
+        };
+        let cube_eye = back * CUBE_CAMERA_DISTANCE;
+        let cube_view_proj = cube_projection * self.look_at(cube_eye, Vec3::ZERO, up);
+        let to_screen = |p: Vec3| {
+            let ndc = cube_view_proj.project_point3(p);
+            Vec2::new(position.x + (ndc.x * 0.5 + 0.5) * size.x, position.y + (0.5 - ndc.y * 0.5) * size.y)
+        };
+        let mouse = Vec2::from(ui.io().mouse_pos);
+        let inside = ui.is_window_hovered()
+            && mouse.cmpge(position).all() && mouse.cmple(position + size).all();
+        // mouse ray in cube space
+        let ray = {
+            let ndc = Vec2::new(
+                (mouse.x - position.x) / size.x * 2. - 1.,
+                1. - (mouse.y - position.y) / size.y * 2.
+            );
+            let inverse = cube_view_proj.inverse();
+            let (a, b) = (inverse.project_point3(ndc.extend(0.1)), inverse.project_point3(ndc.extend(0.9)));
+            if a.distance_squared(cube_eye) < b.distance_squared(cube_eye) { (a, (b - a).normalize()) } else { (b, (a - b).normalize()) }
+        };
+
+        draw_list.add_rect(Into::<[f32; 2]>::into(position), Into::<[f32; 2]>::into(position + size), self.background_color)
+            .filled(true).build();
+        let mut hovered_cell = None;
+        for face in 0..6 {
+            let axis = face % 3;
+            let sign = if face > 2 { -1. } else { 1. };
+            let normal = Vec3::AXES[axis] * sign;
+            // back face culling
+            if normal.dot(cube_eye - normal * 0.5) <= 0. {
+                continue;
+            }
+            let (px, py) = ((axis + 1) % 3, (axis + 2) % 3);
+            let local = {
+                let denom = normal.dot(ray.1);
+                (inside && !self.dragging && denom.abs() > f32::EPSILON).then(|| {
+                    let hit = ray.0 + ray.1 * ((0.5 - normal.dot(ray.0)) / denom);
+                    Vec2::new(hit[px] + 0.5, hit[py] + 0.5)
+                })
+            };
+            let face_color = self.style.direction_color[axis] | 0x80808080;
+            for cell in 0..9 {
+                let (gx, gy) = (cell % 3, cell / 3);
+                let (u0, u1) = (PANEL_SPLITS[gx], PANEL_SPLITS[gx + 1]);
+                let (v0, v1) = (PANEL_SPLITS[gy], PANEL_SPLITS[gy + 1]);
+                let corner = |u: f32, v: f32| {
+                    let mut p = normal * 0.5;
+                    p[px] = u - 0.5;
+                    p[py] = v - 0.5;
+                    Into::<[f32; 2]>::into(to_screen(p))
+                };
+                let quad = vec![corner(u0, v0), corner(u0, v1), corner(u1, v1), corner(u1, v0)];
+                draw_list.add_polyline(quad.clone(), face_color).filled(true).build();
+                let over = local.is_some_and(|l| l.x > u0 && l.x < u1 && l.y > v0 && l.y < v1);
+                if over {
+                    draw_list.add_polyline(quad, self.hover_color).filled(true).build();
+                    let mut cube_cell = [0; 3];
+                    cube_cell[axis] = sign as i32;
+                    cube_cell[px] = gx as i32 - 1;
+                    cube_cell[py] = gy as i32 - 1;
+                    hovered_cell = Some(cube_cell);
+                }
+            }
+        }
+        self.hovered = hovered_cell.is_some();
+
+        let mut new_view = None;
+        let mouse_down = ui.is_mouse_down(MouseButton::Left);
+        if let (Some(cell), true, false) = (hovered_cell, ui.is_mouse_clicked(MouseButton::Left), ui.is_any_item_active()) {
+            self.pressed = Some(cell);
+            self.dragging = true;
+        }
+        let mouse_delta = Vec2::from(ui.io().mouse_delta);
+        // moving the mouse turns the click into a drag
+        if mouse_down && mouse_delta != Vec2::ZERO {
+            self.pressed = None;
+        }
+        if !mouse_down {
+            if let Some(cell) = self.pressed.take() {
+                let dir = Vec3::new(cell[0] as f32, cell[1] as f32, cell[2] as f32).normalize();
+                let snap_up = if dir.dot(REFERENCE_UP).abs() > 1. - 0.01 {
+                    // looking straight up or down, keep the horizontal axis closest to the current right
+                    let mut right = right;
+                    if right.x.abs() > right.z.abs() { right.z = 0. } else { right.x = 0. }
+                    let up_dir = dir.cross(right.normalize()).normalize();
+                    if up_dir.dot(up) < 0. { -up_dir } else { up_dir }
+                } else { REFERENCE_UP };
+                self.target = Some((dir, snap_up));
+            }
+            self.dragging = false;
+        }
+        if let Some((dir, snap_up)) = self.target {
+            let t = 1. - (1. - SNAP_SPEED).powf(ui.io().delta_time * 60.);
+            let new_back = back.lerp(dir, t).normalize();
+            let new_back = if new_back.dot(dir) > 1. - 1e-5 || !new_back.is_finite() {
+                self.target = None;
+                dir
+            } else { new_back };
+            new_view = Some(self.look_at(target + new_back * length, target, snap_up));
+        }
+        if self.dragging && mouse_delta != Vec2::ZERO {
+            self.target = None;
+            // orbit in yaw and pitch. Straight above or below the target, the
+            // horizontal direction comes from the camera's up vector instead.
+            let horizontal = if back.y.abs() > 0.99 { -up * back.y.signum() } else { back };
+            let yaw = horizontal.x.atan2(horizontal.z) - mouse_delta.x * 0.01 * handedness;
+            // stop short of the poles, where the up vector flips
+            let pitch = (back.y.clamp(-1., 1.).asin() + mouse_delta.y * 0.01).clamp(-MAX_PITCH, MAX_PITCH);
+            let new_back = Vec3::new(pitch.cos() * yaw.sin(), pitch.sin(), pitch.cos() * yaw.cos());
+            new_view = Some(self.look_at(target + new_back * length, target, REFERENCE_UP));
+        }
+        if self.hovered || self.is_using() {
+            unsafe { imgui::sys::igSetNextFrameWantCaptureMouse(true); }
+        }
+        new_view
+    }
+}
@@ -0,0 +1,343 @@
+//! Rust port of ImGradient, part of the ImGuizmo library (MIT License)
+//! https://github.com/CedricGuillemet/ImGuizmo/blob/master/ImGradient.h
+//!
+//! As with the sequencer, the C++ `Delegate` class becomes a trait, and
+//! [`Gradient`] provides a ready to use implementation over a list of stops.
+//! Selection and drag state live in [`GradientState`] instead of a reference
+//! passed to `Edit`. Unlike ImGradient, stops can be removed, and the selected
+//! stop's color is edited with a picker under the bar.
+
+use glam::{ Vec2, Vec4 };
+use imgui::{ ImColor32, MouseButton, Ui };
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+const BAR_HEIGHT: f32 = 20.;
+const MARKER_HEIGHT: f32 = 12.;
+const MARKER_HALF_WIDTH: f32 = 5.;
+const CHECKER_SIZE: f32 = 5.;
+
+/// A color at a position along the gradient, with components from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GradientStop {
+    pub position: f32,
+    pub color: [f32; 4]
+}
+
+impl GradientStop {
+    pub fn new(position: f32, color: [f32; 4]) -> Self {
+        Self { position, color }
+    }
+}
+
+/// Interpolated RGBA color at `t` in `0.0..=1.0` between stops sorted by
+/// position. Positions before the first stop or after the last take its color.
+pub fn sample(stops: &[GradientStop], t: f32) -> [f32; 4] {
+    let (Some(first), Some(last)) = (stops.first(), stops.last()) else { return [0.; 4] };
+    if t <= first.position {
+        return first.color;
+    }
+    if t >= last.position {
+        return last.color;
+    }
+    let i = stops.partition_point(|s| s.position <= t);
+    let (a, b) = (&stops[i - 1], &stops[i]);
+    let span = b.position - a.position;
+    let f = if span > f32::EPSILON { (t - a.position) / span } else { 0. };
+    Vec4::from_array(a.color).lerp(Vec4::from_array(b.color), f).to_array()
+}
+
+fn to_color(color: [f32; 4]) -> ImColor32 {
+    ImColor32::from_rgba_f32s(color[0], color[1], color[2], color[3])
+}
+
+/// Selected stop and mouse interaction of a gradient editor
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GradientState {
+    selection: Option<usize>,
+    /// Offset from the mouse to the stop being dragged, in gradient units
+    drag_offset: Option<f32>
+}
+
+impl GradientState {
+    pub fn get_selection(&self) -> Option<usize> { self.selection }
+    pub fn set_selection(&mut self, selection: Option<usize>) { self.selection = selection; }
+    /// Whether a stop is being dragged
+    pub fn is_editing(&self) -> bool { self.drag_offset.is_some() }
+}
+
+pub trait GradientDelegate {
+    /// Stops sorted by position
+    fn get_stops(&self) -> &[GradientStop];
+    /// Replace a stop, keeping the stops sorted. Returns the stop's new index.
+    fn edit_stop(&mut self, index: usize, stop: GradientStop) -> usize;
+    /// Returns the index the stop was inserted at
+    fn add_stop(&mut self, stop: GradientStop) -> usize;
+    fn remove_stop(&mut self, index: usize);
+
+    fn get_state(&self) -> &GradientState;
+    fn get_state_mut(&mut self) -> &mut GradientState;
+
+    /// Called before the first change to the stops during a drag, insertion or removal
+    fn begin_edit(&mut self) {}
+    fn end_edit(&mut self) {}
+
+    fn sample(&self, t: f32) -> [f32; 4] { sample(self.get_stops(), t) }
+
+    /// Draw the gradient bar at the cursor position, with a color picker for
+    /// the selected stop below it. A zero or negative width uses the available
+    /// content region. Double click the bar to add a stop, drag a stop to move
+    /// it, and right click it to remove it. Returns true if a stop was changed.
+    fn draw(&mut self, ui: &Ui, width: f32) -> bool {
+        draw_gradient(self, ui, width)
+    }
+}
+
+fn draw_gradient<D: GradientDelegate + ?Sized>(delegate: &mut D, ui: &Ui, width: f32) -> bool {
+    let _id = ui.push_id("gradient");
+    let pos = Vec2::from(ui.cursor_screen_pos());
+    let width = if width > 0. { width } else { ui.content_region_avail()[0] }.max(1.);
+    ui.invisible_button("gradientBar", [width, BAR_HEIGHT + MARKER_HEIGHT]);
+    let hovered = ui.is_item_hovered();
+    let mouse = Vec2::from(ui.io().mouse_pos);
+    let mouse_t = ((mouse.x - pos.x) / width).clamp(0., 1.);
+    let t_to_x = |t: f32| pos.x + t * width;
+    let mut changed = false;
+
+    if delegate.get_state().selection.is_some_and(|s| s >= delegate.get_stops().len()) {
+        delegate.get_state_mut().selection = None;
+    }
+    let over_stop = (hovered && mouse.y > pos.y + BAR_HEIGHT).then(|| {
+        delegate.get_stops().iter().enumerate()
+            .map(|(i, s)| (i, (t_to_x(s.position) - mouse.x).abs()))
+            .filter(|(_, d)| *d <= MARKER_HALF_WIDTH)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }).flatten();
+
+    if let Some(offset) = delegate.get_state().drag_offset {
+        if ui.is_mouse_down(MouseButton::Left) {
+            if let Some(index) = delegate.get_state().selection {
+                let mut stop = delegate.get_stops()[index];
+                let position = (mouse_t + offset).clamp(0., 1.);
+                if position != stop.position {
+                    stop.position = position;
+                    let index = delegate.edit_stop(index, stop);
+                    delegate.get_state_mut().selection = Some(index);
+                    changed = true;
+                }
+            }
+        } else {
+            delegate.get_state_mut().drag_offset = None;
+            delegate.end_edit();
+        }
+    } else if let Some(index) = over_stop {
+        if ui.is_mouse_clicked(MouseButton::Left) {
+            let offset = delegate.get_stops()[index].position - mouse_t;
+            let state = delegate.get_state_mut();
+            state.selection = Some(index);
+            state.drag_offset = Some(offset);
+            delegate.begin_edit();
+        } else if ui.is_mouse_clicked(MouseButton::Right) && delegate.get_stops().len() > 1 {
+            delegate.begin_edit();
+            delegate.remove_stop(index);
+            delegate.end_edit();
+            let state = delegate.get_state_mut();
+            state.selection = state.selection.and_then(|s| match s.cmp(&index) {
+                std::cmp::Ordering::Less => Some(s),
+                std::cmp::Ordering::Equal => None,
+                std::cmp::Ordering::Greater => Some(s - 1)
+            });
+            changed = true;
+        }
+    } else if hovered && ui.is_mouse_double_clicked(MouseButton::Left) {
+        let stop = GradientStop::new(mouse_t, delegate.sample(mouse_t));
+        delegate.begin_edit();
+        let index = delegate.add_stop(stop);
+        delegate.end_edit();
+        delegate.get_state_mut().selection = Some(index);
+        changed = true;
+    }
+
+    let draw_list = ui.get_window_draw_list();
+    let bar_max = pos + Vec2::new(width, BAR_HEIGHT);
+    // checkerboard behind transparent colors
+    draw_list.add_rect(Into::<[f32; 2]>::into(pos), Into::<[f32; 2]>::into(bar_max), 0xffcccccc).filled(true).build();
+    for row in 0..(BAR_HEIGHT / CHECKER_SIZE).ceil() as usize {
+        for col in (row % 2..(width / CHECKER_SIZE).ceil() as usize).step_by(2) {
+            let min = pos + Vec2::new(col as f32, row as f32) * CHECKER_SIZE;
+            let max = (min + Vec2::splat(CHECKER_SIZE)).min(bar_max);
+            draw_list.add_rect(Into::<[f32; 2]>::into(min), Into::<[f32; 2]>::into(max), 0xff808080).filled(true).build();
+        }
+    }
+    let stops = delegate.get_stops();
+    // the bar is split at every stop, and padded with the end colors
+    let mut edges: Vec<(f32, [f32; 4])> = stops.iter().map(|s| (s.position.clamp(0., 1.), s.color)).collect();
+    if let (Some(first), Some(last)) = (stops.first(), stops.last()) {
+        edges.insert(0, (0., first.color));
+        edges.push((1., last.color));
+    }
+    for pair in edges.windows(2) {
+        let ((t0, c0), (t1, c1)) = (pair[0], pair[1]);
+        if t1 <= t0 {
+            continue;
+        }
+        let (c0, c1) = (to_color(c0), to_color(c1));
+        draw_list.add_rect_filled_multicolor(
+            [t_to_x(t0), pos.y], [t_to_x(t1), bar_max.y], c0, c1, c1, c0
+        );
+    }
+    draw_list.add_rect(Into::<[f32; 2]>::into(pos), Into::<[f32; 2]>::into(bar_max), 0xff000000).build();
+    let selection = delegate.get_state().selection;
+    for (i, stop) in stops.iter().enumerate() {
+        let x = t_to_x(stop.position);
+        let top = bar_max.y;
+        let outline = if selection == Some(i) { 0xffffffff } else if over_stop == Some(i) { 0xffc0c0c0 } else { 0xff808080 };
+        draw_list.add_triangle([x, top], [x - MARKER_HALF_WIDTH, top + MARKER_HALF_WIDTH],
+            [x + MARKER_HALF_WIDTH, top + MARKER_HALF_WIDTH], outline).filled(true).build();
+        let min = [x - MARKER_HALF_WIDTH, top + MARKER_HALF_WIDTH];
+        let max = [x + MARKER_HALF_WIDTH, top + MARKER_HEIGHT];
+        let [r, g, b, _] = stop.color;
+        draw_list.add_rect(min, max, to_color([r, g, b, 1.])).filled(true).build();
+        draw_list.add_rect(min, max, outline).build();
+    }
+    drop(draw_list);
+
+    if let Some(index) = delegate.get_state().selection {
+        let mut stop = delegate.get_stops()[index];
+        ui.set_next_item_width(width);
+        let edited = ui.slider_config("##position", 0., 1.).build(&mut stop.position);
+        ui.set_next_item_width(width);
+        let edited = ui.color_picker4_config("##color", &mut stop.color).alpha_bar(true).build() || edited;
+        if edited {
+            if !delegate.get_state().is_editing() {
+                delegate.begin_edit();
+            }
+            let index = delegate.edit_stop(index, stop);
+            if !delegate.get_state().is_editing() {
+                delegate.end_edit();
+            }
+            delegate.get_state_mut().selection = Some(index);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Gradient stored as a list of stops sorted by position
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Gradient {
+    stops: Vec<GradientStop>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    state: GradientState
+}
+
+impl Default for Gradient {
+    /// Black to white
+    fn default() -> Self {
+        Self::new(vec![
+            GradientStop::new(0., [0., 0., 0., 1.]),
+            GradientStop::new(1., [1., 1., 1., 1.])
+        ])
+    }
+}
+
+impl Gradient {
+    pub fn new(mut stops: Vec<GradientStop>) -> Self {
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Self { stops, state: GradientState::default() }
+    }
+
+    pub fn get_stops(&self) -> &[GradientStop] { &self.stops }
+
+    /// Interpolated RGBA color at `t` in `0.0..=1.0`
+    pub fn sample(&self, t: f32) -> [f32; 4] { sample(&self.stops, t) }
+}
+
+impl GradientDelegate for Gradient {
+    fn get_stops(&self) -> &[GradientStop] { &self.stops }
+
+    fn edit_stop(&mut self, index: usize, stop: GradientStop) -> usize {
+        self.stops.remove(index);
+        self.add_stop(stop)
+    }
+
+    fn add_stop(&mut self, stop: GradientStop) -> usize {
+        let index = self.stops.partition_point(|s| s.position <= stop.position);
+        self.stops.insert(index, stop);
+        index
+    }
+
+    fn remove_stop(&mut self, index: usize) { self.stops.remove(index); }
+
+    fn get_state(&self) -> &GradientState { &self.state }
+    fn get_state_mut(&mut self) -> &mut GradientState { &mut self.state }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [f32; 4] = [1., 0., 0., 1.];
+    const BLUE: [f32; 4] = [0., 0., 1., 1.];
+    const CLEAR: [f32; 4] = [1., 1., 1., 0.];
+
+    fn assert_color(actual: [f32; 4], expected: [f32; 4]) {
+        assert!(Vec4::from_array(actual).abs_diff_eq(Vec4::from_array(expected), 1e-5),
+            "{:?} isn't {:?}", actual, expected);
+    }
+
+    fn stops() -> Vec<GradientStop> {
+        vec![GradientStop::new(0.25, RED), GradientStop::new(0.75, BLUE)]
+    }
+
+    #[test]
+    fn outside_the_stops_takes_the_nearest_color() {
+        assert_color(sample(&stops(), 0.), RED);
+        assert_color(sample(&stops(), 0.25), RED);
+        assert_color(sample(&stops(), -3.), RED);
+        assert_color(sample(&stops(), 0.75), BLUE);
+        assert_color(sample(&stops(), 1.), BLUE);
+        assert_color(sample(&stops(), 7.), BLUE);
+    }
+
+    #[test]
+    fn between_stops_interpolates() {
+        assert_color(sample(&stops(), 0.5), [0.5, 0., 0.5, 1.]);
+        assert_color(sample(&stops(), 0.375), [0.75, 0., 0.25, 1.]);
+        let stops = [GradientStop::new(0., RED), GradientStop::new(0.5, BLUE), GradientStop::new(1., CLEAR)];
+        assert_color(sample(&stops, 0.25), [0.5, 0., 0.5, 1.]);
+        assert_color(sample(&stops, 0.75), [0.5, 0.5, 1., 0.5]);
+    }
+
+    #[test]
+    fn duplicate_positions_make_a_hard_edge() {
+        let stops = [
+            GradientStop::new(0., CLEAR),
+            GradientStop::new(0.5, RED),
+            GradientStop::new(0.5, BLUE),
+            GradientStop::new(1., CLEAR)
+        ];
+        // just before the edge is almost all the first color
+        assert_color(sample(&stops, 0.5 - 1e-6), RED);
+        assert_color(sample(&stops, 0.5), BLUE);
+        assert_color(sample(&stops, 0.25), [1., 0.5, 0.5, 0.5]);
+        assert_color(sample(&stops, 0.75), [0.5, 0.5, 1., 0.5]);
+        // every stop at the same position
+        let stops = [GradientStop::new(0.5, RED), GradientStop::new(0.5, BLUE)];
+        assert_color(sample(&stops, 0.), RED);
+        assert_color(sample(&stops, 0.5), RED);
+        assert_color(sample(&stops, 1.), BLUE);
+    }
+
+    #[test]
+    fn empty_and_single_stop() {
+        assert_eq!(sample(&[], 0.5), [0.; 4]);
+        let stops = [GradientStop::new(0.3, BLUE)];
+        for t in [0., 0.3, 1.] {
+            assert_color(sample(&stops, t), BLUE);
+        }
+    }
+}
@@ -0,0 +1,652 @@
+//! Rust port of GraphEditor, part of the ImGuizmo library (MIT License)
+//! https://github.com/CedricGuillemet/ImGuizmo/blob/master/GraphEditor.h
+//!
+//! As with the sequencer, the C++ `Delegate` class becomes a trait that the
+//! application implements over its own graph. The view position and zoom
+//! (`ViewState` in GraphEditor) and the current mouse interaction, which
+//! GraphEditor keeps in static locals, live in [`GraphEditorState`].
+//!
+//! GraphEditor's `Link` names its ends after the nodes, so that
+//! `mInputNodeIndex` is the node the link starts from. [`GraphLink`] names
+//! them after the slots instead: a link goes from an output slot of one node
+//! to an input slot of another.
+
+use glam::Vec2;
+use imgui::{ DrawListMut, MouseButton, Ui };
+
+/// Height of a node's title bar in graph units
+const HEADER_HEIGHT: f32 = 20.;
+/// Pixel distance from a link's curve that counts as hovering the link
+const LINK_HOVER_DISTANCE: f32 = 6.;
+const LINK_SEGMENTS: usize = 16;
+/// Lines of the grid between two major lines
+const GRID_SUBDIVISIONS: i32 = 10;
+
+/// Colors and sizes of the editor, matching GraphEditor's `Options`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphEditorOptions {
+    /// Corners of the minimap as fractions of the canvas size
+    pub minimap: [Vec2; 2],
+    pub background_color: u32,
+    pub grid_color: u32,
+    /// Color of every 10th grid line
+    pub grid_color2: u32,
+    pub selected_node_border_color: u32,
+    pub node_border_color: u32,
+    pub quad_selection: u32,
+    pub quad_selection_border: u32,
+    /// Color of slots whose template doesn't give one
+    pub default_slot_color: u32,
+    /// Border drawn around the canvas while its window is focused
+    pub frame_focus: u32,
+    pub line_thickness: f32,
+    pub grid_size: f32,
+    pub rounding: f32,
+    /// Zoom change per mouse wheel step
+    pub zoom_ratio: f32,
+    /// Fraction of the remaining zoom covered each frame
+    pub zoom_lerp_factor: f32,
+    pub border_selection_thickness: f32,
+    pub border_thickness: f32,
+    pub node_slot_radius: f32,
+    pub node_slot_hover_factor: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// Grid nodes are snapped to while being moved. Zero disables snapping.
+    pub snap: f32,
+    pub display_links_as_curves: bool,
+    pub allow_quad_selection: bool,
+    pub render_grid: bool,
+    /// Only show slot names while the mouse is over their node
+    pub draw_io_name_on_hover: bool,
+    pub show_minimap: bool
+}
+
+impl Default for GraphEditorOptions {
+    fn default() -> Self {
+        Self {
+            minimap: [Vec2::new(0.75, 0.8), Vec2::new(0.99, 0.99)],
+            background_color: 0xff282828,
+            grid_color: 0x3c000000,
+            grid_color2: 0xa0000000,
+            selected_node_border_color: 0xff1e82ff,
+            node_border_color: 0x00646464,
+            quad_selection: 0x402020ff,
+            quad_selection_border: 0xff2020ff,
+            default_slot_color: 0xff808080,
+            frame_focus: 0xffff8040,
+            line_thickness: 5.,
+            grid_size: 64.,
+            rounding: 3.,
+            zoom_ratio: 0.1,
+            zoom_lerp_factor: 0.25,
+            border_selection_thickness: 6.,
+            border_thickness: 6.,
+            node_slot_radius: 8.,
+            node_slot_hover_factor: 1.2,
+            min_zoom: 0.2,
+            max_zoom: 1.1,
+            snap: 5.,
+            display_links_as_curves: true,
+            allow_quad_selection: true,
+            render_grid: true,
+            draw_io_name_on_hover: true,
+            show_minimap: true
+        }
+    }
+}
+
+/// An input or output of a node template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotTemplate {
+    pub name: String,
+    /// Uses [`GraphEditorOptions::default_slot_color`] if not set
+    pub color: Option<u32>
+}
+
+impl SlotTemplate {
+    pub fn new<T>(name: T, color: Option<u32>) -> Self
+    where T: Into<String>
+    {
+        Self { name: name.into(), color }
+    }
+}
+
+/// Look and slots shared by nodes of the same kind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeTemplate {
+    pub header_color: u32,
+    pub background_color: u32,
+    /// Background while the mouse is over the node
+    pub background_color_over: u32,
+    pub inputs: Vec<SlotTemplate>,
+    pub outputs: Vec<SlotTemplate>
+}
+
+/// Connection from an output slot of one node to an input slot of another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphLink {
+    pub output_node: usize,
+    pub output_slot: usize,
+    pub input_node: usize,
+    pub input_slot: usize
+}
+
+/// Nodes to fit the view to, GraphEditor's `FitOnScreen`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitOnScreen {
+    AllNodes,
+    SelectedNodes
+}
+
+/// A slot of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SlotRef {
+    node: usize,
+    slot: usize,
+    output: bool
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum Interaction {
+    #[default]
+    None,
+    Panning,
+    /// `offset` is the distance moved so far, before snapping
+    MovingNodes { node: usize, origin: Vec2, offset: Vec2 },
+    /// Dragging a new link from a slot
+    Linking(SlotRef),
+    /// Rectangle selection, starting at a point in graph space
+    Selecting(Vec2),
+    Minimap
+}
+
+/// View position and zoom, and mouse interaction of a graph editor
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphEditorState {
+    position: Vec2,
+    factor: f32,
+    factor_target: f32,
+    fit: Option<FitOnScreen>,
+    interaction: Interaction
+}
+
+impl Default for GraphEditorState {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            factor: 1.,
+            factor_target: 1.,
+            fit: None,
+            interaction: Interaction::None
+        }
+    }
+}
+
+impl GraphEditorState {
+    /// Offset of the graph origin from the canvas' top left corner, in graph units
+    pub fn get_position(&self) -> Vec2 { self.position }
+    pub fn get_factor(&self) -> f32 { self.factor }
+    /// Whether nodes are being moved or a link is being dragged
+    pub fn is_editing(&self) -> bool {
+        matches!(self.interaction, Interaction::MovingNodes { .. } | Interaction::Linking(_))
+    }
+
+    pub fn set_position(&mut self, position: Vec2) { self.position = position; }
+    /// Zoom to `factor` without easing into it
+    pub fn set_factor(&mut self, factor: f32) {
+        self.factor = factor;
+        self.factor_target = factor;
+    }
+    /// Fit the view to some of the nodes the next time the editor is drawn
+    pub fn fit(&mut self, fit: FitOnScreen) { self.fit = Some(fit); }
+}
+
+pub trait GraphEditorDelegate {
+    fn get_template_count(&self) -> usize;
+    fn get_template(&self, index: usize) -> &NodeTemplate;
+    fn get_node_count(&self) -> usize;
+    fn get_node_name(&self, node: usize) -> &str;
+    fn get_node_template(&self, node: usize) -> usize;
+    /// Top left and bottom right corners of a node in graph space
+    fn get_node_rect(&self, node: usize) -> [Vec2; 2];
+    fn is_node_selected(&self, node: usize) -> bool;
+    fn get_link_count(&self) -> usize;
+    fn get_link(&self, index: usize) -> GraphLink;
+
+    fn select_node(&mut self, node: usize, selected: bool);
+    fn move_selected_nodes(&mut self, delta: Vec2);
+    fn add_link(&mut self, link: GraphLink);
+    fn del_link(&mut self, index: usize);
+
+    fn get_state(&self) -> &GraphEditorState;
+    fn get_state_mut(&mut self) -> &mut GraphEditorState;
+
+    /// Whether a link may go from an output of `from` to an input of `to`
+    fn allowed_link(&self, from: usize, to: usize) -> bool { from != to }
+    /// Draw the contents of a node below its title bar. The draw list is
+    /// clipped to the canvas but not to `rect`.
+    fn custom_draw(&mut self, _draw_list: &DrawListMut, _rect: [Vec2; 2], _node: usize) {}
+    /// Right click on the canvas, for opening a context menu at the mouse position.
+    /// `node` is set if the click was on a node, along with the slot under the mouse.
+    fn right_click(&mut self, _node: Option<usize>, _input: Option<usize>, _output: Option<usize>) {}
+    /// Called before nodes start moving, or links are added or removed by dragging
+    fn begin_edit(&mut self) {}
+    fn end_edit(&mut self) {}
+
+    /// Draw the editor in the available content region. Interaction is
+    /// disabled unless `enabled` is set. Returns true if nodes were moved or
+    /// links changed.
+    fn draw(&mut self, ui: &Ui, options: &GraphEditorOptions, enabled: bool) -> bool {
+        draw_graph_editor(self, ui, options, enabled)
+    }
+}
+
+/// Conversion between graph space and screen space
+#[derive(Debug, Clone, Copy)]
+struct GraphView {
+    origin: Vec2,
+    position: Vec2,
+    factor: f32
+}
+
+impl GraphView {
+    fn graph_to_screen(&self, p: Vec2) -> Vec2 { self.origin + (p + self.position) * self.factor }
+    fn screen_to_graph(&self, p: Vec2) -> Vec2 { (p - self.origin) / self.factor - self.position }
+}
+
+fn slot_position<D: GraphEditorDelegate + ?Sized>(delegate: &D, slot: SlotRef) -> Vec2 {
+    let [min, max] = delegate.get_node_rect(slot.node);
+    let template = delegate.get_template(delegate.get_node_template(slot.node));
+    let count = if slot.output { template.outputs.len() } else { template.inputs.len() };
+    let y = min.y + (max.y - min.y) * (slot.slot + 1) as f32 / (count + 1) as f32;
+    Vec2::new(if slot.output { max.x } else { min.x }, y)
+}
+
+fn slot_color<D: GraphEditorDelegate + ?Sized>(delegate: &D, slot: SlotRef, options: &GraphEditorOptions) -> u32 {
+    let template = delegate.get_template(delegate.get_node_template(slot.node));
+    let slots = if slot.output { &template.outputs } else { &template.inputs };
+    slots.get(slot.slot).and_then(|s| s.color).unwrap_or(options.default_slot_color)
+}
+
+/// Points along a link between two screen positions
+fn link_points(start: Vec2, end: Vec2, factor: f32, curved: bool) -> Vec<Vec2> {
+    let reach = ((end.x - start.x).abs() * 0.5).max(30. * factor);
+    if curved {
+        let (c1, c2) = (start + Vec2::new(reach, 0.), end - Vec2::new(reach, 0.));
+        (0..=LINK_SEGMENTS).map(|i| {
+            let t = i as f32 / LINK_SEGMENTS as f32;
+            let u = 1. - t;
+            start * u * u * u + c1 * 3. * u * u * t + c2 * 3. * u * t * t + end * t * t * t
+        }).collect()
+    } else {
+        let step = 10. * factor;
+        let middle = (start.x + end.x) * 0.5;
+        let (a, b) = if end.x - start.x > step * 2. { (middle, middle) } else { (start.x + step, end.x - step) };
+        vec![start, Vec2::new(a, start.y), Vec2::new(b, end.y), end]
+    }
+}
+
+fn distance_to_polyline(p: Vec2, points: &[Vec2]) -> f32 {
+    points.windows(2).map(|w| {
+        let ab = w[1] - w[0];
+        let t = if ab.length_squared() > f32::EPSILON { ((p - w[0]).dot(ab) / ab.length_squared()).clamp(0., 1.) } else { 0. };
+        p.distance(w[0] + ab * t)
+    }).fold(f32::MAX, f32::min)
+}
+
+fn fit_view<D: GraphEditorDelegate + ?Sized>(delegate: &mut D, fit: FitOnScreen, size: Vec2, options: &GraphEditorOptions) {
+    let (min, max) = (0..delegate.get_node_count())
+        .filter(|n| fit == FitOnScreen::AllNodes || delegate.is_node_selected(*n))
+        .map(|n| delegate.get_node_rect(n))
+        .fold((Vec2::MAX, Vec2::MIN), |(lo, hi), [a, b]| (lo.min(a), hi.max(b)));
+    if min.x > max.x {
+        return;
+    }
+    let extent = (max - min).max(Vec2::ONE) * 1.1;
+    let factor = (size / extent).min_element().clamp(options.min_zoom, options.max_zoom);
+    let state = delegate.get_state_mut();
+    state.set_factor(factor);
+    state.position = size * 0.5 / factor - (min + max) * 0.5;
+}
+
+fn draw_graph_editor<D: GraphEditorDelegate + ?Sized>(delegate: &mut D, ui: &Ui, options: &GraphEditorOptions,
+    enabled: bool) -> bool {
+    let _id = ui.push_id("graphEditor");
+    let origin = Vec2::from(ui.cursor_screen_pos());
+    let size = Vec2::from(ui.content_region_avail()).max(Vec2::ONE);
+    ui.invisible_button("graphCanvas", Into::<[f32; 2]>::into(size));
+    let canvas_max = origin + size;
+    let hovered = enabled && ui.is_item_hovered();
+    let mouse = Vec2::from(ui.io().mouse_pos);
+    let modifier = ui.io().key_shift || ui.io().key_ctrl;
+    let mut changed = false;
+
+    if let Some(fit) = delegate.get_state_mut().fit.take() {
+        fit_view(delegate, fit, size, options);
+    }
+    // zoom around the mouse and pan with the middle button
+    {
+        let state = delegate.get_state_mut();
+        let wheel = ui.io().mouse_wheel;
+        if hovered && wheel.abs() > f32::EPSILON {
+            state.factor_target = (state.factor_target * (1. + wheel * options.zoom_ratio))
+                .clamp(options.min_zoom, options.max_zoom);
+        }
+        if (state.factor_target - state.factor).abs() > f32::EPSILON {
+            let anchor = if hovered { mouse - origin } else { size * 0.5 };
+            let graph_anchor = anchor / state.factor - state.position;
+            state.factor += (state.factor_target - state.factor) * options.zoom_lerp_factor;
+            if (state.factor_target - state.factor).abs() < 0.001 {
+                state.factor = state.factor_target;
+            }
+            state.position = anchor / state.factor - graph_anchor;
+        }
+        if hovered && state.interaction == Interaction::None && ui.is_mouse_clicked(MouseButton::Middle) {
+            state.interaction = Interaction::Panning;
+        }
+        if state.interaction == Interaction::Panning {
+            if ui.is_mouse_down(MouseButton::Middle) {
+                state.position += Vec2::from(ui.io().mouse_delta) / state.factor;
+            } else {
+                state.interaction = Interaction::None;
+            }
+        }
+    }
+    let view = GraphView { origin, position: delegate.get_state().position, factor: delegate.get_state().factor };
+    let factor = view.factor;
+
+    let node_count = delegate.get_node_count();
+    let rects: Vec<[Vec2; 2]> = (0..node_count)
+        .map(|n| delegate.get_node_rect(n).map(|p| view.graph_to_screen(p)))
+        .collect();
+    let bounds = rects.iter().fold((Vec2::MAX, Vec2::MIN), |(lo, hi), [a, b]| (lo.min(*a), hi.max(*b)));
+    let minimap_rect = options.minimap.map(|p| origin + size * p);
+    let over_minimap = options.show_minimap && node_count > 0 && hovered
+        && mouse.cmpge(minimap_rect[0]).all() && mouse.cmple(minimap_rect[1]).all();
+
+    // what's under the mouse, nodes drawn last first
+    let slot_radius = options.node_slot_radius * factor;
+    let over_slot = (hovered && !over_minimap).then(|| (0..node_count).rev().find_map(|node| {
+        let template = delegate.get_template(delegate.get_node_template(node));
+        [(false, template.inputs.len()), (true, template.outputs.len())].into_iter()
+            .flat_map(|(output, count)| (0..count).map(move |slot| SlotRef { node, slot, output }))
+            .find(|s| view.graph_to_screen(slot_position(delegate, *s)).distance(mouse) <= slot_radius * options.node_slot_hover_factor)
+    })).flatten();
+    let over_node = (hovered && !over_minimap).then(|| {
+        over_slot.map(|s| s.node).or_else(|| rects.iter().rposition(|[a, b]| mouse.cmpge(*a).all() && mouse.cmple(*b).all()))
+    }).flatten();
+
+    if hovered && ui.is_mouse_clicked(MouseButton::Right) {
+        delegate.right_click(
+            over_node,
+            over_slot.filter(|s| !s.output).map(|s| s.slot),
+            over_slot.filter(|s| s.output).map(|s| s.slot)
+        );
+    }
+    if hovered && delegate.get_state().interaction == Interaction::None && ui.is_mouse_clicked(MouseButton::Left) {
+        let interaction = if over_minimap {
+            Interaction::Minimap
+        } else if let Some(slot) = over_slot {
+            // dragging from a connected input picks its link back up from the other end
+            let link = (!slot.output).then(|| (0..delegate.get_link_count())
+                .find(|l| { let l = delegate.get_link(*l); l.input_node == slot.node && l.input_slot == slot.slot })).flatten();
+            delegate.begin_edit();
+            match link {
+                Some(index) => {
+                    let link = delegate.get_link(index);
+                    delegate.del_link(index);
+                    changed = true;
+                    Interaction::Linking(SlotRef { node: link.output_node, slot: link.output_slot, output: true })
+                },
+                None => Interaction::Linking(slot)
+            }
+        } else if let Some(node) = over_node {
+            if modifier {
+                let selected = delegate.is_node_selected(node);
+                delegate.select_node(node, !selected);
+                Interaction::None
+            } else {
+                if !delegate.is_node_selected(node) {
+                    for other in 0..node_count {
+                        if delegate.is_node_selected(other) {
+                            delegate.select_node(other, false);
+                        }
+                    }
+                    delegate.select_node(node, true);
+                }
+                delegate.begin_edit();
+                Interaction::MovingNodes { node, origin: delegate.get_node_rect(node)[0], offset: Vec2::ZERO }
+            }
+        } else {
+            if !modifier {
+                for node in 0..node_count {
+                    if delegate.is_node_selected(node) {
+                        delegate.select_node(node, false);
+                    }
+                }
+            }
+            if options.allow_quad_selection { Interaction::Selecting(view.screen_to_graph(mouse)) } else { Interaction::None }
+        };
+        delegate.get_state_mut().interaction = interaction;
+    }
+
+    let mouse_down = enabled && ui.is_mouse_down(MouseButton::Left);
+    let mut dragged_link = None;
+    let mut selection_rect = None;
+    match delegate.get_state().interaction {
+        Interaction::MovingNodes { node, origin: node_origin, offset } => {
+            if mouse_down {
+                let offset = offset + Vec2::from(ui.io().mouse_delta) / factor;
+                let target = node_origin + offset;
+                let target = if options.snap > 0. { (target / options.snap).round() * options.snap } else { target };
+                let delta = target - delegate.get_node_rect(node)[0];
+                if delta != Vec2::ZERO {
+                    delegate.move_selected_nodes(delta);
+                    changed = true;
+                }
+                delegate.get_state_mut().interaction = Interaction::MovingNodes { node, origin: node_origin, offset };
+            } else {
+                delegate.get_state_mut().interaction = Interaction::None;
+                delegate.end_edit();
+            }
+        },
+        Interaction::Linking(from) => {
+            if mouse_down {
+                dragged_link = Some(from);
+            } else {
+                let target = over_slot.filter(|s| s.output != from.output && s.node != from.node);
+                if let Some(to) = target {
+                    let (output, input) = if from.output { (from, to) } else { (to, from) };
+                    if delegate.allowed_link(output.node, input.node) {
+                        // an input takes a single link
+                        let existing: Vec<usize> = (0..delegate.get_link_count())
+                            .filter(|l| { let l = delegate.get_link(*l); l.input_node == input.node && l.input_slot == input.slot })
+                            .collect();
+                        for index in existing.into_iter().rev() {
+                            delegate.del_link(index);
+                        }
+                        delegate.add_link(GraphLink {
+                            output_node: output.node,
+                            output_slot: output.slot,
+                            input_node: input.node,
+                            input_slot: input.slot
+                        });
+                        changed = true;
+                    }
+                }
+                delegate.get_state_mut().interaction = Interaction::None;
+                delegate.end_edit();
+            }
+        },
+        Interaction::Selecting(start) => {
+            let start = view.graph_to_screen(start);
+            let (min, max) = (start.min(mouse), start.max(mouse));
+            if mouse_down {
+                selection_rect = Some([min, max]);
+            } else {
+                for (node, [a, b]) in rects.iter().enumerate() {
+                    if a.cmple(max).all() && b.cmpge(min).all() && !delegate.is_node_selected(node) {
+                        delegate.select_node(node, true);
+                    }
+                }
+                delegate.get_state_mut().interaction = Interaction::None;
+            }
+        },
+        Interaction::Minimap if !mouse_down => delegate.get_state_mut().interaction = Interaction::None,
+        _ => ()
+    }
+
+    let draw_list = ui.get_window_draw_list();
+    draw_list.with_clip_rect_intersect(Into::<[f32; 2]>::into(origin), Into::<[f32; 2]>::into(canvas_max), || {
+        draw_list.add_rect(Into::<[f32; 2]>::into(origin), Into::<[f32; 2]>::into(canvas_max), options.background_color)
+            .filled(true).build();
+        if options.render_grid {
+            let step = options.grid_size * factor / GRID_SUBDIVISIONS as f32;
+            if step > 2. {
+                let first = (-view.position * factor / step).floor();
+                for axis in 0..2 {
+                    let mut i = first[axis] as i32;
+                    loop {
+                        let along = origin[axis] + view.position[axis] * factor + i as f32 * step;
+                        if along > canvas_max[axis] {
+                            break;
+                        }
+                        let color = if i % GRID_SUBDIVISIONS == 0 { options.grid_color2 } else { options.grid_color };
+                        let (a, b) = if axis == 0 {
+                            ([along, origin.y], [along, canvas_max.y])
+                        } else {
+                            ([origin.x, along], [canvas_max.x, along])
+                        };
+                        draw_list.add_line(a, b, color).build();
+                        i += 1;
+                    }
+                }
+            }
+        }
+
+        // links
+        for index in 0..delegate.get_link_count() {
+            let link = delegate.get_link(index);
+            if link.output_node >= node_count || link.input_node >= node_count {
+                continue;
+            }
+            let output = SlotRef { node: link.output_node, slot: link.output_slot, output: true };
+            let input = SlotRef { node: link.input_node, slot: link.input_slot, output: false };
+            let points = link_points(
+                view.graph_to_screen(slot_position(delegate, output)),
+                view.graph_to_screen(slot_position(delegate, input)),
+                factor, options.display_links_as_curves
+            );
+            let hovered_link = hovered && over_node.is_none() && !over_minimap
+                && distance_to_polyline(mouse, &points) < LINK_HOVER_DISTANCE;
+            let thickness = options.line_thickness * factor * if hovered_link { 1.5 } else { 1. };
+            draw_list.add_polyline(points.into_iter().map(Into::<[f32; 2]>::into).collect(), slot_color(delegate, output, options))
+                .thickness(thickness).build();
+        }
+
+        // nodes
+        for (node, &[min, max]) in rects.iter().enumerate() {
+            if min.cmpgt(canvas_max).any() || max.cmplt(origin).any() {
+                continue;
+            }
+            let template = delegate.get_template(delegate.get_node_template(node));
+            let over = over_node == Some(node);
+            let rounding = options.rounding * factor;
+            let background = if over { template.background_color_over } else { template.background_color };
+            draw_list.add_rect(Into::<[f32; 2]>::into(min), Into::<[f32; 2]>::into(max), background)
+                .filled(true).rounding(rounding).build();
+            let header_max = Vec2::new(max.x, (min.y + HEADER_HEIGHT * factor).min(max.y));
+            draw_list.add_rect(Into::<[f32; 2]>::into(min), Into::<[f32; 2]>::into(header_max), template.header_color)
+                .filled(true).rounding(rounding).build();
+            let (border_color, border_thickness) = if delegate.is_node_selected(node) {
+                (options.selected_node_border_color, options.border_selection_thickness)
+            } else { (options.node_border_color, options.border_thickness) };
+            draw_list.add_rect(Into::<[f32; 2]>::into(min), Into::<[f32; 2]>::into(max), border_color)
+                .rounding(rounding).thickness(border_thickness * factor).build();
+            draw_list.with_clip_rect_intersect(Into::<[f32; 2]>::into(min), Into::<[f32; 2]>::into(max), || {
+                draw_list.add_text(Into::<[f32; 2]>::into(min + Vec2::new(4., 2.) * factor), 0xffffffff, delegate.get_node_name(node));
+            });
+            let show_names = over || !options.draw_io_name_on_hover;
+            for (output, slots) in [(false, &template.inputs), (true, &template.outputs)] {
+                for (slot, slot_template) in slots.iter().enumerate() {
+                    let slot_ref = SlotRef { node, slot, output };
+                    let pos = view.graph_to_screen(slot_position(delegate, slot_ref));
+                    let color = slot_template.color.unwrap_or(options.default_slot_color);
+                    let radius = if over_slot == Some(slot_ref) { slot_radius * options.node_slot_hover_factor } else { slot_radius };
+                    draw_list.add_circle(Into::<[f32; 2]>::into(pos), radius, color).filled(true).build();
+                    if show_names {
+                        let text_size = Vec2::from(ui.calc_text_size(&slot_template.name));
+                        let offset = slot_radius + 4.;
+                        let text_pos = if output {
+                            pos - Vec2::new(offset + text_size.x, text_size.y * 0.5)
+                        } else {
+                            pos + Vec2::new(offset, -text_size.y * 0.5)
+                        };
+                        draw_list.add_text(Into::<[f32; 2]>::into(text_pos), 0xffffffff, &slot_template.name);
+                    }
+                }
+            }
+        }
+
+        if let Some(from) = dragged_link {
+            let start = view.graph_to_screen(slot_position(delegate, from));
+            let (a, b) = if from.output { (start, mouse) } else { (mouse, start) };
+            let points = link_points(a, b, factor, options.display_links_as_curves);
+            draw_list.add_polyline(points.into_iter().map(Into::<[f32; 2]>::into).collect(), slot_color(delegate, from, options))
+                .thickness(options.line_thickness * factor).build();
+        }
+        if let Some([min, max]) = selection_rect {
+            draw_list.add_rect(Into::<[f32; 2]>::into(min), Into::<[f32; 2]>::into(max), options.quad_selection)
+                .filled(true).build();
+            draw_list.add_rect(Into::<[f32; 2]>::into(min), Into::<[f32; 2]>::into(max), options.quad_selection_border)
+                .build();
+        }
+    });
+
+    // node contents go on top, since drawing them needs the delegate to be mutable
+    for (node, [min, max]) in rects.iter().enumerate() {
+        if min.cmpgt(canvas_max).any() || max.cmplt(origin).any() {
+            continue;
+        }
+        let content_min = Vec2::new(min.x, (min.y + HEADER_HEIGHT * factor).min(max.y));
+        draw_list.with_clip_rect_intersect(Into::<[f32; 2]>::into(origin), Into::<[f32; 2]>::into(canvas_max), || {
+            delegate.custom_draw(&draw_list, [content_min, *max], node);
+        });
+    }
+
+    if options.show_minimap && node_count > 0 {
+        // node bounds and the visible area, fitted into the minimap keeping their aspect ratio
+        let (world_min, world_max) = (bounds.0.min(origin), bounds.1.max(canvas_max));
+        let extent = (world_max - world_min).max(Vec2::ONE);
+        let map_size = minimap_rect[1] - minimap_rect[0];
+        let scale = (map_size / extent).min_element();
+        let map_origin = minimap_rect[0] + (map_size - extent * scale) * 0.5;
+        let to_map = |p: Vec2| map_origin + (p - world_min) * scale;
+        if delegate.get_state().interaction == Interaction::Minimap {
+            // center the view on the mouse
+            let target = world_min + (mouse - map_origin) / scale;
+            let center = origin + size * 0.5;
+            let state = delegate.get_state_mut();
+            state.position += (center - target) / factor;
+        }
+        draw_list.add_rect(Into::<[f32; 2]>::into(minimap_rect[0]), Into::<[f32; 2]>::into(minimap_rect[1]),
+            if over_minimap { 0xc0404040 } else { 0xa0303030 }).filled(true).build();
+        for (node, [min, max]) in rects.iter().enumerate() {
+            let color = if delegate.is_node_selected(node) {
+                options.selected_node_border_color
+            } else {
+                delegate.get_template(delegate.get_node_template(node)).header_color
+            };
+            draw_list.add_rect(Into::<[f32; 2]>::into(to_map(*min)), Into::<[f32; 2]>::into(to_map(*max)), color)
+                .filled(true).build();
+        }
+        draw_list.add_rect(Into::<[f32; 2]>::into(to_map(origin)), Into::<[f32; 2]>::into(to_map(canvas_max)), 0xffffffff)
+            .build();
+    }
+    if ui.is_window_focused() {
+        draw_list.add_rect(Into::<[f32; 2]>::into(origin), Into::<[f32; 2]>::into(canvas_max), options.frame_focus)
+            .build();
+    }
+    changed
+}
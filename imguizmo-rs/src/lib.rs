@@ -4,4 +4,9 @@ extern crate imgui_original as imgui;
 #[cfg(feature = "imgui-riri")]
 extern crate imgui_riri as imgui;
 
-pub mod sequencer;
\ No newline at end of file
+pub mod curve_edit;
+pub mod gizmo;
+pub mod gradient;
+pub mod graph_editor;
+pub mod sequencer;
+pub mod zoom_slider;
\ No newline at end of file
@@ -9,7 +9,14 @@
 
 use bitflags::bitflags;
 use glam::Vec2;
-use imgui::Ui;
+use imgui::{ DrawListMut, ImColor32, Key, MouseButton, Style, StyleColor, Ui };
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+pub mod lanes;
+#[cfg(feature = "serde")]
+pub mod persist;
+pub mod timeline;
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -24,21 +31,275 @@ bitflags! {
     }
 }
 
-#[derive(Debug)]
-#[allow(dead_code)]
-pub(super) struct CustomDraw {
+/// Regions given to [`Sequencer::custom_draw`] and [`Sequencer::custom_draw_compact`].
+/// `custom_rect` spans the whole frame range of the sequence (so it's usually wider
+/// than the canvas) while `clip_rect` is the part of it that's visible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CustomDraw {
     index: usize,
     custom_rect: [Vec2; 2],
     legend_rect: [Vec2; 2],
     clip_rect: [Vec2; 2],
     legend_clip_rect: [Vec2; 2],
+    frame_min: u32,
+    frame_pixel_width: f32,
 }
 
 impl CustomDraw {
+    pub fn get_index(&self) -> usize { self.index }
+    pub fn get_custom_rect(&self) -> [Vec2; 2] { self.custom_rect }
+    pub fn get_legend_rect(&self) -> [Vec2; 2] { self.legend_rect }
+    pub fn get_clip_rect(&self) -> [Vec2; 2] { self.clip_rect }
+    pub fn get_legend_clip_rect(&self) -> [Vec2; 2] { self.legend_clip_rect }
+    pub fn get_frame_pixel_width(&self) -> f32 { self.frame_pixel_width }
+
+    /// Screen X coordinate of the left edge of `frame`. Fractional frames are allowed.
+    pub fn frame_to_x(&self, frame: f32) -> f32 {
+        self.custom_rect[0].x + (frame - self.frame_min as f32) * self.frame_pixel_width
+    }
+
+    /// Inverse of [`CustomDraw::frame_to_x`]
+    pub fn x_to_frame(&self, x: f32) -> f32 {
+        (x - self.custom_rect[0].x) / self.frame_pixel_width + self.frame_min as f32
+    }
+}
+
+const CURSOR_WIDTH: f32 = 8.;
+
+/// Range and appearance of a single sequencer entry. Frames are inclusive, so an
+/// item where `start == end` still occupies one frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SequencerItem {
+    pub start: u32,
+    pub end: u32,
+    pub item_type: usize,
+    pub color: u32
+}
+
+/// How dragging an item treats the other items on its track
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OverlapMode {
+    /// Neighbours are pushed along in front of the dragged item
+    Push,
+    /// The dragged item stops at its neighbours
+    Block
+}
+
+/// Items shifted along with an item being moved or resized in ripple mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RippleScope {
+    /// Only items after it on the same track
+    Track,
+    /// Items after it on every track
+    AllTracks
+}
+
+/// Rules applied when items are dragged with [`SequencerOptions::EditStartEnd`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SequencerEditMode {
+    /// Items move freely and may overlap
+    #[default]
+    Normal,
+    /// Items on the same track never overlap
+    NoOverlap(OverlapMode),
+    /// Moving an item or changing its end frame shifts every item starting after it
+    Ripple(RippleScope)
+}
+
+/// Part of an item grabbed by the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DragPart {
+    Start,
+    End,
+    Whole
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ItemDrag {
+    entry: usize,
+    part: DragPart,
+    /// Mouse x position matching the item's current range
+    anchor_x: f32
+}
+
+/// View state that ImSequencer keeps in static locals and pointer parameters
+/// (current frame, first visible frame, selected entry and zoom level).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SequencerState {
+    current_frame: u32,
+    first_frame: u32,
+    selected_entry: Option<usize>,
+    frame_pixel_width: f32,
+    frame_pixel_width_target: f32,
+    sub_sequence_path: Vec<usize>,
+    edit_mode: SequencerEditMode,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    drag: Option<ItemDrag>,
+    /// Width of the track area the last time the sequencer was drawn
+    #[cfg_attr(feature = "serde", serde(skip))]
+    track_width: f32,
+}
+
+impl Default for SequencerState {
+    fn default() -> Self {
+        Self {
+            current_frame: 0,
+            first_frame: 0,
+            selected_entry: None,
+            frame_pixel_width: 10.,
+            frame_pixel_width_target: 10.,
+            sub_sequence_path: vec![],
+            edit_mode: SequencerEditMode::Normal,
+            drag: None,
+            track_width: 0.,
+        }
+    }
+}
 
+impl SequencerState {
+    pub fn get_current_frame(&self) -> u32 { self.current_frame }
+    pub fn get_first_frame(&self) -> u32 { self.first_frame }
+    pub fn get_selected_entry(&self) -> Option<usize> { self.selected_entry }
+    pub fn get_frame_pixel_width(&self) -> f32 { self.frame_pixel_width }
+    /// Item indices leading from the root sequence to the nested sequence being edited
+    pub fn get_sub_sequence_path(&self) -> &[usize] { &self.sub_sequence_path }
+    pub fn get_edit_mode(&self) -> SequencerEditMode { self.edit_mode }
+    /// Whether an item is currently being dragged
+    pub fn is_editing(&self) -> bool { self.drag.is_some() }
+    /// First frame in view and the fractional frame at the right edge of the
+    /// tracks, as of the last time the sequencer was drawn
+    pub fn get_view_range(&self) -> (f32, f32) {
+        let first = self.first_frame as f32;
+        (first, first + self.track_width / self.frame_pixel_width.max(f32::EPSILON))
+    }
+
+    pub fn set_current_frame(&mut self, frame: u32) { self.current_frame = frame; }
+    pub fn set_first_frame(&mut self, frame: u32) { self.first_frame = frame; }
+    pub fn set_selected_entry(&mut self, entry: Option<usize>) { self.selected_entry = entry; }
+    pub fn set_frame_pixel_width(&mut self, width: f32) {
+        self.frame_pixel_width = width;
+        self.frame_pixel_width_target = width;
+    }
+    pub fn set_sub_sequence_path(&mut self, path: Vec<usize>) { self.sub_sequence_path = path; }
+    pub fn set_edit_mode(&mut self, mode: SequencerEditMode) { self.edit_mode = mode; }
+    /// Scroll and zoom so frames `lower` to `higher` fill the tracks, e.g. from a
+    /// [`crate::zoom_slider::ZoomSlider`]. Takes effect once the sequencer has been drawn.
+    pub fn set_view_range(&mut self, lower: f32, higher: f32) {
+        self.first_frame = lower.round().max(0.) as u32;
+        if self.track_width > 0. && higher > lower {
+            self.set_frame_pixel_width((self.track_width / (higher - lower)).clamp(0.1, 50.));
+        }
+    }
+
+    /// Scroll the view so that `frame` is visible, given the number of frames
+    /// that fit in the track area.
+    fn scroll_to(&mut self, frame: u32, visible_frame_count: u32) {
+        if frame < self.first_frame {
+            self.first_frame = frame;
+        } else if frame >= self.first_frame + visible_frame_count {
+            self.first_frame = frame + 1 - visible_frame_count.max(1);
+        }
+    }
+}
+
+/// A labelled frame drawn as a flag in the header and a line across the tracks
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SequencerMarker {
+    pub frame: u32,
+    pub label: String,
+    pub color: u32
 }
 
-#[derive(Debug)]
+/// Key bindings used while the sequencer is focused. Every binding can be
+/// reassigned, e.g. `SequencerKeymap { frame_selection: Key::Z, ..Default::default() }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequencerKeymap {
+    /// Move the current frame to the first frame
+    pub frame_min: Key,
+    /// Move the current frame to the last frame
+    pub frame_max: Key,
+    /// Step the current frame back by one frame
+    pub step_back: Key,
+    /// Step the current frame forward by one frame
+    pub step_forward: Key,
+    /// Held with `step_back`/`step_forward` to step by one tick instead
+    pub tick_modifier: Key,
+    /// Select the previous track
+    pub track_up: Key,
+    /// Select the next track
+    pub track_down: Key,
+    /// Remove the selected entry (requires `SequencerOptions::Delete`)
+    pub delete: Key,
+    /// Zoom and scroll so the selected entry fills the view
+    pub frame_selection: Key,
+    /// Return from a nested sequence to its parent
+    pub leave_sub_sequence: Key,
+}
+
+impl Default for SequencerKeymap {
+    fn default() -> Self {
+        Self {
+            frame_min: Key::Home,
+            frame_max: Key::End,
+            step_back: Key::LeftArrow,
+            step_forward: Key::RightArrow,
+            tick_modifier: Key::ModShift,
+            track_up: Key::UpArrow,
+            track_down: Key::DownArrow,
+            delete: Key::Delete,
+            frame_selection: Key::F,
+            leave_sub_sequence: Key::Escape,
+        }
+    }
+}
+
+/// Linear blend between two packed `0xAABBGGRR` colors
+fn mix_color(a: u32, b: u32, t: f32) -> u32 {
+    let (a, b) = (ImColor32::from_bits(a), ImColor32::from_bits(b));
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    ImColor32::from_rgba(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), mix(a.a, b.a)).to_bits()
+}
+
+fn with_alpha(col: u32, alpha: u8) -> u32 {
+    (col & 0x00ffffff) | ((alpha as u32) << 24)
+}
+
+fn style_color(style: &Style, col: StyleColor) -> u32 {
+    ImColor32::from(style.colors[col as usize]).to_bits()
+}
+
+/// Bar colors for one item type. `hovered` is used while the mouse is over the
+/// bar and `selected` while the item is the selected entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SequencerItemColors {
+    pub normal: u32,
+    pub hovered: u32,
+    pub selected: u32
+}
+
+impl SequencerItemColors {
+    /// Derive the hovered and selected variants by lightening `normal`
+    pub fn from_base(normal: u32) -> Self {
+        let normal = normal | 0xff000000;
+        Self {
+            normal,
+            hovered: mix_color(normal, 0xffffffff, 0.125),
+            selected: mix_color(normal, 0xffffffff, 0.3)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct SequencerStyle {
     header_color: u32,
     bg_color: u32,
@@ -46,7 +307,11 @@ pub struct SequencerStyle {
     head_tick_color: u32,
     body_tick_color: u32,
     frame_num_color: u32,
-    slot_color: [u32; 2]
+    slot_color: [u32; 2],
+    item_colors: Vec<SequencerItemColors>,
+    selection_color: u32,
+    playhead_color: u32,
+    playhead_text_color: u32
 }
 
 impl Default for SequencerStyle {
@@ -58,12 +323,49 @@ impl Default for SequencerStyle {
             head_tick_color: 0xFF606060,
             body_tick_color: 0x30606060,
             frame_num_color: 0xFFBBBBBB,
-            slot_color: [0xff3a3636, 0xff413d3d]
+            slot_color: [0xff3a3636, 0xff413d3d],
+            item_colors: vec![],
+            selection_color: 0x801080FF,
+            playhead_color: 0xA02A2AFF,
+            playhead_text_color: 0xFF2A2AFF
         }
     }
 }
 
 impl SequencerStyle {
+    /// Build a palette from the host application's imgui style, so the sequencer
+    /// follows light, dark or custom themes. Item types 0 to 3 take their colors
+    /// from the button, histogram, plot line and check mark colors, in that order.
+    pub fn from_imgui_style(style: &Style) -> Self {
+        let window_bg = style_color(style, StyleColor::WindowBg) | 0xff000000;
+        // frame colors are usually translucent, so flatten them onto the window
+        let flatten = |col: StyleColor| {
+            let c = ImColor32::from_bits(style_color(style, col));
+            mix_color(window_bg, c.to_bits() | 0xff000000, c.a as f32 / 255.)
+        };
+        let border = style_color(style, StyleColor::Border) | 0xff000000;
+        let item_colors = [
+            StyleColor::ButtonActive,
+            StyleColor::PlotHistogram,
+            StyleColor::PlotLines,
+            StyleColor::CheckMark
+        ].into_iter().map(|c| SequencerItemColors::from_base(style_color(style, c))).collect();
+        let playhead = style_color(style, StyleColor::PlotLinesHovered);
+        Self {
+            header_color: flatten(StyleColor::TitleBgActive),
+            bg_color: window_bg,
+            top_color: flatten(StyleColor::TitleBg),
+            head_tick_color: border,
+            body_tick_color: with_alpha(border, 0x30),
+            frame_num_color: style_color(style, StyleColor::TextDisabled),
+            slot_color: [flatten(StyleColor::FrameBg), flatten(StyleColor::FrameBgHovered)],
+            item_colors,
+            selection_color: with_alpha(style_color(style, StyleColor::Header), 0x80),
+            playhead_color: with_alpha(playhead, 0xa0),
+            playhead_text_color: playhead | 0xff000000
+        }
+    }
+
     pub fn set_header_color(mut self, col: u32) -> Self {
         self.header_color = col;
         self
@@ -99,6 +401,34 @@ impl SequencerStyle {
         self
     }
 
+    pub fn set_item_colors(mut self, col: Vec<SequencerItemColors>) -> Self {
+        self.item_colors = col;
+        self
+    }
+
+    pub fn set_item_type_color(mut self, item_type: usize, col: SequencerItemColors) -> Self {
+        if self.item_colors.len() <= item_type {
+            self.item_colors.resize(item_type + 1, SequencerItemColors::from_base(0xff808080));
+        }
+        self.item_colors[item_type] = col;
+        self
+    }
+
+    pub fn set_selection_color(mut self, col: u32) -> Self {
+        self.selection_color = col;
+        self
+    }
+
+    pub fn set_playhead_color(mut self, col: u32) -> Self {
+        self.playhead_color = col;
+        self
+    }
+
+    pub fn set_playhead_text_color(mut self, col: u32) -> Self {
+        self.playhead_text_color = col;
+        self
+    }
+
     fn get_header_color(&self) -> u32 { self.header_color }
     fn get_bg_color(&self) -> u32 { self.bg_color }
     fn get_top_color(&self) -> u32 { self.top_color }
@@ -106,140 +436,847 @@ impl SequencerStyle {
     fn get_body_tick_color(&self) -> u32 { self.body_tick_color }
     fn get_frame_num_color(&self) -> u32 { self.frame_num_color }
     fn get_slot_color(&self) -> [u32; 2] { self.slot_color }
+    fn get_selection_color(&self) -> u32 { self.selection_color }
+    fn get_playhead_color(&self) -> u32 { self.playhead_color }
+    fn get_playhead_text_color(&self) -> u32 { self.playhead_text_color }
 
+    /// Colors for an item, falling back to variants of the item's own color when
+    /// no colors were registered for its type
+    pub fn get_item_colors(&self, item: &SequencerItem) -> SequencerItemColors {
+        match self.item_colors.get(item.item_type) {
+            Some(c) => *c,
+            None => SequencerItemColors::from_base(item.color)
+        }
+    }
 }
 
 pub trait Sequencer {
     fn get_frame_min(&self) -> u32;
     fn get_frame_max(&self) -> u32;
     fn get_item_count(&self) -> usize;
+    fn get_item(&self, index: usize) -> SequencerItem;
 
     fn set_focused(&mut self, focused: bool);
     fn get_focused(&self) -> bool;
     fn get_style(&self) -> &SequencerStyle;
+    fn get_state(&self) -> &SequencerState;
+    fn get_state_mut(&mut self) -> &mut SequencerState;
+
+    fn get_item_label(&self, _index: usize) -> &str { "" }
+    /// Row an item is drawn on. As in ImSequencer, every item has its own track by default.
+    fn get_item_track(&self, index: usize) -> usize { index }
+    fn get_track_count(&self) -> usize { self.get_item_count() }
+    fn get_track_label(&self, track: usize) -> &str {
+        if track < self.get_item_count() { self.get_item_label(track) } else { "" }
+    }
+    fn get_markers(&self) -> &[SequencerMarker] { &[] }
+
+    fn del(&mut self, _index: usize) {}
+    /// Store a new range for an item dragged with [`SequencerOptions::EditStartEnd`]
+    fn set_item_range(&mut self, _index: usize, _start: u32, _end: u32) {}
+    /// Called before the first change of a drag
+    fn begin_edit(&mut self, _index: usize) {}
+    /// Called when the dragged item is released
+    fn end_edit(&mut self) {}
+    /// Called when an item without a nested sequence is double-clicked
+    fn double_click(&mut self, _index: usize) {}
+
+    /// Nested sequence represented by an item. Double-clicking the item opens it in
+    /// the same widget, and its items are previewed inside the item's bar.
+    fn get_sub_sequence(&self, _index: usize) -> Option<&dyn Sequencer> { None }
+    fn get_sub_sequence_mut(&mut self, _index: usize) -> Option<&mut dyn Sequencer> { None }
+
+    /// Extra height below a track's bars, for content drawn in [`Sequencer::custom_draw`]
+    fn get_custom_height(&self, _index: usize) -> f32 { 0. }
+    /// Draw into the area below a track with a non-zero custom height
+    fn custom_draw(&mut self, _index: usize, _draw_list: &DrawListMut, _custom: &CustomDraw) {}
+    /// Draw over a track without custom height
+    fn custom_draw_compact(&mut self, _index: usize, _draw_list: &DrawListMut, _custom: &CustomDraw) {}
     /* 
 
-    fn begin_edit(&self, index: usize);
-    fn end_edit(&self);
     fn get_item_type_count(&self) -> u32 { 0 }
     fn get_item_type_name(&self, index: usize) -> &str { "" }
-    fn get_item_label(&self, index: usize) -> &str { "" }
 
     fn copy(&self);
     fn paste(&self);
-
-    fn get_custom_height(&self, index: usize) -> usize { 0 }
-    fn double_click(&self, index: usize);
     */
 
-    fn draw(&mut self, ui: &Ui, flags: SequencerOptions) {
-        let draw_list = ui.get_window_draw_list();
-        let canvas_pos = Vec2::from(ui.cursor_screen_pos());
-        let canvas_size = Vec2::from(ui.content_region_avail());
-
-        let frame_pixel_width = 10.;
-        let frame_pixel_width_target = 10.;
-        let legend_width = 200;
-        let item_height = 20.;
-        let frame_count = (self.get_frame_max() - self.get_frame_min()).max(1);
-        let control_height = self.get_item_count() as f32 * item_height;
-        // zoom in/out
-        let visible_frame_count = ((canvas_size.x - legend_width as f32) / frame_pixel_width) as u32;
-        let bar_width_ratio = (visible_frame_count as f32 / frame_count as f32).min(1.);
-        let bar_width_pixels = bar_width_ratio * (canvas_size.x - legend_width as f32);
-        // header
-        let header_rect = [ canvas_pos, canvas_pos + Vec2::new(canvas_size.x,  item_height) ];
-        let header_size: [f32; 2] = (header_rect[1] - header_rect[0]).into();
-        // let scroll_size = Vec2::new(canvas_size.x, 14.);
-        if ui.invisible_button("topBar", header_size) {}
-        let content_bar_size: [f32; 2] = [ canvas_size.x, control_height ];
-        if ui.invisible_button("contentBar", content_bar_size) {}
+    /// Apply the bindings in `keymap` for this frame. `frame_step` is the frame
+    /// distance between two header ticks at the current zoom level.
+    fn handle_keyboard(&mut self, ui: &Ui, flags: SequencerOptions, keymap: &SequencerKeymap,
+        visible_frame_count: u32, frame_step: u32) {
+        let (frame_min, frame_max) = (self.get_frame_min(), self.get_frame_max());
+        let step = if is_modifier_down(ui, keymap.tick_modifier) { frame_step } else { 1 };
+        let current_frame = self.get_state().current_frame;
+        let new_frame = if ui.is_key_pressed(keymap.frame_min) {
+            Some(frame_min)
+        } else if ui.is_key_pressed(keymap.frame_max) {
+            Some(frame_max)
+        } else if ui.is_key_pressed(keymap.step_back) {
+            Some(current_frame.saturating_sub(step).max(frame_min))
+        } else if ui.is_key_pressed(keymap.step_forward) {
+            Some((current_frame + step).min(frame_max))
+        } else { None };
+        if let Some(frame) = new_frame {
+            let state = self.get_state_mut();
+            state.current_frame = frame;
+            state.scroll_to(frame, visible_frame_count);
+        }
+        let track_count = self.get_track_count();
+        if track_count > 0 {
+            let selected_track = self.get_state().selected_entry
+                .filter(|s| *s < self.get_item_count())
+                .map(|s| self.get_item_track(s));
+            // skip over tracks without items
+            let target = if ui.is_key_pressed(keymap.track_up) {
+                (0..selected_track.unwrap_or(track_count)).rev()
+                    .find_map(|t| nearest_item_on_track(self, t, current_frame))
+            } else if ui.is_key_pressed(keymap.track_down) {
+                (selected_track.map_or(0, |t| t + 1)..track_count)
+                    .find_map(|t| nearest_item_on_track(self, t, current_frame))
+            } else { None };
+            if target.is_some() {
+                self.get_state_mut().selected_entry = target;
+            }
+        }
+        let selected = match self.get_state().selected_entry {
+            Some(s) if s < self.get_item_count() => s,
+            _ => return
+        };
+        if flags.contains(SequencerOptions::Delete) && ui.is_key_pressed(keymap.delete) {
+            self.del(selected);
+            self.get_state_mut().selected_entry = None;
+        } else if ui.is_key_pressed(keymap.frame_selection) {
+            let item = self.get_item(selected);
+            let frames = (item.end.saturating_sub(item.start) + 1) as f32;
+            let state = self.get_state_mut();
+            // leave a frame of padding on both sides of the item. The view starts on
+            // a whole frame, so half a frame on each side can't be centered.
+            let track_width = visible_frame_count as f32 * state.frame_pixel_width;
+            state.frame_pixel_width_target = (track_width / (frames + 2.)).clamp(0.1, 50.);
+            state.first_frame = item.start.saturating_sub(1).max(frame_min);
+        }
+    }
+
+    fn draw(&mut self, ui: &Ui, flags: SequencerOptions, keymap: &SequencerKeymap) {
+        let mut path = self.get_state().sub_sequence_path.clone();
+        let mut breadcrumb = vec![String::from("Root")];
+        // keyboard focus is tracked on the root, so a sequence that was just
+        // entered takes input from its first frame
+        let focused = self.get_focused();
+        let action = draw_nested(self, &path, &mut breadcrumb, ui, flags, keymap, focused);
         self.set_focused(ui.is_window_focused());
-        let content_rect = [
-            Vec2::from(ui.item_rect_min()),
-            Vec2::from(ui.item_rect_max()),
-        ];
-
-        draw_list.add_rect(
-            Into::<[f32; 2]>::into(header_rect[0]),
-            Into::<[f32; 2]>::into(header_rect[1]), 
-            self.get_style().get_header_color()).filled(true).build();
-        // draw background
-        let bg_rect = [canvas_pos, canvas_pos + canvas_size];
-        draw_list.add_rect(
-            Into::<[f32; 2]>::into(bg_rect[0]), 
-            Into::<[f32; 2]>::into(bg_rect[1]), 
-            self.get_style().get_bg_color()).filled(true).build();
-        // frame top
-        let top_rect = [
-            Vec2::new(canvas_pos.x + legend_width as f32, canvas_pos.y),
-            Vec2::new(canvas_pos.x + canvas_size.x, canvas_pos.y + item_height),
-        ];
-        draw_list.add_rect(
-            Into::<[f32; 2]>::into(top_rect[0]), 
-            Into::<[f32; 2]>::into(top_rect[1]), 
-            self.get_style().get_top_color()).filled(true).build();
-        if flags.contains(SequencerOptions::Add) {
-
-        }
-        // frame number and lines
-        let mut mod_frame_count = 10;
-        let mut frame_step = 1;
-        while mod_frame_count * (frame_pixel_width as u32) < 150 {
-            mod_frame_count *= 2;
-            frame_step *= 2;
-        }
-        let half_mod_frame_count = mod_frame_count / 2;
-
-        let draw_line = |i: u32, region_height| {
-            let base_index = (i % mod_frame_count) == 0 || i == self.get_frame_max() || i == self.get_frame_min();
-            let half_index = i % half_mod_frame_count == 0;
-            let px = canvas_pos.x + (i as f32 * frame_pixel_width) + legend_width as f32;
-            let tick_start = if base_index { 4. } else { if half_index { 10. } else { 14. }};
-            let tick_end = if base_index { region_height } else { item_height };
-            if px <= canvas_pos.x + canvas_size.x && px >= canvas_pos.x + legend_width as f32 {
-                draw_list.add_line([px, canvas_pos.y + tick_start], [px, canvas_pos.y + tick_end], self.get_style().get_head_tick_color()).thickness(1.).build();
-                // draw_list.add_line([px, canvas_pos.y + item_height],[px, canvas_pos.y + region_height - 1.],BODY_TICK_COLOR).thickness(1.).build();
+        // drop any part of the path that no longer leads to a nested sequence
+        path.truncate(breadcrumb.len() - 1);
+        match action {
+            Some(SubSequenceAction::Enter(index)) => path.push(index),
+            Some(SubSequenceAction::Leave(depth)) => path.truncate(depth),
+            None => ()
+        }
+        self.get_state_mut().sub_sequence_path = path;
+    }
+}
+
+/// Navigation between nested sequences requested while drawing
+enum SubSequenceAction {
+    /// Open the nested sequence of this item
+    Enter(usize),
+    /// Return to this depth of the breadcrumb path
+    Leave(usize)
+}
+
+fn draw_nested<S: Sequencer + ?Sized>(seq: &mut S, path: &[usize], breadcrumb: &mut Vec<String>,
+    ui: &Ui, flags: SequencerOptions, keymap: &SequencerKeymap, focused: bool) -> Option<SubSequenceAction> {
+    match path.split_first() {
+        Some((index, rest)) if *index < seq.get_item_count() && seq.get_sub_sequence(*index).is_some() => {
+            breadcrumb.push(seq.get_item_label(*index).to_owned());
+            draw_nested(seq.get_sub_sequence_mut(*index).unwrap(), rest, breadcrumb, ui, flags, keymap, focused)
+        },
+        _ => draw_sequence(seq, ui, flags, keymap, breadcrumb, focused)
+    }
+}
+
+/// Item on `track` closest to `frame`, preferring one that contains it
+fn nearest_item_on_track<S: Sequencer + ?Sized>(seq: &S, track: usize, frame: u32) -> Option<usize> {
+    (0..seq.get_item_count())
+        .filter(|i| seq.get_item_track(*i) == track)
+        .min_by_key(|i| {
+            let item = seq.get_item(*i);
+            item.start.saturating_sub(frame).max(frame.saturating_sub(item.end))
+        })
+}
+
+/// Item on `track` whose bar covers `frame`, the last drawn one if several overlap
+fn item_at<S: Sequencer + ?Sized>(seq: &S, track: usize, frame: f32) -> Option<usize> {
+    (0..seq.get_item_count()).rev()
+        .filter(|i| seq.get_item_track(*i) == track)
+        .find(|i| {
+            let item = seq.get_item(*i);
+            item.start as f32 <= frame && frame < item.end as f32 + 1.
+        })
+}
+
+/// Move one edge or the whole of an item by `delta` frames, resolving the
+/// result against the sequence's edit mode. Returns the number of frames the
+/// dragged part actually moved.
+fn drag_item<S: Sequencer + ?Sized>(seq: &mut S, index: usize, part: DragPart, delta: i64) -> i64 {
+    let mode = seq.get_state().edit_mode;
+    let frame_min = seq.get_frame_min() as i64;
+    let item = seq.get_item(index);
+    let (start, end) = (item.start as i64, item.end as i64);
+    let track = seq.get_item_track(index);
+    // (index, start, end, on the same track) of every other item
+    let others: Vec<(usize, i64, i64, bool)> = (0..seq.get_item_count())
+        .filter(|j| *j != index)
+        .map(|j| {
+            let other = seq.get_item(j);
+            (j, other.start as i64, other.end as i64, seq.get_item_track(j) == track)
+        })
+        .collect();
+    let neighbours = || others.iter().copied().filter(|n| n.3);
+    let mut delta = match part {
+        // an item can end before the first frame after the frame range changed,
+        // so clamp with max and min, which unlike clamp don't panic
+        DragPart::Start => delta.max(frame_min - start).min(end - start),
+        DragPart::End => delta.max(start - end),
+        DragPart::Whole => delta.max(frame_min - start)
+    };
+    match mode {
+        SequencerEditMode::NoOverlap(OverlapMode::Block) => {
+            let left = neighbours().filter(|n| n.2 < start).map(|n| n.2 + 1).max();
+            let right = neighbours().filter(|n| n.1 > end).map(|n| n.1 - 1).min();
+            if let Some(left) = left.filter(|_| part != DragPart::End) {
+                delta = delta.max(left - start);
             }
-            if base_index && px >= canvas_pos.x + legend_width as f32 {
-                draw_list.add_text([px + 3., canvas_pos.y], self.get_style().get_frame_num_color(), &format!("{}", i));
+            if let Some(right) = right.filter(|_| part != DragPart::Start) {
+                delta = delta.min(right - end);
             }
-        };
-        let draw_line_content = |i: u32, item_height: f32| {
-            let px = canvas_pos.x + (i as f32 * frame_pixel_width) + legend_width as f32;
-            if px <= canvas_pos.x + canvas_size.x && px >= canvas_pos.x + legend_width as f32 {
-                draw_list.add_line([px, content_rect[0].y], [px, content_rect[1].y], self.get_style().get_body_tick_color()).thickness(1.).build();
+        },
+        SequencerEditMode::NoOverlap(OverlapMode::Push) if part != DragPart::End => {
+            // earlier items can be pushed back until they're packed against the first frame
+            let packed: i64 = neighbours().filter(|n| n.1 < start).map(|n| n.2 - n.1 + 1).sum();
+            delta = delta.max(frame_min + packed - start);
+            if part == DragPart::Start {
+                delta = delta.min(end - start);
+            }
+        },
+        _ => ()
+    }
+    if delta == 0 {
+        return 0;
+    }
+    let (new_start, new_end) = match part {
+        DragPart::Start => (start + delta, end),
+        DragPart::End => (start, end + delta),
+        DragPart::Whole => (start + delta, end + delta)
+    };
+    seq.set_item_range(index, new_start as u32, new_end as u32);
+    match mode {
+        SequencerEditMode::NoOverlap(OverlapMode::Push) => {
+            let mut after: Vec<_> = neighbours().filter(|n| n.1 >= start).collect();
+            after.sort_by_key(|n| n.1);
+            let mut bound = new_end;
+            for (j, s, e, _) in after {
+                let shift = (bound + 1 - s).max(0);
+                if shift > 0 {
+                    seq.set_item_range(j, (s + shift) as u32, (e + shift) as u32);
+                }
+                bound = bound.max(e + shift);
+            }
+            let mut before: Vec<_> = neighbours().filter(|n| n.1 < start).collect();
+            before.sort_by_key(|n| std::cmp::Reverse(n.2));
+            let mut bound = new_start;
+            for (j, s, e, _) in before {
+                let shift = (e + 1 - bound).max(0).min(s - frame_min);
+                if shift > 0 {
+                    seq.set_item_range(j, (s - shift) as u32, (e - shift) as u32);
+                }
+                bound = bound.min(s - shift);
+            }
+        },
+        SequencerEditMode::Ripple(scope) => {
+            let shift = new_end - end;
+            if shift != 0 {
+                for (j, s, e, same_track) in others.iter().copied().filter(|n| n.1 > end) {
+                    if scope == RippleScope::AllTracks || same_track {
+                        seq.set_item_range(j, (s + shift) as u32, (e + shift) as u32);
+                    }
+                }
+            }
+        },
+        _ => ()
+    }
+    delta
+}
+
+/// `Ui::is_key_down` asserts on the `Mod*` keys in some imgui versions, so read
+/// those from the IO state instead
+fn is_modifier_down(ui: &Ui, key: Key) -> bool {
+    match key {
+        Key::ModShift => ui.io().key_shift,
+        Key::ModCtrl => ui.io().key_ctrl,
+        Key::ModAlt => ui.io().key_alt,
+        Key::ModSuper => ui.io().key_super,
+        _ => ui.is_key_down(key)
+    }
+}
+
+fn mouse_in_rect(pos: Vec2, min: Vec2, max: Vec2) -> bool {
+    pos.cmpge(min).all() && pos.cmplt(max).all()
+}
+
+/// Intersection of two rects, or `None` if they don't overlap
+fn intersect_rect(a: [Vec2; 2], b: [Vec2; 2]) -> Option<[Vec2; 2]> {
+    let rect = [a[0].max(b[0]), a[1].min(b[1])];
+    rect[0].cmplt(rect[1]).all().then_some(rect)
+}
+
+/// Draws into the window draw list while keeping every vertex inside `clip`.
+/// Clip rects only scissor pixels when the draw data is rendered, so shapes are
+/// also clamped or culled here and text uses imgui's CPU-side fine clipping.
+struct ClippedDraw<'a, 'ui> {
+    draw_list: &'a DrawListMut<'ui>,
+    clip: Option<[Vec2; 2]>
+}
+
+impl<'a, 'ui> ClippedDraw<'a, 'ui> {
+    fn new(draw_list: &'a DrawListMut<'ui>, clip: [Vec2; 2]) -> Self {
+        Self { draw_list, clip: intersect_rect(clip, clip) }
+    }
+
+    /// Narrow the clip region to `clip`
+    fn region(&self, clip: [Vec2; 2]) -> Self {
+        Self { draw_list: self.draw_list, clip: self.clip.and_then(|c| intersect_rect(c, clip)) }
+    }
+
+    /// Also push the region as a clip rect while running `f`, for draw calls
+    /// made by user code
+    fn scissor<F: FnOnce()>(&self, f: F) {
+        if let Some(clip) = self.clip {
+            self.draw_list.with_clip_rect_intersect(
+                Into::<[f32; 2]>::into(clip[0]),
+                Into::<[f32; 2]>::into(clip[1]),
+                f);
+        }
+    }
+
+    fn rect(&self, min: Vec2, max: Vec2, col: u32, rounding: f32) {
+        if let Some(rect) = self.clip.and_then(|c| intersect_rect(c, [min, max])) {
+            // only round corners that weren't cut off
+            let rounding = if rect == [min, max] { rounding } else { 0. };
+            self.draw_list.add_rect(
+                Into::<[f32; 2]>::into(rect[0]),
+                Into::<[f32; 2]>::into(rect[1]),
+                col
+            ).filled(true).rounding(rounding).build();
+        }
+    }
+
+    fn vline(&self, x: f32, y0: f32, y1: f32, col: u32) {
+        let Some(clip) = self.clip else { return };
+        if x < clip[0].x || x > clip[1].x {
+            return;
+        }
+        let (y0, y1) = (y0.max(clip[0].y), y1.min(clip[1].y));
+        if y0 < y1 {
+            self.draw_list.add_line([x, y0], [x, y1], col).thickness(1.).build();
+        }
+    }
+
+    fn triangle(&self, a: Vec2, b: Vec2, c: Vec2, col: u32) {
+        let Some(clip) = self.clip else { return };
+        if [a, b, c].iter().all(|p| p.cmpge(clip[0]).all() && p.cmple(clip[1]).all()) {
+            self.draw_list.add_triangle(
+                Into::<[f32; 2]>::into(a),
+                Into::<[f32; 2]>::into(b),
+                Into::<[f32; 2]>::into(c),
+                col
+            ).filled(true).build();
+        }
+    }
+
+    fn text(&self, pos: Vec2, col: u32, text: &str) {
+        let Some(clip) = self.clip else { return };
+        let clip = imgui::sys::ImVec4 { x: clip[0].x, y: clip[0].y, z: clip[1].x, w: clip[1].y };
+        // DrawListMut::add_text doesn't expose the fine clip rect, so call AddText
+        // directly on the current window's draw list (the same list as self.draw_list)
+        unsafe {
+            let start = text.as_ptr() as *const std::os::raw::c_char;
+            let end = start.add(text.len());
+            imgui::sys::ImDrawList_AddText_FontPtr(
+                imgui::sys::igGetWindowDrawList(),
+                imgui::sys::igGetFont(),
+                imgui::sys::igGetFontSize(),
+                imgui::sys::ImVec2 { x: pos.x, y: pos.y },
+                col,
+                start,
+                end,
+                0.,
+                &raw const clip
+            );
+        }
+    }
+}
+
+/// Draw the items of a nested sequence as thin bars inside its parent's bar
+fn draw_sub_sequence_preview(draw: &ClippedDraw, sub: &dyn Sequencer, min: Vec2, max: Vec2) {
+    let track_count = sub.get_track_count();
+    if track_count == 0 {
+        return;
+    }
+    let frame_min = sub.get_frame_min() as f32;
+    let frames = (sub.get_frame_max() as f32 - frame_min).max(0.) + 1.;
+    let width = max.x - min.x;
+    let row_height = ((max.y - min.y - 2.) / track_count as f32).max(1.);
+    for j in 0..sub.get_item_count() {
+        let y = min.y + 1. + sub.get_item_track(j) as f32 * row_height;
+        if y + row_height > max.y {
+            continue;
+        }
+        let item = sub.get_item(j);
+        let x0 = min.x + (item.start as f32 - frame_min) / frames * width;
+        let x1 = min.x + (item.end as f32 + 1. - frame_min) / frames * width;
+        draw.rect(
+            Vec2::new(x0.max(min.x), y),
+            Vec2::new(x1.min(max.x), (y + row_height - 1.).max(y + 1.)),
+            sub.get_style().get_item_colors(&item).normal,
+            0.
+        );
+    }
+}
+
+/// Draw a single level of the sequence. `breadcrumb` holds the labels of the
+/// sequences leading to `seq`, starting at the root, and `focused` whether the
+/// root had keyboard focus.
+fn draw_sequence<S: Sequencer + ?Sized>(seq: &mut S, ui: &Ui, flags: SequencerOptions,
+    keymap: &SequencerKeymap, breadcrumb: &[String], focused: bool) -> Option<SubSequenceAction> {
+    let draw_list = ui.get_window_draw_list();
+    let canvas_pos = Vec2::from(ui.cursor_screen_pos());
+    let canvas_size = Vec2::from(ui.content_region_avail());
+
+    let legend_width = 200.;
+    let item_height = 20.;
+    let frame_count = (seq.get_frame_max() - seq.get_frame_min()).max(1);
+    let mut action = None;
+    // zoom in/out
+    let frame_pixel_width = {
+        let state = seq.get_state_mut();
+        state.frame_pixel_width_target = state.frame_pixel_width_target.clamp(0.1, 50.);
+        state.frame_pixel_width += (state.frame_pixel_width_target - state.frame_pixel_width) * 0.33;
+        state.frame_pixel_width
+    };
+    seq.get_state_mut().track_width = (canvas_size.x - legend_width).max(0.);
+    let visible_frame_count = ((canvas_size.x - legend_width) / frame_pixel_width).max(0.) as u32;
+    // frame number and lines
+    let mut mod_frame_count = 10;
+    let mut frame_step = 1;
+    while (mod_frame_count as f32 * frame_pixel_width) < 150. {
+        mod_frame_count *= 2;
+        frame_step *= 2;
+    }
+    let half_mod_frame_count = mod_frame_count / 2;
+    if focused && !ui.io().want_text_input {
+        seq.handle_keyboard(ui, flags, keymap, visible_frame_count, frame_step);
+        if breadcrumb.len() > 1 && ui.is_key_pressed(keymap.leave_sub_sequence) {
+            action = Some(SubSequenceAction::Leave(breadcrumb.len() - 2));
+        }
+    }
+    if visible_frame_count >= frame_count {
+        let frame_min = seq.get_frame_min();
+        seq.get_state_mut().first_frame = frame_min;
+    }
+    let first_frame = seq.get_state().first_frame;
+    let last_frame = first_frame + visible_frame_count;
+    let item_count = seq.get_item_count();
+    let track_count = seq.get_track_count();
+    // top of each track relative to the content area, plus the total height at the end
+    let mut row_offsets = Vec::with_capacity(track_count + 1);
+    row_offsets.push(0.);
+    for i in 0..track_count {
+        row_offsets.push(row_offsets[i] + item_height + seq.get_custom_height(i));
+    }
+    let control_height = row_offsets[track_count];
+    // header
+    let header_rect = [ canvas_pos, canvas_pos + Vec2::new(canvas_size.x,  item_height) ];
+    let header_size: [f32; 2] = (header_rect[1] - header_rect[0]).into();
+    // let scroll_size = Vec2::new(canvas_size.x, 14.);
+    ui.invisible_button("topBar", header_size);
+    let content_bar_size: [f32; 2] = [ canvas_size.x, control_height ];
+    ui.invisible_button("contentBar", content_bar_size);
+    let content_clicked = ui.is_item_clicked_with_button(MouseButton::Left);
+    seq.set_focused(ui.is_window_focused());
+    let content_rect = [
+        Vec2::from(ui.item_rect_min()),
+        Vec2::from(ui.item_rect_max()),
+    ];
+    let mouse_pos = Vec2::from(ui.io().mouse_pos);
+    if content_clicked {
+        let mouse_y = mouse_pos.y - content_rect[0].y;
+        let mouse_x = mouse_pos.x - content_rect[0].x - legend_width;
+        let mouse_frame = first_frame as f32 + mouse_x / frame_pixel_width;
+        // clicks on the legend or between items clear the selection
+        let entry = row_offsets[1..].iter().position(|o| mouse_y < *o)
+            .filter(|_| mouse_x >= 0.)
+            .and_then(|track| item_at(seq, track, mouse_frame));
+        seq.get_state_mut().selected_entry = entry;
+    }
+    // move the dragged item by whole frames, keeping the remainder in the anchor
+    if let Some(drag) = seq.get_state().drag {
+        if drag.entry >= item_count || !ui.is_mouse_down(MouseButton::Left) {
+            seq.get_state_mut().drag = None;
+            seq.end_edit();
+        } else {
+            let delta = ((mouse_pos.x - drag.anchor_x) / frame_pixel_width) as i64;
+            if delta != 0 {
+                let moved = drag_item(seq, drag.entry, drag.part, delta);
+                let state = seq.get_state_mut();
+                state.drag = Some(ItemDrag { anchor_x: drag.anchor_x + moved as f32 * frame_pixel_width, ..drag });
+                state.selected_entry = Some(drag.entry);
+            }
+        }
+    }
+
+    // clip regions. Everything stays inside the canvas, the header keeps to the
+    // top row, item names to the legend and bars to the track area.
+    let canvas_max = canvas_pos + canvas_size;
+    let legend_x = canvas_pos.x + legend_width;
+    let canvas = ClippedDraw::new(&draw_list, [canvas_pos, canvas_max]);
+    let header = canvas.region(header_rect);
+    let header_legend = header.region([canvas_pos, Vec2::new(legend_x, header_rect[1].y)]);
+    let header_track = header.region([Vec2::new(legend_x, canvas_pos.y), header_rect[1]]);
+    let content = canvas.region([Vec2::new(canvas_pos.x, header_rect[1].y), canvas_max]);
+    let legend = content.region([content_rect[0], Vec2::new(legend_x, canvas_max.y)]);
+    let track = content.region([Vec2::new(legend_x, header_rect[1].y), canvas_max]);
+    // column below the header's frame numbers, for markers and the cursor
+    let frame_column = canvas.region([Vec2::new(legend_x, canvas_pos.y), canvas_max]);
+
+    header.rect(header_rect[0], header_rect[1], seq.get_style().get_header_color(), 0.);
+    // draw background
+    canvas.rect(canvas_pos, canvas_max, seq.get_style().get_bg_color(), 0.);
+    // frame top
+    header_track.rect(Vec2::new(legend_x, canvas_pos.y), header_rect[1], seq.get_style().get_top_color(), 0.);
+    if flags.contains(SequencerOptions::Add) {
+        // TODO: add entry button and popup
+    }
+    // breadcrumb path to a nested sequence
+    if breadcrumb.len() > 1 {
+        let mut x = canvas_pos.x + 4.;
+        let clicked = ui.is_mouse_clicked(MouseButton::Left);
+        for (depth, name) in breadcrumb.iter().enumerate() {
+            let last = depth == breadcrumb.len() - 1;
+            let size = Vec2::from(ui.calc_text_size(name));
+            let min = Vec2::new(x, canvas_pos.y + 2.);
+            let hovered = !last && mouse_in_rect(Vec2::from(ui.io().mouse_pos), min, min + size);
+            let color = if last || hovered { 0xffffffff } else { seq.get_style().get_frame_num_color() };
+            header_legend.text(min, color, name);
+            if hovered && clicked {
+                action = Some(SubSequenceAction::Leave(depth));
+            }
+            x += size.x;
+            if !last {
+                header_legend.text(Vec2::new(x, min.y), seq.get_style().get_frame_num_color(), " > ");
+                x += ui.calc_text_size(" > ")[0];
             }
+        }
+    }
+
+    let frame_to_x = |i: u32| legend_x + (i as f32 - first_frame as f32) * frame_pixel_width;
+    let draw_line = |i: u32, region_height| {
+        let base_index = i.is_multiple_of(mod_frame_count) || i == seq.get_frame_max() || i == seq.get_frame_min();
+        let half_index = i.is_multiple_of(half_mod_frame_count);
+        let px = frame_to_x(i);
+        let tick_start = if base_index { 4. } else if half_index { 10. } else { 14. };
+        let tick_end = if base_index { region_height } else { item_height };
+        header_track.vline(px, canvas_pos.y + tick_start, canvas_pos.y + tick_end, seq.get_style().get_head_tick_color());
+        if base_index {
+            header_track.text(Vec2::new(px + 3., canvas_pos.y), seq.get_style().get_frame_num_color(), &format!("{}", i));
+        }
+    };
+    let draw_line_content = |i: u32| {
+        track.vline(frame_to_x(i), content_rect[0].y, content_rect[1].y, seq.get_style().get_body_tick_color());
+    };
+
+    // only the ticks for frames inside the visible window
+    let tick_range = (first_frame.max(seq.get_frame_min()) / frame_step)..=(last_frame.min(seq.get_frame_max()) / frame_step);
+    // draw header ticks
+    for i in tick_range.clone() {
+        draw_line(i * frame_step, item_height);
+    }
+    draw_line(seq.get_frame_min(), item_height);
+    draw_line(seq.get_frame_max(), item_height);
+    // tracks that overlap the visible part of the content area
+    let visible_rows: Vec<usize> = (0..track_count)
+        .filter(|i| {
+            let top = content_rect[0].y + row_offsets[*i];
+            let bottom = content_rect[0].y + row_offsets[*i + 1];
+            bottom > header_rect[1].y && top < canvas_max.y
+        })
+        .collect();
+    // draw item names
+    for i in visible_rows.iter().copied() {
+        let text_pos = Vec2::new(content_rect[0].x + 3., content_rect[0].y + row_offsets[i] + 2.);
+        legend.text(text_pos, 0xffffffff, seq.get_track_label(i));
+    }
+    // slots
+    for i in visible_rows.iter().copied() {
+        let color = seq.get_style().get_slot_color()[i & 1];
+        let pos = Vec2::new(legend_x, content_rect[0].y + row_offsets[i] + 1.);
+        let size = Vec2::new(canvas_max.x, content_rect[0].y + row_offsets[i + 1]);
+        track.rect(pos, size, color, 0.);
+    }
+    // vertical lines in content
+    for i in tick_range {
+        draw_line_content(i * frame_step);
+    }
+    draw_line_content(seq.get_frame_min());
+    draw_line_content(seq.get_frame_max());
+    // selection
+    let selected_row = seq.get_state().selected_entry
+        .filter(|s| *s < seq.get_item_count())
+        .map(|s| seq.get_item_track(s))
+        .filter(|t| *t < track_count);
+    if let Some(selected) = selected_row {
+        let pos = Vec2::new(content_rect[0].x, content_rect[0].y + row_offsets[selected]);
+        let height = row_offsets[selected + 1] - row_offsets[selected];
+        content.rect(pos, pos + Vec2::new(canvas_size.x, height), seq.get_style().get_selection_color(), 1.);
+    }
+    // items
+    let mut row_visible = vec![false; track_count];
+    for i in visible_rows.iter().copied() {
+        row_visible[i] = true;
+    }
+    let mouse_in_track = track.clip.is_some_and(|c| mouse_in_rect(mouse_pos, c[0], c[1]));
+    let mut drag_start = None;
+    for i in 0..item_count {
+        let row = seq.get_item_track(i);
+        if !row_visible.get(row).copied().unwrap_or(false) {
+            continue;
+        }
+        let item = seq.get_item(i);
+        let row_y = content_rect[0].y + row_offsets[row] + 1.;
+        let p1 = Vec2::new(frame_to_x(item.start), row_y + 2.);
+        let p2 = Vec2::new(frame_to_x(item.end) + frame_pixel_width, row_y + item_height - 2.);
+        // cull items entirely outside the visible frame window
+        if item.end >= first_frame && item.start <= last_frame {
+            let colors = seq.get_style().get_item_colors(&item);
+            let hovered = mouse_in_rect(mouse_pos, p1, p2) && mouse_in_track;
+            let color = if seq.get_state().selected_entry == Some(i) {
+                colors.selected
+            } else if hovered {
+                colors.hovered
+            } else {
+                colors.normal
+            };
+            track.rect(p1, p2, color, 2.);
+            if let Some(sub) = seq.get_sub_sequence(i) {
+                draw_sub_sequence_preview(&track, sub, p1, p2);
+            }
+            if hovered && ui.is_mouse_double_clicked(MouseButton::Left) {
+                match seq.get_sub_sequence(i) {
+                    Some(_) => action = Some(SubSequenceAction::Enter(i)),
+                    None => seq.double_click(i)
+                }
+            }
+            // grab handles for both edges and the whole item
+            if flags.contains(SequencerOptions::EditStartEnd) && seq.get_state().drag.is_none()
+                && drag_start.is_none() && mouse_in_track {
+                let max_handle_width = (p2.x - p1.x) / 3.;
+                let handle_width = (frame_pixel_width / 2.).clamp(max_handle_width.min(10.), max_handle_width);
+                let handles = [
+                    (DragPart::Start, p1, Vec2::new(p1.x + handle_width, p2.y)),
+                    (DragPart::End, Vec2::new(p2.x - handle_width, p1.y), p2),
+                    (DragPart::Whole, p1, p2)
+                ];
+                if let Some((part, min, max)) = handles.into_iter().find(|h| mouse_in_rect(mouse_pos, h.1, h.2)) {
+                    if part != DragPart::Whole {
+                        track.rect(min, max, 0xffffffff, 2.);
+                    }
+                    if ui.is_mouse_clicked(MouseButton::Left) {
+                        drag_start = Some(ItemDrag { entry: i, part, anchor_x: mouse_pos.x });
+                    }
+                }
+            }
+        }
+    }
+    if let Some(drag) = drag_start {
+        let state = seq.get_state_mut();
+        state.drag = Some(drag);
+        state.selected_entry = Some(drag.entry);
+        seq.begin_edit(drag.entry);
+    }
+    let mut custom_draws = vec![];
+    let mut compact_custom_draws = vec![];
+    for i in visible_rows.iter().copied() {
+        // custom draw
+        let custom_height = row_offsets[i + 1] - row_offsets[i] - item_height;
+        let track_min = Vec2::new(frame_to_x(seq.get_frame_min()), content_rect[0].y + row_offsets[i]);
+        let track_max = Vec2::new(frame_to_x(seq.get_frame_max()) + frame_pixel_width, track_min.y + item_height);
+        let custom = CustomDraw {
+            index: i,
+            custom_rect: [track_min, track_max],
+            legend_rect: [Vec2::new(canvas_pos.x, track_min.y), Vec2::new(legend_x, track_max.y)],
+            clip_rect: [Vec2::new(legend_x, track_min.y), Vec2::new(canvas_max.x, track_max.y)],
+            legend_clip_rect: [Vec2::new(canvas_pos.x, track_min.y), Vec2::new(legend_x, track_max.y)],
+            frame_min: seq.get_frame_min(),
+            frame_pixel_width
         };
+        let custom = if custom_height > 0. {
+            let offset = Vec2::new(0., item_height);
+            let extend = Vec2::new(0., custom_height);
+            CustomDraw {
+                custom_rect: [track_min + offset, track_max + extend],
+                legend_rect: [custom.legend_rect[0] + offset, custom.legend_rect[1] + extend],
+                clip_rect: [custom.clip_rect[0] + offset, custom.clip_rect[1] + extend],
+                legend_clip_rect: [custom.legend_clip_rect[0] + offset, custom.legend_clip_rect[1] + extend],
+                ..custom
+            }
+        } else { custom };
+        // pass on only the visible part of each region
+        let Some(clip_rect) = track.region(custom.clip_rect).clip else { continue };
+        let legend_clip_rect = legend.region(custom.legend_clip_rect).clip
+            .unwrap_or([custom.legend_clip_rect[0]; 2]);
+        let custom = CustomDraw { clip_rect, legend_clip_rect, ..custom };
+        if custom_height > 0. {
+            custom_draws.push(custom);
+        } else {
+            compact_custom_draws.push(custom);
+        }
+    }
+    for custom in &custom_draws {
+        content.region([custom.legend_clip_rect[0], custom.clip_rect[1]])
+            .scissor(|| seq.custom_draw(custom.index, &draw_list, custom));
+    }
+    for custom in &compact_custom_draws {
+        track.region(custom.clip_rect)
+            .scissor(|| seq.custom_draw_compact(custom.index, &draw_list, custom));
+    }
+    // markers
+    for marker in seq.get_markers() {
+        let px = frame_to_x(marker.frame) + frame_pixel_width * 0.5;
+        frame_column.vline(px, canvas_pos.y, content_rect[1].y, marker.color);
+        frame_column.triangle(
+            Vec2::new(px, canvas_pos.y),
+            Vec2::new(px, canvas_pos.y + 8.),
+            Vec2::new(px + 6., canvas_pos.y + 4.),
+            marker.color
+        );
+        header_track.text(Vec2::new(px + 8., canvas_pos.y + 2.), marker.color, &marker.label);
+    }
+    // cursor
+    let current_frame = seq.get_state().current_frame;
+    if current_frame >= first_frame && current_frame <= seq.get_frame_max().min(last_frame) {
+        let cursor_x = frame_to_x(current_frame) + (frame_pixel_width - CURSOR_WIDTH) * 0.5;
+        frame_column.rect(
+            Vec2::new(cursor_x - CURSOR_WIDTH * 0.5, canvas_pos.y),
+            Vec2::new(cursor_x + CURSOR_WIDTH * 0.5, content_rect[1].y),
+            seq.get_style().get_playhead_color(),
+            0.
+        );
+        header_track.text(Vec2::new(cursor_x + 10., canvas_pos.y + 2.), seq.get_style().get_playhead_text_color(), &format!("{}", current_frame));
+    }
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use timeline::{ Timeline, TimelineEntry };
+
+    /// Three items on the first track and one on the second:
+    /// 0: 10-19, 1: 30-39, 2: 50-59 on track 0 and 3: 40-49 on track 1
+    fn timeline(mode: SequencerEditMode) -> Timeline {
+        let mut timeline = Timeline::new(0, 100);
+        timeline.add_track("first");
+        timeline.add_track("second");
+        for (start, track) in [(10, 0), (30, 0), (50, 0), (40, 1)] {
+            let item = SequencerItem { start, end: start + 9, item_type: 0, color: 0xffffffff };
+            timeline.add_entry(TimelineEntry::new("item", item).with_track(track));
+        }
+        timeline.get_state_mut().set_edit_mode(mode);
+        timeline
+    }
+
+    fn ranges(timeline: &Timeline) -> Vec<(u32, u32)> {
+        timeline.get_entries().iter().map(|e| (e.item.start, e.item.end)).collect()
+    }
+
+    #[test]
+    fn normal_mode_moves_freely_inside_the_frame_range() {
+        let mut seq = timeline(SequencerEditMode::Normal);
+        assert_eq!(drag_item(&mut seq, 1, DragPart::Whole, 15), 15);
+        assert_eq!(ranges(&seq), [(10, 19), (45, 54), (50, 59), (40, 49)]);
+        assert_eq!(drag_item(&mut seq, 1, DragPart::Start, -100), -45);
+        assert_eq!(drag_item(&mut seq, 1, DragPart::End, -100), -54);
+        assert_eq!(ranges(&seq), [(10, 19), (0, 0), (50, 59), (40, 49)]);
+        assert_eq!(drag_item(&mut seq, 1, DragPart::Whole, -1), 0);
+    }
 
-        // draw header ticks
-        for i in (self.get_frame_min() / frame_step)..(self.get_frame_max() / frame_step) {
-            draw_line(i * frame_step, item_height);
-        }
-        draw_line(self.get_frame_min(), item_height);
-        draw_line(self.get_frame_max(), item_height);
-        // draw item names
-        for i in 0..self.get_item_count() {
-            let text_pos = Vec2::new(content_rect[0].x + 3., content_rect[0].y + i as f32 * item_height + 2.);
-            draw_list.add_text(Into::<[f32; 2]>::into(text_pos), 0xffffffff, &format!("label {}", i));
-        }
-        // slots
-        for i in 0..self.get_item_count() {
-            let color = self.get_style().get_slot_color()[i & 1];
-            let pos = Vec2::new(content_rect[0].x + legend_width as f32, content_rect[0].y + i as f32 * item_height + 1.);
-            let size = Vec2::new(canvas_size.x + canvas_pos.x, pos.y + item_height - 1.);
-            draw_list.add_rect(
-                Into::<[f32; 2]>::into(pos),
-                Into::<[f32; 2]>::into(size),
-                color
-            ).filled(true).build()
-        }
-        // vertical lines in content
-        for i in (self.get_frame_min() / frame_step)..(self.get_frame_max() / frame_step) {
-            draw_line_content(i * frame_step, item_height);
-        }
-        draw_line_content(self.get_frame_min(), item_height);
-        draw_line_content(self.get_frame_max(), item_height);
-        
-    }
-}
\ No newline at end of file
+    #[test]
+    fn block_mode_stops_at_neighbours_on_the_same_track() {
+        let mut seq = timeline(SequencerEditMode::NoOverlap(OverlapMode::Block));
+        assert_eq!(drag_item(&mut seq, 1, DragPart::Whole, 15), 10);
+        assert_eq!(ranges(&seq), [(10, 19), (40, 49), (50, 59), (40, 49)]);
+        assert_eq!(drag_item(&mut seq, 1, DragPart::Whole, -30), -20);
+        assert_eq!(ranges(&seq), [(10, 19), (20, 29), (50, 59), (40, 49)]);
+        assert_eq!(drag_item(&mut seq, 1, DragPart::End, 30), 20);
+        assert_eq!(drag_item(&mut seq, 0, DragPart::Start, -20), -10);
+        assert_eq!(ranges(&seq), [(0, 19), (20, 49), (50, 59), (40, 49)]);
+    }
+
+    #[test]
+    fn push_mode_pushes_neighbours_on_the_same_track() {
+        let mut seq = timeline(SequencerEditMode::NoOverlap(OverlapMode::Push));
+        assert_eq!(drag_item(&mut seq, 1, DragPart::Whole, 15), 15);
+        assert_eq!(ranges(&seq), [(10, 19), (45, 54), (55, 64), (40, 49)]);
+        // earlier items are pushed back only until they reach the first frame
+        assert_eq!(drag_item(&mut seq, 1, DragPart::Whole, -45), -35);
+        assert_eq!(ranges(&seq), [(0, 9), (10, 19), (55, 64), (40, 49)]);
+        assert_eq!(drag_item(&mut seq, 1, DragPart::End, 40), 40);
+        assert_eq!(ranges(&seq), [(0, 9), (10, 59), (60, 69), (40, 49)]);
+    }
+
+    #[test]
+    fn ripple_mode_shifts_later_items() {
+        let mut seq = timeline(SequencerEditMode::Ripple(RippleScope::Track));
+        assert_eq!(drag_item(&mut seq, 1, DragPart::End, 5), 5);
+        assert_eq!(ranges(&seq), [(10, 19), (30, 44), (55, 64), (40, 49)]);
+        // moving the start leaves later items where they are
+        assert_eq!(drag_item(&mut seq, 1, DragPart::Start, 5), 5);
+        assert_eq!(ranges(&seq), [(10, 19), (35, 44), (55, 64), (40, 49)]);
+
+        let mut seq = timeline(SequencerEditMode::Ripple(RippleScope::AllTracks));
+        assert_eq!(drag_item(&mut seq, 1, DragPart::Whole, 5), 5);
+        assert_eq!(ranges(&seq), [(10, 19), (35, 44), (55, 64), (45, 54)]);
+    }
+
+    #[test]
+    fn dragging_items_before_the_first_frame() {
+        for mode in [
+            SequencerEditMode::Normal,
+            SequencerEditMode::NoOverlap(OverlapMode::Block),
+            SequencerEditMode::NoOverlap(OverlapMode::Push),
+            SequencerEditMode::Ripple(RippleScope::AllTracks)
+        ] {
+            let mut seq = timeline(mode);
+            seq.set_frame_range(25, 100);
+            for part in [DragPart::Start, DragPart::End, DragPart::Whole] {
+                drag_item(&mut seq, 0, part, -5);
+                drag_item(&mut seq, 0, part, 5);
+                let (start, end) = ranges(&seq)[0];
+                assert!(start <= end, "{:?} {:?} gave {}-{}", mode, part, start, end);
+            }
+        }
+    }
+
+    #[test]
+    fn clicks_select_the_item_under_the_mouse() {
+        let seq = timeline(SequencerEditMode::Normal);
+        assert_eq!(item_at(&seq, 0, 30.), Some(1));
+        assert_eq!(item_at(&seq, 0, 39.9), Some(1));
+        assert_eq!(item_at(&seq, 0, 40.), None);
+        assert_eq!(item_at(&seq, 0, 25.), None);
+        assert_eq!(item_at(&seq, 1, 45.), Some(3));
+        assert_eq!(item_at(&seq, 2, 45.), None);
+        assert_eq!(item_at(&seq, 0, -1.), None);
+    }
+}
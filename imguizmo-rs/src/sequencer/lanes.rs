@@ -0,0 +1,386 @@
+//! Built-in renderers for the custom area below a sequencer item. Return the
+//! lane's height from [`Sequencer::get_custom_height`] and forward
+//! [`Sequencer::custom_draw`] to [`SequencerLane::draw`]:
+//!
+//! ```ignore
+//! fn get_custom_height(&self, index: usize) -> f32 { self.lanes[index].get_height() }
+//! fn custom_draw(&mut self, index: usize, draw_list: &DrawListMut, custom: &CustomDraw) {
+//!     self.lanes[index].draw(draw_list, custom);
+//! }
+//! ```
+//!
+//! [`Sequencer::get_custom_height`]: super::Sequencer::get_custom_height
+//! [`Sequencer::custom_draw`]: super::Sequencer::custom_draw
+
+use glam::Vec2;
+use imgui::DrawListMut;
+use super::CustomDraw;
+
+/// Number of samples summarized by each entry in the first level of a [`PeakPyramid`]
+const PEAK_BASE_BLOCK: usize = 16;
+
+pub trait SequencerLane {
+    fn get_height(&self) -> f32;
+    fn draw(&self, draw_list: &DrawListMut, custom: &CustomDraw);
+}
+
+fn draw_lane_label(draw_list: &DrawListMut, custom: &CustomDraw, label: &str, color: u32) {
+    if !label.is_empty() {
+        let pos = custom.get_legend_rect()[0] + Vec2::new(8., 2.);
+        draw_list.add_text(Into::<[f32; 2]>::into(pos), color, label);
+    }
+}
+
+/// Range of pixel columns for the visible part of a lane
+fn visible_columns(custom: &CustomDraw) -> (i32, i32) {
+    let rect = custom.get_custom_rect();
+    let clip = custom.get_clip_rect();
+    (rect[0].x.max(clip[0].x).floor() as i32, rect[1].x.min(clip[1].x).ceil() as i32)
+}
+
+/// Min/max peaks of a sample buffer at successively halved resolutions, so a
+/// waveform can be drawn at any zoom level without touching every sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeakPyramid {
+    samples: Vec<f32>,
+    levels: Vec<Vec<(f32, f32)>>
+}
+
+impl PeakPyramid {
+    pub fn new(samples: Vec<f32>) -> Self {
+        let mut levels: Vec<Vec<(f32, f32)>> = vec![];
+        let base: Vec<(f32, f32)> = samples.chunks(PEAK_BASE_BLOCK)
+            .map(|c| c.iter().fold((f32::MAX, f32::MIN), |(lo, hi), s| (lo.min(*s), hi.max(*s))))
+            .collect();
+        let mut len = base.len();
+        levels.push(base);
+        while len > 1 {
+            let next: Vec<(f32, f32)> = levels.last().unwrap().chunks(2)
+                .map(|c| c.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p.0), hi.max(p.1))))
+                .collect();
+            len = next.len();
+            levels.push(next);
+        }
+        Self { samples, levels }
+    }
+
+    pub fn get_samples(&self) -> &[f32] { &self.samples }
+    pub fn get_level_count(&self) -> usize { self.levels.len() }
+
+    /// Minimum and maximum sample in `start..end`, reading from the coarsest
+    /// level that still resolves the range. Returns `None` for an empty range.
+    pub fn peak(&self, start: usize, end: usize) -> Option<(f32, f32)> {
+        let end = end.min(self.samples.len());
+        if start >= end {
+            return None;
+        }
+        let Some(level) = peak_level(end - start, self.levels.len()) else {
+            return Some(self.samples[start..end].iter()
+                .fold((f32::MAX, f32::MIN), |(lo, hi), s| (lo.min(*s), hi.max(*s))));
+        };
+        // each level has blocks of PEAK_BASE_BLOCK << level samples
+        let block = PEAK_BASE_BLOCK << level;
+        let (first, last) = (start / block, end.div_ceil(block));
+        Some(self.levels[level][first..last.min(self.levels[level].len())].iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p.0), hi.max(p.1))))
+    }
+}
+
+/// Level of a [`PeakPyramid`] to read `len` samples from, the coarsest with at
+/// least two blocks in the range. `None` when the samples should be read directly.
+fn peak_level(len: usize, level_count: usize) -> Option<usize> {
+    if len < PEAK_BASE_BLOCK * 2 || level_count == 0 {
+        return None;
+    }
+    Some(((len / PEAK_BASE_BLOCK).ilog2() as usize).saturating_sub(1).min(level_count - 1))
+}
+
+/// Draws the min/max envelope of an audio buffer aligned to the sequencer's frames
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveformLane {
+    peaks: PeakPyramid,
+    samples_per_frame: f32,
+    start_frame: f32,
+    height: f32,
+    label: String,
+    color: u32,
+    bg_color: u32,
+}
+
+impl WaveformLane {
+    /// `samples` are expected to be a single channel in the range `-1.0..=1.0`
+    pub fn new(samples: Vec<f32>, sample_rate: f32, frame_rate: f32) -> Self {
+        Self {
+            peaks: PeakPyramid::new(samples),
+            samples_per_frame: sample_rate / frame_rate,
+            start_frame: 0.,
+            height: 48.,
+            label: String::new(),
+            color: 0xffd0a060,
+            bg_color: 0x40000000
+        }
+    }
+
+    pub fn set_start_frame(mut self, frame: f32) -> Self {
+        self.start_frame = frame;
+        self
+    }
+
+    pub fn set_height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    pub fn set_label<T>(mut self, label: T) -> Self
+    where T: Into<String>
+    {
+        self.label = label.into();
+        self
+    }
+
+    pub fn set_color(mut self, col: u32) -> Self {
+        self.color = col;
+        self
+    }
+
+    pub fn set_bg_color(mut self, col: u32) -> Self {
+        self.bg_color = col;
+        self
+    }
+
+    pub fn get_peaks(&self) -> &PeakPyramid { &self.peaks }
+}
+
+impl SequencerLane for WaveformLane {
+    fn get_height(&self) -> f32 { self.height }
+
+    fn draw(&self, draw_list: &DrawListMut, custom: &CustomDraw) {
+        let rect = custom.get_custom_rect();
+        let clip = custom.get_clip_rect();
+        draw_list.add_rect(
+            Into::<[f32; 2]>::into(clip[0]),
+            Into::<[f32; 2]>::into(clip[1]),
+            self.bg_color
+        ).filled(true).build();
+        draw_lane_label(draw_list, custom, &self.label, self.color);
+        let center = (rect[0].y + rect[1].y) * 0.5;
+        let half_height = (rect[1].y - rect[0].y) * 0.5 - 1.;
+        let (x0, x1) = visible_columns(custom);
+        let sample_at = |x: f32| (custom.x_to_frame(x) - self.start_frame) * self.samples_per_frame;
+        for x in x0..x1 {
+            let (start, end) = (sample_at(x as f32), sample_at(x as f32 + 1.));
+            if end <= 0. {
+                continue;
+            }
+            let start = start.max(0.) as usize;
+            // zoomed in past one sample per pixel: keep each column at least one sample wide
+            let end = (end.ceil() as usize).max(start + 1);
+            let Some((lo, hi)) = self.peaks.peak(start, end) else { break };
+            let (lo, hi) = (lo.clamp(-1., 1.), hi.clamp(-1., 1.));
+            let top = (center - hi * half_height).max(clip[0].y);
+            let bottom = (center - lo * half_height + 1.).min(clip[1].y);
+            if top < bottom {
+                draw_list.add_line([x as f32 + 0.5, top], [x as f32 + 0.5, bottom], self.color).build();
+            }
+        }
+    }
+}
+
+/// Indices of the values a [`ValueGraphLane`] plots between the frames `visible`:
+/// every `step`th value, starting one frame before the visible area and ending
+/// one frame after it so the line runs off the edges. `len` must not be 0.
+fn graph_indices(visible: (f32, f32), start_frame: u32, len: usize, step: usize) -> impl Iterator<Item = usize> {
+    let last = len as i64 - 1;
+    let first = (visible.0.floor() as i64 - start_frame as i64 - 1).clamp(0, last) as usize;
+    let end = (visible.1.ceil() as i64 - start_frame as i64 + 1).clamp(0, last) as usize;
+    // finish on the last frame even when the steps skip over it
+    (first..=end).step_by(step).chain((!(end - first).is_multiple_of(step)).then_some(end))
+}
+
+/// Plots one `f32` value per frame as a line graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueGraphLane {
+    values: Vec<f32>,
+    start_frame: u32,
+    range: Option<(f32, f32)>,
+    height: f32,
+    label: String,
+    color: u32,
+    bg_color: u32,
+}
+
+impl ValueGraphLane {
+    pub fn new(values: Vec<f32>) -> Self {
+        Self {
+            values,
+            start_frame: 0,
+            range: None,
+            height: 48.,
+            label: String::new(),
+            color: 0xff60d0a0,
+            bg_color: 0x40000000
+        }
+    }
+
+    pub fn set_start_frame(mut self, frame: u32) -> Self {
+        self.start_frame = frame;
+        self
+    }
+
+    /// Fix the value range mapped to the lane's height. By default the range of
+    /// the values in the lane is used.
+    pub fn set_range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn set_height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    pub fn set_label<T>(mut self, label: T) -> Self
+    where T: Into<String>
+    {
+        self.label = label.into();
+        self
+    }
+
+    pub fn set_color(mut self, col: u32) -> Self {
+        self.color = col;
+        self
+    }
+
+    pub fn set_bg_color(mut self, col: u32) -> Self {
+        self.bg_color = col;
+        self
+    }
+
+    pub fn get_values(&self) -> &[f32] { &self.values }
+    pub fn get_values_mut(&mut self) -> &mut Vec<f32> { &mut self.values }
+
+    fn get_range(&self) -> (f32, f32) {
+        self.range.unwrap_or_else(|| self.values.iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(*v), hi.max(*v))))
+    }
+}
+
+impl SequencerLane for ValueGraphLane {
+    fn get_height(&self) -> f32 { self.height }
+
+    fn draw(&self, draw_list: &DrawListMut, custom: &CustomDraw) {
+        let rect = custom.get_custom_rect();
+        let clip = custom.get_clip_rect();
+        draw_list.add_rect(
+            Into::<[f32; 2]>::into(clip[0]),
+            Into::<[f32; 2]>::into(clip[1]),
+            self.bg_color
+        ).filled(true).build();
+        draw_lane_label(draw_list, custom, &self.label, self.color);
+        if self.values.is_empty() {
+            return;
+        }
+        let (lo, hi) = self.get_range();
+        let span = if hi > lo { hi - lo } else { 1. };
+        let (top, bottom) = (rect[0].y + 2., rect[1].y - 2.);
+        let (x0, x1) = visible_columns(custom);
+        // when frames are narrower than a pixel, skip frames instead of drawing overlapping segments
+        let step = (1. / custom.get_frame_pixel_width()).ceil().max(1.) as usize;
+        let frames = (custom.x_to_frame(x0 as f32), custom.x_to_frame(x1 as f32));
+        let points: Vec<[f32; 2]> = graph_indices(frames, self.start_frame, self.values.len(), step)
+            .map(|i| {
+                let x = custom.frame_to_x((self.start_frame as usize + i) as f32 + 0.5);
+                let t = ((self.values[i] - lo) / span).clamp(0., 1.);
+                [x, bottom + (top - bottom) * t]
+            })
+            .collect();
+        if points.len() > 1 {
+            draw_list.add_polyline(points, self.color).thickness(1.5).build();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Exact minimum and maximum of `samples[start..end]`
+    fn brute_peak(samples: &[f32], start: usize, end: usize) -> (f32, f32) {
+        samples[start..end].iter().fold((f32::MAX, f32::MIN), |(lo, hi), s| (lo.min(*s), hi.max(*s)))
+    }
+
+    fn samples(len: usize) -> Vec<f32> {
+        (0..len).map(|i| ((i * 7919) % 1000) as f32 / 500. - 1.).collect()
+    }
+
+    #[test]
+    fn short_ranges_read_samples_directly() {
+        assert_eq!(peak_level(0, 10), None);
+        assert_eq!(peak_level(PEAK_BASE_BLOCK * 2 - 1, 10), None);
+        assert_eq!(peak_level(PEAK_BASE_BLOCK * 2, 0), None);
+    }
+
+    #[test]
+    fn level_keeps_at_least_two_blocks_in_range() {
+        for len in PEAK_BASE_BLOCK * 2..PEAK_BASE_BLOCK * 300 {
+            let level = peak_level(len, usize::MAX).unwrap();
+            let block = PEAK_BASE_BLOCK << level;
+            assert!(len >= block * 2, "{} samples read from blocks of {}", len, block);
+            assert!(len < block * 8, "{} samples read from blocks of only {}", len, block);
+        }
+        assert_eq!(peak_level(PEAK_BASE_BLOCK * 2, usize::MAX), Some(0));
+        assert_eq!(peak_level(PEAK_BASE_BLOCK * 4, usize::MAX), Some(1));
+        assert_eq!(peak_level(PEAK_BASE_BLOCK * 1024, 3), Some(2));
+    }
+
+    #[test]
+    fn peaks_cover_the_range() {
+        let samples = samples(10_000);
+        let pyramid = PeakPyramid::new(samples.clone());
+        assert_eq!(pyramid.get_level_count(), 11);
+        for (start, end) in [(0, 10_000), (0, 32), (5, 37), (100, 2_000), (3_333, 9_999), (9_990, 10_000)] {
+            let (lo, hi) = pyramid.peak(start, end).unwrap();
+            let (exact_lo, exact_hi) = brute_peak(&samples, start, end);
+            assert!(lo <= exact_lo && hi >= exact_hi, "{}..{} gave {:?}", start, end, (lo, hi));
+        }
+        // two blocks of the level the range is read from, so the result is exact
+        let block = PEAK_BASE_BLOCK << 2;
+        assert_eq!(peak_level(block * 2, pyramid.get_level_count()), Some(2));
+        assert_eq!(pyramid.peak(block * 5, block * 7), Some(brute_peak(&samples, block * 5, block * 7)));
+    }
+
+    #[test]
+    fn peaks_stop_at_the_end_of_the_samples() {
+        let pyramid = PeakPyramid::new(samples(100));
+        assert_eq!(pyramid.peak(50, 10_000), Some(brute_peak(pyramid.get_samples(), 50, 100)));
+        assert_eq!(pyramid.peak(100, 200), None);
+        assert_eq!(pyramid.peak(30, 30), None);
+    }
+
+    #[test]
+    fn graph_pads_visible_frames_by_one() {
+        let indices: Vec<usize> = graph_indices((10.5, 20.5), 0, 100, 1).collect();
+        assert_eq!(indices, (9..=22).collect::<Vec<_>>());
+        let indices: Vec<usize> = graph_indices((10.5, 20.5), 5, 100, 1).collect();
+        assert_eq!(indices, (4..=17).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn graph_clamps_to_the_values() {
+        assert_eq!(graph_indices((-50., 200.), 0, 10, 1).collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+        assert_eq!(graph_indices((-50., -20.), 0, 10, 1).collect::<Vec<_>>(), [0]);
+        assert_eq!(graph_indices((50., 80.), 0, 10, 1).collect::<Vec<_>>(), [9]);
+        assert_eq!(graph_indices((0., 5.), 100, 10, 1).collect::<Vec<_>>(), [0]);
+    }
+
+    #[test]
+    fn graph_ends_on_the_last_frame_once() {
+        for step in 1..8 {
+            for len in 1..40 {
+                let indices: Vec<usize> = graph_indices((0., 1000.), 0, len, step).collect();
+                assert_eq!(indices.last(), Some(&(len - 1)));
+                assert!(indices.windows(2).all(|w| w[0] < w[1]), "step {} len {}: {:?}", step, len, indices);
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Load and save helpers for sequencer data. Anything in the sequencer module
+//! that derives `Serialize` (styles, view state, markers and [`Timeline`]) can be
+//! written as pretty-printed JSON or RON, which keeps diffs in version control
+//! readable.
+//! 
+//! [`Timeline`]: super::timeline::Timeline
+
+use serde::{ de::DeserializeOwned, Serialize };
+use std::{
+    error::Error,
+    fmt::{ Display, Formatter },
+    fs,
+    io,
+    path::Path
+};
+
+#[derive(Debug)]
+pub enum PersistError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Ron(ron::Error),
+    RonParse(ron::error::SpannedError)
+}
+
+impl Display for PersistError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Json(e) => write!(f, "JSON error: {}", e),
+            Self::Ron(e) => write!(f, "RON error: {}", e),
+            Self::RonParse(e) => write!(f, "RON parse error: {}", e),
+        }
+    }
+}
+
+impl Error for PersistError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Json(e) => Some(e),
+            Self::Ron(e) => Some(e),
+            Self::RonParse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for PersistError {
+    fn from(value: io::Error) -> Self { Self::Io(value) }
+}
+
+impl From<serde_json::Error> for PersistError {
+    fn from(value: serde_json::Error) -> Self { Self::Json(value) }
+}
+
+impl From<ron::Error> for PersistError {
+    fn from(value: ron::Error) -> Self { Self::Ron(value) }
+}
+
+impl From<ron::error::SpannedError> for PersistError {
+    fn from(value: ron::error::SpannedError) -> Self { Self::RonParse(value) }
+}
+
+pub fn to_json<T: Serialize>(value: &T) -> Result<String, PersistError> {
+    Ok(serde_json::to_string_pretty(value)?)
+}
+
+pub fn from_json<T: DeserializeOwned>(src: &str) -> Result<T, PersistError> {
+    Ok(serde_json::from_str(src)?)
+}
+
+pub fn to_ron<T: Serialize>(value: &T) -> Result<String, PersistError> {
+    Ok(ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?)
+}
+
+pub fn from_ron<T: DeserializeOwned>(src: &str) -> Result<T, PersistError> {
+    Ok(ron::from_str(src)?)
+}
+
+pub fn save_json<T: Serialize, P: AsRef<Path>>(path: P, value: &T) -> Result<(), PersistError> {
+    Ok(fs::write(path, to_json(value)?)?)
+}
+
+pub fn load_json<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T, PersistError> {
+    from_json(&fs::read_to_string(path)?)
+}
+
+pub fn save_ron<T: Serialize, P: AsRef<Path>>(path: P, value: &T) -> Result<(), PersistError> {
+    Ok(fs::write(path, to_ron(value)?)?)
+}
+
+pub fn load_ron<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T, PersistError> {
+    from_ron(&fs::read_to_string(path)?)
+}
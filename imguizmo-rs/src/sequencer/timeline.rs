@@ -0,0 +1,151 @@
+//! A ready-made item model for the sequencer. Tools that only need labelled
+//! ranges and markers can use [`Timeline`] directly instead of implementing
+//! [`Sequencer`] over their own data.
+
+use super::{
+    Sequencer,
+    SequencerItem,
+    SequencerMarker,
+    SequencerState,
+    SequencerStyle
+};
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimelineEntry {
+    pub label: String,
+    pub item: SequencerItem,
+    /// Index into the timeline's tracks. Ignored while the timeline has no tracks.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub track: usize,
+    /// Nested timeline opened by double-clicking the entry
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub sub_sequence: Option<Box<Timeline>>
+}
+
+impl TimelineEntry {
+    pub fn new<T>(label: T, item: SequencerItem) -> Self
+    where T: Into<String>
+    {
+        Self { label: label.into(), item, track: 0, sub_sequence: None }
+    }
+
+    pub fn with_track(mut self, track: usize) -> Self {
+        self.track = track;
+        self
+    }
+
+    pub fn with_sub_sequence(mut self, sub_sequence: Timeline) -> Self {
+        self.sub_sequence = Some(Box::new(sub_sequence));
+        self
+    }
+}
+
+/// Without any tracks every entry gets its own row, labelled with the entry's
+/// label. Once tracks are added, entries are placed on the track they name.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Timeline {
+    frame_min: u32,
+    frame_max: u32,
+    entries: Vec<TimelineEntry>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    tracks: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    markers: Vec<SequencerMarker>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    style: SequencerStyle,
+    #[cfg_attr(feature = "serde", serde(default))]
+    state: SequencerState,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    focused: bool
+}
+
+impl Timeline {
+    pub fn new(frame_min: u32, frame_max: u32) -> Self {
+        Self {
+            frame_min,
+            frame_max,
+            entries: vec![],
+            tracks: vec![],
+            markers: vec![],
+            style: SequencerStyle::default(),
+            state: SequencerState::default(),
+            focused: false
+        }
+    }
+
+    pub fn with_style(mut self, style: SequencerStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn set_frame_range(&mut self, frame_min: u32, frame_max: u32) {
+        self.frame_min = frame_min;
+        self.frame_max = frame_max;
+    }
+
+    pub fn get_entries(&self) -> &[TimelineEntry] { &self.entries }
+    pub fn get_entries_mut(&mut self) -> &mut Vec<TimelineEntry> { &mut self.entries }
+    pub fn add_entry(&mut self, entry: TimelineEntry) { self.entries.push(entry); }
+
+    pub fn get_tracks(&self) -> &[String] { &self.tracks }
+    /// Add a named track and return its index
+    pub fn add_track<T>(&mut self, label: T) -> usize
+    where T: Into<String>
+    {
+        self.tracks.push(label.into());
+        self.tracks.len() - 1
+    }
+
+    pub fn get_markers_mut(&mut self) -> &mut Vec<SequencerMarker> { &mut self.markers }
+    pub fn add_marker(&mut self, marker: SequencerMarker) { self.markers.push(marker); }
+}
+
+impl Sequencer for Timeline {
+    fn get_frame_min(&self) -> u32 { self.frame_min }
+    fn get_frame_max(&self) -> u32 { self.frame_max }
+    fn get_item_count(&self) -> usize { self.entries.len() }
+    fn get_item(&self, index: usize) -> SequencerItem { self.entries[index].item }
+
+    fn set_focused(&mut self, focused: bool) { self.focused = focused; }
+    fn get_focused(&self) -> bool { self.focused }
+    fn get_style(&self) -> &SequencerStyle { &self.style }
+    fn get_state(&self) -> &SequencerState { &self.state }
+    fn get_state_mut(&mut self) -> &mut SequencerState { &mut self.state }
+
+    fn get_item_label(&self, index: usize) -> &str { &self.entries[index].label }
+    fn get_markers(&self) -> &[SequencerMarker] { &self.markers }
+
+    fn get_item_track(&self, index: usize) -> usize {
+        if self.tracks.is_empty() { index } else { self.entries[index].track }
+    }
+
+    fn get_track_count(&self) -> usize {
+        if self.tracks.is_empty() { self.entries.len() } else { self.tracks.len() }
+    }
+
+    fn get_track_label(&self, track: usize) -> &str {
+        if self.tracks.is_empty() { &self.entries[track].label } else { &self.tracks[track] }
+    }
+
+    fn del(&mut self, index: usize) {
+        self.entries.remove(index);
+    }
+
+    fn set_item_range(&mut self, index: usize, start: u32, end: u32) {
+        let item = &mut self.entries[index].item;
+        item.start = start;
+        item.end = end;
+    }
+
+    fn get_sub_sequence(&self, index: usize) -> Option<&dyn Sequencer> {
+        self.entries[index].sub_sequence.as_deref().map(|s| s as &dyn Sequencer)
+    }
+
+    fn get_sub_sequence_mut(&mut self, index: usize) -> Option<&mut dyn Sequencer> {
+        self.entries[index].sub_sequence.as_deref_mut().map(|s| s as &mut dyn Sequencer)
+    }
+}
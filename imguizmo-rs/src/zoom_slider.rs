@@ -0,0 +1,210 @@
+//! Rust port of ImZoomSlider, part of the ImGuizmo library (MIT License)
+//! https://github.com/CedricGuillemet/ImGuizmo/blob/master/ImZoomSlider.h
+//!
+//! ImZoomSlider keeps the part being dragged in ImGui's storage. Here it
+//! lives in a [`ZoomSlider`] owned by the caller, like [`crate::gizmo::Gizmo`].
+//!
+//! To scroll and zoom a sequencer, pass it the range from
+//! [`crate::sequencer::SequencerState::get_view_range`] and write the result
+//! back with `set_view_range`. A curve editor's range is available through
+//! [`crate::curve_edit::CurveEditState::get_min`], `get_max` and `set_range`.
+
+use bitflags::bitflags;
+use glam::Vec2;
+use imgui::{ MouseButton, Ui };
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct ZoomSliderFlags : u32 {
+        const Vertical = 1 << 0;
+        /// Don't draw the circles at the ends of the view window
+        const NoAnchors = 1 << 1;
+        /// Don't draw the lines in the middle of the view window
+        const NoMiddleCarets = 1 << 2;
+        const NoWheel = 1 << 3;
+    }
+}
+
+/// Thickness of the bar in pixels
+const BAR_SIZE: f32 = 14.;
+const ANCHOR_RADIUS: f32 = 6.;
+/// Smallest length of the view window in pixels
+const MIN_WINDOW_PIXELS: f32 = 10.;
+const BACKGROUND_COLOR: u32 = 0xff101010;
+const BAR_COLOR: u32 = 0xff222222;
+const WINDOW_COLOR: [u32; 2] = [0xff606060, 0xff808080];
+const ANCHOR_COLOR: [u32; 2] = [0xffaaaaaa, 0xffffffff];
+const CARET_COLOR: u32 = 0xffa0a0a0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Lower,
+    Higher,
+    Window
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Drag {
+    part: Part,
+    /// Mouse position along the bar when the drag started
+    mouse_origin: f32,
+    view_origin: (f32, f32)
+}
+
+/// Slider editing a view range inside a larger range. Drag the ends to zoom,
+/// drag the middle to scroll, click outside the view window to center it on
+/// the mouse, or use the mouse wheel to zoom around the mouse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoomSlider {
+    flags: ZoomSliderFlags,
+    wheel_ratio: f32,
+    drag: Option<Drag>,
+    hovered: bool
+}
+
+impl Default for ZoomSlider {
+    fn default() -> Self {
+        Self {
+            flags: ZoomSliderFlags::empty(),
+            wheel_ratio: 0.01,
+            drag: None,
+            hovered: false
+        }
+    }
+}
+
+impl ZoomSlider {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn set_flags(mut self, flags: ZoomSliderFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Fraction of the view range zoomed per mouse wheel step
+    pub fn set_wheel_ratio(mut self, ratio: f32) -> Self {
+        self.wheel_ratio = ratio;
+        self
+    }
+
+    pub fn is_using(&self) -> bool { self.drag.is_some() }
+    pub fn is_over(&self) -> bool { self.hovered }
+
+    /// Draw the slider at the cursor position, filling the available width (or
+    /// height when vertical), and edit the view range between `lower` and
+    /// `higher`. Returns true if the view range changed. The slider's ImGui ID
+    /// comes from its address, so several can be drawn in the same window.
+    pub fn draw(&mut self, ui: &Ui, lower: f32, higher: f32, view_lower: &mut f32, view_higher: &mut f32) -> bool {
+        let vertical = self.flags.contains(ZoomSliderFlags::Vertical);
+        let axis = if vertical { 1 } else { 0 };
+        let avail = Vec2::from(ui.content_region_avail());
+        let size = if vertical { Vec2::new(BAR_SIZE, avail.y) } else { Vec2::new(avail.x, BAR_SIZE) }.max(Vec2::ONE);
+        // sliders are often drawn side by side, so each needs its own ID
+        let _id = ui.push_id_ptr(self);
+        ui.invisible_button("zoomSlider", Into::<[f32; 2]>::into(size));
+        let min = Vec2::from(ui.item_rect_min());
+        let max = min + size;
+        let length = size[axis];
+        let range = higher - lower;
+        if range <= f32::EPSILON {
+            return false;
+        }
+        let original = (*view_lower, *view_higher);
+        let to_pixel = |v: f32| min[axis] + (v - lower) / range * length;
+        let to_value = |p: f32| lower + (p - min[axis]) / length * range;
+        let min_window = (MIN_WINDOW_PIXELS / length * range).min(range);
+        let mouse = Vec2::from(ui.io().mouse_pos);
+        let mouse_along = mouse[axis];
+        self.hovered = ui.is_item_hovered();
+        let wheel = match self.flags.contains(ZoomSliderFlags::NoWheel) {
+            true => 0.,
+            false => ui.io().mouse_wheel
+        };
+        let clicked = self.drag.is_none() && ui.is_mouse_clicked(MouseButton::Left);
+        if self.hovered && (wheel.abs() > f32::EPSILON || clicked) {
+            // the view can be wider than the range, or lie partly outside it, such
+            // as a zoomed out sequencer. Bring it inside before editing it.
+            *view_lower = view_lower.max(lower).min(higher);
+            *view_higher = view_higher.min(higher).max(*view_lower);
+        }
+
+        let start = to_pixel(*view_lower);
+        let end = to_pixel(*view_higher);
+        let over_part = self.hovered.then(|| {
+            if (mouse_along - start).abs() <= ANCHOR_RADIUS {
+                Some(Part::Lower)
+            } else if (mouse_along - end).abs() <= ANCHOR_RADIUS {
+                Some(Part::Higher)
+            } else if mouse_along > start && mouse_along < end {
+                Some(Part::Window)
+            } else { None }
+        }).flatten();
+
+        if self.hovered && wheel.abs() > f32::EPSILON {
+            let view_range = *view_higher - *view_lower;
+            let ratio = ((mouse_along - start) / (end - start).max(1.)).clamp(0., 1.);
+            let amount = (wheel * self.wheel_ratio * view_range).min(view_range - min_window);
+            *view_lower = (*view_lower + amount * ratio).max(lower);
+            *view_higher = (*view_higher - amount * (1. - ratio)).min(higher);
+        }
+        if self.hovered && clicked {
+            let part = over_part.unwrap_or_else(|| {
+                // jump so the window is centered on the mouse, then drag it from there
+                let half = (*view_higher - *view_lower) * 0.5;
+                let center = to_value(mouse_along).max(lower + half).min(higher - half);
+                (*view_lower, *view_higher) = (center - half, center + half);
+                Part::Window
+            });
+            self.drag = Some(Drag { part, mouse_origin: mouse_along, view_origin: (*view_lower, *view_higher) });
+        }
+        if let Some(drag) = self.drag {
+            if ui.is_mouse_down(MouseButton::Left) {
+                let delta = (mouse_along - drag.mouse_origin) / length * range;
+                let (origin_lower, origin_higher) = drag.view_origin;
+                // the bounds cross when the view is narrower than the smallest window,
+                // so clamp with max and min, which unlike clamp don't panic
+                match drag.part {
+                    Part::Lower => *view_lower = (origin_lower + delta).max(lower).min(*view_higher - min_window),
+                    Part::Higher => *view_higher = (origin_higher + delta).min(higher).max(*view_lower + min_window),
+                    Part::Window => {
+                        let delta = delta.max(lower - origin_lower).min(higher - origin_higher);
+                        (*view_lower, *view_higher) = (origin_lower + delta, origin_higher + delta);
+                    }
+                }
+            } else {
+                self.drag = None;
+            }
+        }
+
+        let draw_list = ui.get_window_draw_list();
+        let across = if vertical { Vec2::X } else { Vec2::Y };
+        draw_list.add_rect(Into::<[f32; 2]>::into(min - across * 2.), Into::<[f32; 2]>::into(max + across * 2.),
+            BACKGROUND_COLOR).filled(true).rounding(8.).build();
+        draw_list.add_rect(Into::<[f32; 2]>::into(min), Into::<[f32; 2]>::into(max), BAR_COLOR)
+            .filled(true).rounding(8.).build();
+        let (start, end) = (to_pixel(*view_lower), to_pixel(*view_higher));
+        let point = |along: f32, offset: f32| {
+            let mut p = min + across * (BAR_SIZE * 0.5 + offset);
+            p[axis] = along;
+            p
+        };
+        let active = |part: Part| self.drag.map(|d| d.part) == Some(part) || (self.drag.is_none() && over_part == Some(part));
+        let window_color = WINDOW_COLOR[active(Part::Window) as usize];
+        draw_list.add_rect(Into::<[f32; 2]>::into(point(start, -BAR_SIZE * 0.5 + 1.)),
+            Into::<[f32; 2]>::into(point(end, BAR_SIZE * 0.5 - 1.)), window_color).filled(true).rounding(6.).build();
+        if !self.flags.contains(ZoomSliderFlags::NoMiddleCarets) {
+            let middle = (start + end) * 0.5;
+            for offset in [-4., 0., 4.] {
+                draw_list.add_line(Into::<[f32; 2]>::into(point(middle + offset, -3.)),
+                    Into::<[f32; 2]>::into(point(middle + offset, 3.)), CARET_COLOR).build();
+            }
+        }
+        if !self.flags.contains(ZoomSliderFlags::NoAnchors) {
+            for (pos, part) in [(start, Part::Lower), (end, Part::Higher)] {
+                draw_list.add_circle(Into::<[f32; 2]>::into(point(pos, 0.)), ANCHOR_RADIUS, ANCHOR_COLOR[active(part) as usize])
+                    .filled(true).build();
+            }
+        }
+        (*view_lower, *view_higher) != original
+    }
+}
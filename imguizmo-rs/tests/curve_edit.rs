@@ -0,0 +1,145 @@
+#[cfg(feature = "imgui")]
+extern crate imgui_original as imgui;
+
+#[cfg(feature = "imgui-riri")]
+extern crate imgui_riri as imgui;
+
+use std::sync::{ Mutex, PoisonError };
+use glam::Vec2;
+use imguizmo_rs::curve_edit::{ CurveEditDelegate, CurveEditKeymap, CurveEditState, EditPoint };
+
+const EDITOR_SIZE: [f32; 2] = [200., 200.];
+/// imgui has one global context, so tests running in parallel take turns creating theirs
+static CONTEXT_LOCK: Mutex<()> = Mutex::new(());
+
+struct Curves {
+    curves: Vec<Vec<Vec2>>,
+    state: CurveEditState
+}
+
+impl Curves {
+    fn new(curves: Vec<Vec<Vec2>>) -> Self {
+        Self { curves, state: CurveEditState::new(Vec2::ZERO, Vec2::splat(10.)) }
+    }
+}
+
+impl CurveEditDelegate for Curves {
+    fn get_curve_count(&self) -> usize { self.curves.len() }
+    fn get_curve_color(&self, _curve: usize) -> u32 { 0xffffffff }
+    fn get_points(&self, curve: usize) -> &[Vec2] { &self.curves[curve] }
+    fn edit_point(&mut self, curve: usize, point: usize, value: Vec2) -> usize {
+        let points = &mut self.curves[curve];
+        points.remove(point);
+        let index = points.partition_point(|p| p.x < value.x);
+        points.insert(index, value);
+        index
+    }
+    fn add_point(&mut self, curve: usize, value: Vec2) {
+        let points = &mut self.curves[curve];
+        let index = points.partition_point(|p| p.x < value.x);
+        points.insert(index, value);
+    }
+    fn get_state(&self) -> &CurveEditState { &self.state }
+    fn get_state_mut(&mut self) -> &mut CurveEditState { &mut self.state }
+}
+
+/// Mouse input for a single frame
+#[derive(Debug, Clone, Copy, Default)]
+struct Input {
+    /// Position in value space, mapped through the view at the start of the frame
+    value: Vec2,
+    down: bool,
+    wheel: f32
+}
+
+/// Draw the editor once per input, feeding it the mouse state. Returns
+/// whether any frame changed a curve.
+fn run_frames(curves: &mut Curves, inputs: &[Input]) -> bool {
+    let _guard = CONTEXT_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut ctx = imgui::Context::create();
+    ctx.set_ini_filename(None);
+    ctx.io_mut().display_size = [1280., 720.];
+    ctx.io_mut().delta_time = 1. / 60.;
+    ctx.fonts().build_rgba32_texture();
+    let mut canvas = Vec2::ZERO;
+    let mut changed = false;
+    // one frame to lay out the window before any input arrives
+    for input in [None].into_iter().chain(inputs.iter().map(Some)) {
+        if let Some(input) = input {
+            let (min, max) = (curves.state.get_min(), curves.state.get_max());
+            let n = (input.value - min) / (max - min);
+            let screen = canvas + Vec2::new(n.x, 1. - n.y) * Vec2::from(EDITOR_SIZE);
+            let io = ctx.io_mut();
+            io.mouse_pos = screen.into();
+            io.mouse_down[0] = input.down;
+            io.mouse_wheel = input.wheel;
+        }
+        let ui = ctx.new_frame();
+        ui.window("curves")
+            .position([0., 0.], imgui::Condition::Always)
+            .size([400., 400.], imgui::Condition::Always)
+            .flags(imgui::WindowFlags::NO_TITLE_BAR | imgui::WindowFlags::NO_MOVE)
+            .build(|| {
+                canvas = Vec2::from(ui.cursor_screen_pos());
+                changed |= curves.draw(ui, EDITOR_SIZE, &CurveEditKeymap::default());
+            });
+        ctx.render();
+    }
+    changed
+}
+
+#[test]
+fn zooming_in_keeps_a_minimum_range() {
+    let mut curves = Curves::new(vec![vec![Vec2::ZERO, Vec2::splat(10.)]]);
+    let input = Input { value: Vec2::splat(5.), wheel: 10., ..Default::default() };
+    run_frames(&mut curves, &[input; 200]);
+    let range = curves.state.get_max() - curves.state.get_min();
+    assert!(range.cmplt(Vec2::splat(1e-2)).all(), "view wasn't zoomed: {:?}", range);
+    assert!(range.cmpge(Vec2::splat(1e-4)).all(), "range collapsed to {:?}", range);
+}
+
+#[test]
+fn empty_range_draws() {
+    let mut curves = Curves::new(vec![vec![Vec2::ZERO, Vec2::splat(10.)]]);
+    curves.state.set_range(Vec2::splat(1.), Vec2::splat(1.));
+    run_frames(&mut curves, &[]);
+    curves.state.set_range(Vec2::splat(1e6), Vec2::splat(1e6 + 1e-3));
+    run_frames(&mut curves, &[]);
+}
+
+#[test]
+fn moving_selected_points_past_another_point() {
+    let mut curves = Curves::new(vec![vec![Vec2::new(1., 1.), Vec2::new(2., 2.), Vec2::new(5., 5.)]]);
+    let grab = Input { value: Vec2::new(1., 1.), down: true, ..Default::default() };
+    let drag = Input { value: Vec2::new(7., 1.), down: true, ..Default::default() };
+    let release = Input { value: Vec2::new(7., 1.), ..Default::default() };
+    // select the first two points, then drag both past the third
+    curves.state.set_selection(vec![
+        EditPoint { curve_index: 0, point_index: 0 },
+        EditPoint { curve_index: 0, point_index: 1 }
+    ]);
+    assert!(run_frames(&mut curves, &[grab, drag, drag, release]));
+    assert_eq!(curves.curves[0], [Vec2::new(5., 5.), Vec2::new(7., 1.), Vec2::new(8., 2.)]);
+    let mut selection = curves.state.get_selection().to_vec();
+    selection.sort();
+    assert_eq!(selection, [
+        EditPoint { curve_index: 0, point_index: 1 },
+        EditPoint { curve_index: 0, point_index: 2 }
+    ]);
+}
+
+#[test]
+fn moving_a_curve_further_than_its_point_spacing() {
+    for shift in [3., -3.] {
+        let points = vec![Vec2::new(4., 1.), Vec2::new(6., 3.), Vec2::new(8., 5.)];
+        let mut curves = Curves::new(vec![points.clone()]);
+        // grab the curve between its first two points
+        let grab = Input { value: Vec2::new(5., 2.), down: true, ..Default::default() };
+        let drag = Input { value: Vec2::new(5. + shift, 2.), down: true, ..Default::default() };
+        let release = Input { value: drag.value, ..Default::default() };
+        // a single step, as a later frame can put the points back by chance
+        assert!(run_frames(&mut curves, &[grab, drag, release]));
+        let moved: Vec<Vec2> = points.iter().map(|p| *p + Vec2::new(shift, 0.)).collect();
+        assert_eq!(curves.curves[0], moved);
+    }
+}
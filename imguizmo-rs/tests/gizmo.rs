@@ -0,0 +1,107 @@
+#[cfg(feature = "imgui")]
+extern crate imgui_original as imgui;
+
+#[cfg(feature = "imgui-riri")]
+extern crate imgui_riri as imgui;
+
+use std::sync::{ Mutex, PoisonError };
+use glam::{ Mat4, Vec2, Vec3 };
+use imguizmo_rs::gizmo::{ Gizmo, GizmoConfig, GizmoMode, GizmoOperation };
+
+const DISPLAY_SIZE: [f32; 2] = [800., 600.];
+/// imgui has one global context, so tests running in parallel take turns creating theirs
+static CONTEXT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Mouse input for a single frame
+#[derive(Debug, Clone, Copy)]
+struct Input {
+    pos: Vec2,
+    down: bool
+}
+
+/// Run `manipulate` once per input with a camera looking down -Z at the origin.
+/// Returns what each call returned, and whether the gizmo was hovered after it.
+fn run_frames(operation: GizmoOperation, matrix: &mut Mat4, inputs: &[Input]) -> Vec<(Option<Mat4>, bool)> {
+    let _guard = CONTEXT_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut ctx = imgui::Context::create();
+    ctx.set_ini_filename(None);
+    ctx.io_mut().display_size = DISPLAY_SIZE;
+    ctx.io_mut().delta_time = 1. / 60.;
+    ctx.fonts().build_rgba32_texture();
+    let view = Mat4::look_at_rh(Vec3::new(0., 0., 5.), Vec3::ZERO, Vec3::Y);
+    let projection = Mat4::perspective_rh(45f32.to_radians(), DISPLAY_SIZE[0] / DISPLAY_SIZE[1], 0.1, 100.);
+    let config = GizmoConfig::default().set_rect(Vec2::ZERO, Vec2::from(DISPLAY_SIZE));
+    let mut gizmo = Gizmo::new();
+    let mut results = vec![];
+    for input in inputs {
+        ctx.io_mut().mouse_pos = input.pos.into();
+        ctx.io_mut().mouse_down[0] = input.down;
+        let ui = ctx.new_frame();
+        ui.window("viewport")
+            .position([0., 0.], imgui::Condition::Always)
+            .size(DISPLAY_SIZE, imgui::Condition::Always)
+            .flags(imgui::WindowFlags::NO_DECORATION | imgui::WindowFlags::NO_MOVE)
+            .build(|| {
+                let delta = gizmo.manipulate(ui, &view, &projection, operation, GizmoMode::Local, matrix, &config);
+                results.push((delta, gizmo.is_over()));
+            });
+        ctx.render();
+    }
+    results
+}
+
+/// Screen position along the X axis handle of a gizmo at the origin
+fn find_x_handle(operation: GizmoOperation) -> Vec2 {
+    let center = Vec2::from(DISPLAY_SIZE) * 0.5;
+    let inputs: Vec<Input> = (0..300).map(|x| Input { pos: center + Vec2::new(x as f32, 0.), down: false }).collect();
+    let mut matrix = Mat4::IDENTITY;
+    let results = run_frames(operation, &mut matrix, &inputs);
+    // hover takes effect a frame late while the window becomes hovered
+    let hovered: Vec<usize> = results.iter().enumerate().skip(2).filter(|(_, r)| r.1).map(|(i, _)| i).collect();
+    assert!(!hovered.is_empty(), "no handle along the X axis");
+    inputs[hovered[hovered.len() / 2]].pos
+}
+
+#[test]
+fn only_moving_the_mouse_reports_changes() {
+    for operation in [GizmoOperation::TranslateX, GizmoOperation::ScaleX] {
+        let handle = find_x_handle(operation);
+        let moved = handle + Vec2::new(30., 0.);
+        let inputs = [
+            Input { pos: handle, down: false },
+            Input { pos: handle, down: false },
+            Input { pos: handle, down: true },
+            Input { pos: handle, down: true },
+            Input { pos: moved, down: true },
+            Input { pos: moved, down: true },
+            Input { pos: moved, down: false },
+            Input { pos: moved, down: false }
+        ];
+        let mut matrix = Mat4::IDENTITY;
+        let results = run_frames(operation, &mut matrix, &inputs);
+        assert!(results[4].0.is_some_and(|d| d != Mat4::IDENTITY), "{:?} didn't move", operation);
+        for (i, (delta, _)) in results.iter().enumerate().filter(|(i, _)| *i != 4) {
+            assert!(delta.is_none(), "{:?} reported {:?} on frame {} without moving", operation, delta, i);
+        }
+        assert!(!matrix.abs_diff_eq(Mat4::IDENTITY, 1e-3));
+    }
+}
+
+#[test]
+fn singular_matrices_report_no_delta() {
+    let handle = find_x_handle(GizmoOperation::TranslateX);
+    let moved = handle + Vec2::new(30., 0.);
+    let inputs = [
+        Input { pos: handle, down: false },
+        Input { pos: handle, down: false },
+        Input { pos: handle, down: true },
+        Input { pos: moved, down: true },
+        Input { pos: moved, down: false }
+    ];
+    // flattened along the view direction, which leaves the X handle where it was
+    let flat = Mat4::from_scale(Vec3::new(1., 1., 0.));
+    let mut matrix = flat;
+    let results = run_frames(GizmoOperation::TranslateX, &mut matrix, &inputs);
+    assert_ne!(matrix, flat, "the gizmo wasn't dragged");
+    assert!(results.iter().all(|(delta, _)| delta.is_none()), "{:?}", results);
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "serde")]
+
+#[cfg(feature = "imgui")]
+extern crate imgui_original as imgui;
+
+#[cfg(feature = "imgui-riri")]
+extern crate imgui_riri as imgui;
+
+use imguizmo_rs::sequencer::{
+    OverlapMode,
+    Sequencer,
+    SequencerEditMode,
+    SequencerItem,
+    SequencerMarker,
+    SequencerState,
+    persist::{ self, PersistError },
+    timeline::{ Timeline, TimelineEntry }
+};
+
+fn item(start: u32, end: u32) -> SequencerItem {
+    SequencerItem { start, end, item_type: 1, color: 0xff3080c0 }
+}
+
+fn state() -> SequencerState {
+    let mut state = SequencerState::default();
+    state.set_current_frame(42);
+    state.set_first_frame(10);
+    state.set_selected_entry(Some(1));
+    state.set_frame_pixel_width(12.5);
+    state.set_sub_sequence_path(vec![1, 0]);
+    state.set_edit_mode(SequencerEditMode::NoOverlap(OverlapMode::Block));
+    state
+}
+
+/// Timeline using every serialized field, including a nested sequence
+fn timeline() -> Timeline {
+    let mut nested = Timeline::new(0, 30);
+    nested.add_entry(TimelineEntry::new("nested \"quoted\" label", item(2, 8)));
+    let mut timeline = Timeline::new(0, 200);
+    let track = timeline.add_track("Animation");
+    timeline.add_track("Audio");
+    timeline.add_entry(TimelineEntry::new("walk", item(0, 40)).with_track(track));
+    timeline.add_entry(TimelineEntry::new("cutscene", item(50, 80)).with_track(1).with_sub_sequence(nested));
+    timeline.add_marker(SequencerMarker { frame: 60, label: "hit".to_owned(), color: 0xff00ffff });
+    *timeline.get_state_mut() = state();
+    timeline
+}
+
+#[test]
+fn timeline_round_trips_through_json() {
+    let timeline = timeline();
+    let json = persist::to_json(&timeline).unwrap();
+    assert_eq!(persist::from_json::<Timeline>(&json).unwrap(), timeline);
+}
+
+#[test]
+fn timeline_round_trips_through_ron() {
+    let timeline = timeline();
+    let ron = persist::to_ron(&timeline).unwrap();
+    assert_eq!(persist::from_ron::<Timeline>(&ron).unwrap(), timeline);
+}
+
+#[test]
+fn state_round_trips_through_json() {
+    let state = state();
+    let json = persist::to_json(&state).unwrap();
+    assert_eq!(persist::from_json::<SequencerState>(&json).unwrap(), state);
+}
+
+#[test]
+fn state_round_trips_through_ron() {
+    let state = state();
+    let ron = persist::to_ron(&state).unwrap();
+    assert_eq!(persist::from_ron::<SequencerState>(&ron).unwrap(), state);
+}
+
+#[test]
+fn timeline_round_trips_through_files() {
+    let dir = std::env::temp_dir().join(format!("imguizmo-persist-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let timeline = timeline();
+    persist::save_json(dir.join("timeline.json"), &timeline).unwrap();
+    persist::save_ron(dir.join("timeline.ron"), &timeline).unwrap();
+    let from_json: Timeline = persist::load_json(dir.join("timeline.json")).unwrap();
+    let from_ron: Timeline = persist::load_ron(dir.join("timeline.ron")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(from_json, timeline);
+    assert_eq!(from_ron, timeline);
+}
+
+#[test]
+fn malformed_input_is_an_error() {
+    assert!(matches!(persist::from_json::<Timeline>("{ \"frame_min\": 0, "), Err(PersistError::Json(_))));
+    assert!(matches!(persist::from_json::<Timeline>("{ \"frame_min\": \"zero\" }"), Err(PersistError::Json(_))));
+    assert!(matches!(persist::from_ron::<Timeline>("(frame_min: 0,"), Err(PersistError::RonParse(_))));
+    assert!(matches!(persist::from_ron::<SequencerState>("(current_frame: -1)"), Err(PersistError::RonParse(_))));
+    assert!(matches!(persist::load_json::<Timeline, _>("does/not/exist.json"), Err(PersistError::Io(_))));
+}
+
+#[test]
+fn missing_state_fields_use_defaults() {
+    let state: SequencerState = persist::from_json("{ \"current_frame\": 5 }").unwrap();
+    assert_eq!(state.get_current_frame(), 5);
+    assert_eq!(state.get_frame_pixel_width(), SequencerState::default().get_frame_pixel_width());
+}
@@ -0,0 +1,119 @@
+#[cfg(feature = "imgui")]
+extern crate imgui_original as imgui;
+
+#[cfg(feature = "imgui-riri")]
+extern crate imgui_riri as imgui;
+
+use std::sync::{ Mutex, PoisonError };
+use glam::Vec2;
+use imguizmo_rs::sequencer::{
+    Sequencer,
+    SequencerItem,
+    SequencerKeymap,
+    SequencerMarker,
+    SequencerOptions,
+    timeline::{ Timeline, TimelineEntry }
+};
+
+const WINDOW_SIZE: [f32; 2] = [640., 240.];
+/// Anti-aliased edges add up to a pixel of fringe around lines and rounded shapes
+const AA_FRINGE: f32 = 1.;
+/// imgui has one global context, so tests running in parallel take turns creating theirs
+static CONTEXT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Draw `timeline` in a window and return the canvas it was given along with
+/// every vertex it added to the window's draw list
+fn draw_timeline(timeline: &mut Timeline) -> ([Vec2; 2], Vec<Vec2>) {
+    let _guard = CONTEXT_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut ctx = imgui::Context::create();
+    ctx.set_ini_filename(None);
+    ctx.io_mut().display_size = [1280., 720.];
+    ctx.io_mut().delta_time = 1. / 60.;
+    ctx.fonts().build_rgba32_texture();
+    let ui = ctx.new_frame();
+    let mut result = None;
+    ui.window("sequencer")
+        .position([100., 100.], imgui::Condition::Always)
+        .size(WINDOW_SIZE, imgui::Condition::Always)
+        .build(|| {
+            let canvas_pos = Vec2::from(ui.cursor_screen_pos());
+            let canvas = [canvas_pos, canvas_pos + Vec2::from(ui.content_region_avail())];
+            let draw_list = unsafe { &*imgui::sys::igGetWindowDrawList() };
+            let first_vertex = draw_list.VtxBuffer.Size as usize;
+            timeline.draw(ui, SequencerOptions::EditStartEnd, &SequencerKeymap::default());
+            let vertices = unsafe {
+                std::slice::from_raw_parts(draw_list.VtxBuffer.Data, draw_list.VtxBuffer.Size as usize)
+            };
+            result = Some((canvas, vertices[first_vertex..].iter()
+                .map(|v| Vec2::new(v.pos.x, v.pos.y)).collect()));
+        });
+    ctx.render();
+    result.unwrap()
+}
+
+fn item(start: u32, end: u32) -> SequencerItem {
+    SequencerItem { start, end, item_type: 0, color: 0xff3080c0 }
+}
+
+/// A timeline that overflows the canvas in every direction: more rows than fit,
+/// items and markers on both sides of the visible frames and long labels
+fn overflowing_timeline() -> Timeline {
+    let mut timeline = Timeline::new(0, 1000);
+    for i in 0..40 {
+        let start = (i * 37) % 900;
+        timeline.add_entry(TimelineEntry::new(
+            format!("a very long entry label that runs past the legend {}", i),
+            item(start, start + 120)
+        ));
+    }
+    for frame in [0, 95, 100, 130, 600, 1000] {
+        timeline.add_marker(SequencerMarker {
+            frame,
+            label: format!("marker with a long name at {}", frame),
+            color: 0xff00ffff
+        });
+    }
+    let state = timeline.get_state_mut();
+    state.set_frame_pixel_width(10.);
+    state.set_first_frame(100);
+    state.set_current_frame(140);
+    state.set_selected_entry(Some(3));
+    timeline
+}
+
+#[test]
+fn vertices_stay_inside_canvas() {
+    let mut timeline = overflowing_timeline();
+    let (canvas, vertices) = draw_timeline(&mut timeline);
+    assert!(!vertices.is_empty());
+    let min = canvas[0] - Vec2::splat(AA_FRINGE);
+    let max = canvas[1] + Vec2::splat(AA_FRINGE);
+    for v in vertices {
+        assert!(v.cmpge(min).all() && v.cmple(max).all(),
+            "vertex {:?} is outside of canvas {:?}", v, canvas);
+    }
+}
+
+#[test]
+fn vertices_stay_inside_canvas_at_the_end_of_the_range() {
+    let mut timeline = overflowing_timeline();
+    timeline.get_state_mut().set_first_frame(990);
+    timeline.get_state_mut().set_current_frame(1000);
+    let (canvas, vertices) = draw_timeline(&mut timeline);
+    let min = canvas[0] - Vec2::splat(AA_FRINGE);
+    let max = canvas[1] + Vec2::splat(AA_FRINGE);
+    assert!(vertices.iter().all(|v| v.cmpge(min).all() && v.cmple(max).all()));
+}
+
+#[test]
+fn items_outside_visible_frames_are_culled() {
+    let mut timeline = overflowing_timeline();
+    let (_, baseline) = draw_timeline(&mut timeline);
+    // same row layout, but the first entry now lies far outside the visible frames
+    timeline.get_entries_mut()[0].item = item(900, 950);
+    let (_, culled) = draw_timeline(&mut timeline);
+    timeline.get_entries_mut()[0].item = item(120, 130);
+    let (_, visible) = draw_timeline(&mut timeline);
+    assert!(culled.len() < visible.len());
+    assert!(culled.len() < baseline.len());
+}
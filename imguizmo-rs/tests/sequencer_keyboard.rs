@@ -0,0 +1,172 @@
+#[cfg(feature = "imgui")]
+extern crate imgui_original as imgui;
+
+#[cfg(feature = "imgui-riri")]
+extern crate imgui_riri as imgui;
+
+use std::sync::{ Mutex, MutexGuard, PoisonError };
+use imgui::Key;
+use imguizmo_rs::sequencer::{
+    Sequencer,
+    SequencerItem,
+    SequencerKeymap,
+    SequencerOptions,
+    timeline::{ Timeline, TimelineEntry }
+};
+
+/// imgui has one global context, so tests running in parallel take turns creating theirs
+static CONTEXT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Every shortcut moved off its default key
+const KEYMAP: SequencerKeymap = SequencerKeymap {
+    frame_min: Key::A,
+    frame_max: Key::E,
+    step_back: Key::J,
+    step_forward: Key::L,
+    tick_modifier: Key::ModCtrl,
+    track_up: Key::I,
+    track_down: Key::K,
+    delete: Key::X,
+    frame_selection: Key::Z,
+    leave_sub_sequence: Key::Q
+};
+
+/// Keeps one imgui context alive across frames, so the sequencer window stays focused
+struct Frames {
+    ctx: imgui::Context,
+    _guard: MutexGuard<'static, ()>
+}
+
+impl Frames {
+    /// Draw `timeline` until its window has focus
+    fn new(timeline: &mut Timeline) -> Self {
+        let guard = CONTEXT_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut ctx = imgui::Context::create();
+        ctx.set_ini_filename(None);
+        ctx.io_mut().display_size = [1280., 720.];
+        ctx.io_mut().delta_time = 1. / 60.;
+        ctx.fonts().build_rgba32_texture();
+        let mut frames = Self { ctx, _guard: guard };
+        frames.idle(timeline, 2);
+        assert!(timeline.get_focused());
+        frames
+    }
+
+    /// Draw one frame pressing `keys`, then one releasing them. imgui applies
+    /// one change per key each frame, so a press right after a release would
+    /// be merged with it.
+    fn press(&mut self, timeline: &mut Timeline, keys: &[Key]) {
+        for key in keys {
+            self.ctx.io_mut().add_key_event(*key, true);
+        }
+        self.draw(timeline);
+        for key in keys {
+            self.ctx.io_mut().add_key_event(*key, false);
+        }
+        self.draw(timeline);
+    }
+
+    fn idle(&mut self, timeline: &mut Timeline, count: usize) {
+        for _ in 0..count {
+            self.draw(timeline);
+        }
+    }
+
+    fn draw(&mut self, timeline: &mut Timeline) {
+        let ui = self.ctx.new_frame();
+        ui.window("sequencer")
+            .position([0., 0.], imgui::Condition::Always)
+            .size([640., 240.], imgui::Condition::Always)
+            .build(|| timeline.draw(ui, SequencerOptions::EditStartEnd | SequencerOptions::Delete, &KEYMAP));
+        self.ctx.render();
+    }
+}
+
+fn item(start: u32, end: u32) -> SequencerItem {
+    SequencerItem { start, end, item_type: 0, color: 0xff3080c0 }
+}
+
+/// Items 0 (0-10) and 1 (50-60) on the first track, nothing on the second
+/// and item 2 (20-30) on the third
+fn timeline() -> Timeline {
+    let mut timeline = Timeline::new(0, 100);
+    for label in ["first", "empty", "third"] {
+        timeline.add_track(label);
+    }
+    for (start, track) in [(0, 0), (50, 0), (20, 2)] {
+        timeline.add_entry(TimelineEntry::new("item", item(start, start + 10)).with_track(track));
+    }
+    timeline
+}
+
+#[test]
+fn remapped_keys_move_the_current_frame() {
+    let mut timeline = timeline();
+    let mut frames = Frames::new(&mut timeline);
+    let frame = |timeline: &Timeline| timeline.get_state().get_current_frame();
+    frames.press(&mut timeline, &[Key::L]);
+    frames.press(&mut timeline, &[Key::L]);
+    assert_eq!(frame(&timeline), 2);
+    frames.press(&mut timeline, &[Key::J]);
+    assert_eq!(frame(&timeline), 1);
+    // stepping by a tick moves further, and back by the same amount
+    frames.press(&mut timeline, &[Key::ModCtrl, Key::L]);
+    let tick = frame(&timeline) - 1;
+    assert!(tick > 1, "a tick step moved {} frames", tick);
+    frames.press(&mut timeline, &[Key::ModCtrl, Key::J]);
+    assert_eq!(frame(&timeline), 1);
+    frames.press(&mut timeline, &[Key::E]);
+    assert_eq!(frame(&timeline), 100);
+    frames.press(&mut timeline, &[Key::L]);
+    assert_eq!(frame(&timeline), 100);
+    frames.press(&mut timeline, &[Key::A]);
+    assert_eq!(frame(&timeline), 0);
+    frames.press(&mut timeline, &[Key::J]);
+    assert_eq!(frame(&timeline), 0);
+    // the default keys no longer do anything
+    frames.press(&mut timeline, &[Key::RightArrow]);
+    frames.press(&mut timeline, &[Key::End]);
+    assert_eq!(frame(&timeline), 0);
+}
+
+#[test]
+fn remapped_keys_change_the_selected_track() {
+    let mut timeline = timeline();
+    let mut frames = Frames::new(&mut timeline);
+    let selected = |timeline: &Timeline| timeline.get_state().get_selected_entry();
+    frames.press(&mut timeline, &[Key::K]);
+    assert_eq!(selected(&timeline), Some(0));
+    // the empty track is skipped
+    frames.press(&mut timeline, &[Key::K]);
+    assert_eq!(selected(&timeline), Some(2));
+    frames.press(&mut timeline, &[Key::K]);
+    assert_eq!(selected(&timeline), Some(2));
+    // the item nearest to the current frame is picked on the new track
+    timeline.get_state_mut().set_current_frame(55);
+    frames.press(&mut timeline, &[Key::I]);
+    assert_eq!(selected(&timeline), Some(1));
+    frames.press(&mut timeline, &[Key::I]);
+    assert_eq!(selected(&timeline), Some(1));
+    frames.press(&mut timeline, &[Key::DownArrow]);
+    assert_eq!(selected(&timeline), Some(1));
+    frames.press(&mut timeline, &[Key::X]);
+    assert_eq!(selected(&timeline), None);
+    assert_eq!(timeline.get_entries().len(), 2);
+}
+
+#[test]
+fn remapped_key_frames_the_selection() {
+    let mut timeline = timeline();
+    timeline.get_state_mut().set_first_frame(70);
+    timeline.get_state_mut().set_selected_entry(Some(2));
+    let mut frames = Frames::new(&mut timeline);
+    frames.press(&mut timeline, &[Key::F]);
+    assert_eq!(timeline.get_state().get_first_frame(), 70);
+    frames.press(&mut timeline, &[Key::Z]);
+    // zooming eases in over a few frames
+    frames.idle(&mut timeline, 60);
+    let (lower, higher) = timeline.get_state().get_view_range();
+    // a frame of padding on either side of frames 20 to 30
+    assert_eq!(lower, 19.);
+    assert!((higher - 32.).abs() < 0.5, "view ends at {}", higher);
+}
@@ -0,0 +1,57 @@
+#[cfg(feature = "imgui")]
+extern crate imgui_original as imgui;
+
+#[cfg(feature = "imgui-riri")]
+extern crate imgui_riri as imgui;
+
+use imguizmo_rs::sequencer::{
+    Sequencer,
+    SequencerItem,
+    SequencerKeymap,
+    SequencerOptions,
+    timeline::{ Timeline, TimelineEntry }
+};
+
+fn item(start: u32, end: u32) -> SequencerItem {
+    SequencerItem { start, end, item_type: 0, color: 0xff3080c0 }
+}
+
+/// Draw `timeline` once per entry in `keys`, pressing the given key for that frame
+fn run_frames(timeline: &mut Timeline, keys: &[Option<imgui::Key>]) {
+    let mut ctx = imgui::Context::create();
+    ctx.set_ini_filename(None);
+    ctx.io_mut().display_size = [1280., 720.];
+    ctx.io_mut().delta_time = 1. / 60.;
+    ctx.fonts().build_rgba32_texture();
+    for key in keys {
+        if let Some(key) = key {
+            ctx.io_mut().add_key_event(*key, true);
+        }
+        let ui = ctx.new_frame();
+        ui.window("sequencer")
+            .position([0., 0.], imgui::Condition::Always)
+            .size([640., 240.], imgui::Condition::Always)
+            .build(|| timeline.draw(ui, SequencerOptions::EditStartEnd, &SequencerKeymap::default()));
+        ctx.render();
+        if let Some(key) = key {
+            ctx.io_mut().add_key_event(*key, false);
+        }
+    }
+}
+
+#[test]
+fn entered_sub_sequence_takes_input_on_its_first_frame() {
+    let mut nested = Timeline::new(0, 50);
+    nested.add_entry(TimelineEntry::new("inner", item(0, 10)));
+    let mut timeline = Timeline::new(0, 100);
+    timeline.add_entry(TimelineEntry::new("outer", item(0, 40)).with_sub_sequence(nested));
+    // focus the window at the root
+    run_frames(&mut timeline, &[None, None]);
+    assert!(timeline.get_focused());
+
+    timeline.get_state_mut().set_sub_sequence_path(vec![0]);
+    run_frames(&mut timeline, &[Some(imgui::Key::RightArrow)]);
+    let nested = timeline.get_entries()[0].sub_sequence.as_ref().unwrap();
+    assert_eq!(nested.get_state().get_current_frame(), 1);
+    assert_eq!(timeline.get_state().get_current_frame(), 0);
+}
@@ -0,0 +1,114 @@
+#[cfg(feature = "imgui")]
+extern crate imgui_original as imgui;
+
+#[cfg(feature = "imgui-riri")]
+extern crate imgui_riri as imgui;
+
+use glam::{ Mat4, Quat, Vec3 };
+use imguizmo_rs::gizmo::transform::{
+    EulerOrder,
+    TransformComponents,
+    decompose_matrix_to_components,
+    recompose_matrix_from_components
+};
+
+const EPSILON: f32 = 1e-4;
+
+/// Matrices covering positive, negative and non-uniform scale, and rotations
+/// around every axis
+fn matrices() -> Vec<Mat4> {
+    let rotations = [
+        Quat::IDENTITY,
+        Quat::from_rotation_x(0.7),
+        Quat::from_rotation_y(-2.1),
+        Quat::from_rotation_z(3.),
+        Quat::from_euler(glam::EulerRot::XYZ, 0.3, -1.2, 2.5),
+        Quat::from_axis_angle(Vec3::new(1., 2., -3.).normalize(), 1.9)
+    ];
+    let scales = [Vec3::ONE, Vec3::new(2., 0.5, 3.), Vec3::new(-1.5, 1., 0.25)];
+    rotations.into_iter().flat_map(|rotation| scales.map(|scale|
+        Mat4::from_scale_rotation_translation(scale, rotation, Vec3::new(1., -2., 30.))
+    )).collect()
+}
+
+/// Index of the axis an order applies second, the one that can hit gimbal lock
+fn middle_axis(order: EulerOrder) -> usize {
+    match order.get_name().as_bytes()[1] {
+        b'X' => 0,
+        b'Y' => 1,
+        _ => 2
+    }
+}
+
+/// Whether two quaternions describe the same rotation
+fn same_rotation(a: Quat, b: Quat) -> bool {
+    a.dot(b).abs() > 1. - EPSILON
+}
+
+#[test]
+fn recompose_of_decompose_is_identity_for_every_order() {
+    for order in EulerOrder::ALL {
+        for matrix in matrices() {
+            let components = TransformComponents::from_matrix(&matrix, order);
+            let recomposed = components.to_matrix(order);
+            assert!(recomposed.abs_diff_eq(matrix, EPSILON),
+                "{} changed {:?} into {:?} through {:?}", order.get_name(), matrix, recomposed, components);
+        }
+    }
+}
+
+#[test]
+fn imguizmo_decompose_and_recompose_round_trip() {
+    for matrix in matrices() {
+        let (translation, rotation, scale) = decompose_matrix_to_components(&matrix);
+        assert!(recompose_matrix_from_components(translation, rotation, scale).abs_diff_eq(matrix, EPSILON));
+    }
+}
+
+#[test]
+fn recompose_of_decompose_at_gimbal_lock() {
+    for order in EulerOrder::ALL {
+        for middle in [90., -90.] {
+            let mut angles = Vec3::new(30., 20., 40.);
+            angles[middle_axis(order)] = middle;
+            let matrix = TransformComponents { rotation: angles, ..Default::default() }.to_matrix(order);
+            let recomposed = TransformComponents::from_matrix(&matrix, order).to_matrix(order);
+            assert!(recomposed.abs_diff_eq(matrix, EPSILON), "{} at {}", order.get_name(), middle);
+        }
+    }
+}
+
+#[test]
+fn angles_near_gimbal_lock_stay_continuous() {
+    for order in EulerOrder::ALL {
+        let middle = middle_axis(order);
+        let mut angles = Vec3::new(30., 20., 40.);
+        angles[middle] = 88.;
+        let mut previous = angles;
+        // sweep the middle angle through 90 degrees and back
+        let steps = (0..=80).chain((0..80).rev()).map(|i| 88. + i as f32 * 0.05);
+        for value in steps {
+            angles[middle] = value;
+            let rotation = order.to_quat(angles);
+            let out = order.from_quat_near(rotation, previous);
+            assert!(same_rotation(order.to_quat(out), rotation),
+                "{} gave {:?}, a different rotation from {:?}", order.get_name(), out, angles);
+            let jump = (out - previous).abs().max_element();
+            assert!(jump < 1., "{} jumped from {:?} to {:?} at {}", order.get_name(), previous, out, value);
+            previous = out;
+        }
+    }
+}
+
+#[test]
+fn matrices_near_gimbal_lock_keep_previous_angles() {
+    for order in EulerOrder::ALL {
+        let mut angles = Vec3::new(-15., 25., 60.);
+        angles[middle_axis(order)] = 89.999;
+        let matrix = TransformComponents { rotation: angles, ..Default::default() }.to_matrix(order);
+        let components = TransformComponents::from_matrix_near(&matrix, order, Some(angles));
+        assert!(components.to_matrix(order).abs_diff_eq(matrix, EPSILON), "{}", order.get_name());
+        assert!((components.rotation - angles).abs().max_element() < 0.5,
+            "{} turned {:?} into {:?}", order.get_name(), angles, components.rotation);
+    }
+}
@@ -0,0 +1,107 @@
+#[cfg(feature = "imgui")]
+extern crate imgui_original as imgui;
+
+#[cfg(feature = "imgui-riri")]
+extern crate imgui_riri as imgui;
+
+use std::sync::{ Mutex, PoisonError };
+use imguizmo_rs::zoom_slider::{ ZoomSlider, ZoomSliderFlags };
+
+const RANGE: (f32, f32) = (0., 100.);
+/// imgui has one global context, so tests running in parallel take turns creating theirs
+static CONTEXT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Mouse input for a single frame
+#[derive(Debug, Clone, Copy, Default)]
+struct Input {
+    /// Position along the slider, from 0 at the left end to 1 at the right end
+    along: f32,
+    down: bool,
+    wheel: f32
+}
+
+/// Draw a horizontal slider over [`RANGE`] once per input, editing `view`.
+/// Returns whether any frame changed the view.
+fn run_frames(view: &mut (f32, f32), inputs: &[Input]) -> bool {
+    let _guard = CONTEXT_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut ctx = imgui::Context::create();
+    ctx.set_ini_filename(None);
+    ctx.io_mut().display_size = [1280., 720.];
+    ctx.io_mut().delta_time = 1. / 60.;
+    ctx.fonts().build_rgba32_texture();
+    let mut slider = ZoomSlider::new();
+    let mut bar = ([0f32; 2], [0f32; 2]);
+    let mut changed = false;
+    // one frame to lay out the window before any input arrives
+    for input in [None].into_iter().chain(inputs.iter().map(Some)) {
+        if let Some(input) = input {
+            let io = ctx.io_mut();
+            io.mouse_pos = [bar.0[0] + (bar.1[0] - bar.0[0]) * input.along, (bar.0[1] + bar.1[1]) * 0.5];
+            io.mouse_down[0] = input.down;
+            io.mouse_wheel = input.wheel;
+        }
+        let ui = ctx.new_frame();
+        ui.window("slider")
+            .position([0., 0.], imgui::Condition::Always)
+            .size([400., 100.], imgui::Condition::Always)
+            .flags(imgui::WindowFlags::NO_TITLE_BAR | imgui::WindowFlags::NO_MOVE)
+            .build(|| {
+                changed |= slider.draw(ui, RANGE.0, RANGE.1, &mut view.0, &mut view.1);
+                bar = (ui.item_rect_min(), ui.item_rect_max());
+            });
+        ctx.render();
+    }
+    changed
+}
+
+fn assert_inside_range(view: (f32, f32)) {
+    assert!(RANGE.0 <= view.0 && view.0 <= view.1 && view.1 <= RANGE.1, "view {:?} is outside of {:?}", view, RANGE);
+}
+
+#[test]
+fn dragging_a_view_wider_than_the_range() {
+    let mut view = (-50., 150.);
+    let press = Input { along: 0.5, down: true, ..Default::default() };
+    let drag = Input { along: 0.7, down: true, ..Default::default() };
+    assert!(run_frames(&mut view, &[press, drag, drag, Input { along: 0.7, ..Default::default() }]));
+    assert_inside_range(view);
+}
+
+#[test]
+fn zooming_a_view_wider_than_the_range() {
+    let mut view = (-50., 150.);
+    assert!(run_frames(&mut view, &[Input { along: 0.3, wheel: 1., ..Default::default() }]));
+    assert_inside_range(view);
+}
+
+#[test]
+fn clicking_with_the_view_outside_the_range() {
+    for start in [-300., 200.] {
+        let mut view = (start, start + 20.);
+        let press = Input { along: 0.5, down: true, ..Default::default() };
+        let drag = Input { along: 0.1, down: true, ..Default::default() };
+        assert!(run_frames(&mut view, &[press, drag, Input::default()]));
+        assert_inside_range(view);
+    }
+}
+
+#[test]
+fn sliders_side_by_side_have_their_own_ids() {
+    let _guard = CONTEXT_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut ctx = imgui::Context::create();
+    ctx.set_ini_filename(None);
+    ctx.io_mut().display_size = [1280., 720.];
+    ctx.fonts().build_rgba32_texture();
+    let mut sliders = [ZoomSlider::new(), ZoomSlider::new().set_flags(ZoomSliderFlags::Vertical)];
+    let mut view = (10., 20.);
+    let mut ids = vec![];
+    let ui = ctx.new_frame();
+    ui.window("sliders").build(|| {
+        for slider in sliders.iter_mut() {
+            slider.draw(ui, RANGE.0, RANGE.1, &mut view.0, &mut view.1);
+            ids.push(unsafe { imgui::sys::igGetItemID() });
+        }
+    });
+    ctx.render();
+    assert_ne!(ids[0], ids[1]);
+}
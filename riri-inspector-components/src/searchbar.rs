@@ -1,110 +1,811 @@
 // #![allow(dead_code)]
 use imgui::{
     ComboBoxFlags,
-    Ui
+    Condition,
+    Key,
+    MouseButton,
+    StyleColor,
+    StyleVar,
+    Ui,
+    WindowFlags
 };
-use regex::Regex;
+use regex::{ Regex, RegexBuilder };
+use crate::text::{ create_highlighted_text, HighlightStyle };
 use std::{
+    error::Error,
     fmt::Debug,
-    ptr::NonNull
+    sync::Arc
 };
 
-pub trait SearchType : Debug {
+pub mod filter;
+pub mod fuzzy;
+pub mod glob;
+pub mod history;
+pub mod query;
+
+use history::{ HistoryEntry, HistoryStore, SavedFilter, SearchHistory };
+use query::{ Query, Searchable };
+
+/// How well a candidate matched the query
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SearchMatch {
+    /// Higher is better. Only comparable between matches from the same search type.
+    pub score: i32,
+    /// Byte offsets of the matched characters in the candidate, in order
+    pub indices: Vec<usize>
+}
+
+impl SearchMatch {
+    /// Match covering a byte range of the candidate
+    pub fn from_range(src: &str, range: std::ops::Range<usize>, score: i32) -> Self {
+        Self {
+            score,
+            indices: src[range.clone()].char_indices().map(|(i, _)| range.start + i).collect()
+        }
+    }
+}
+
+/// Options shared by every search type, toggled with the buttons next to the search box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchOptions {
+    /// When off, text is compared after [`fold_case`], except by regexes, which
+    /// use the regex crate's simple case folding and so don't match `ß` to `ss`
+    pub case_sensitive: bool,
+    /// Only match whole words: the match can't start or end next to a letter, digit or `_`
+    pub whole_word: bool,
+    /// Show what doesn't match instead. Applied by [`Searchbar`], so search types can ignore it.
+    pub invert: bool
+}
+
+/// Lowercase `src` for case insensitive comparisons, folding `ß` to `ss` and
+/// final sigma to sigma as Unicode case folding does. Returns the folded
+/// string and, for each of its bytes, the offset of the character in `src` it
+/// came from.
+pub fn fold_case(src: &str) -> (String, Vec<usize>) {
+    let mut out = String::with_capacity(src.len());
+    let mut offsets = Vec::with_capacity(src.len());
+    for (i, c) in src.char_indices() {
+        let before = out.len();
+        match c {
+            'ß' | 'ẞ' => out.push_str("ss"),
+            'ς' => out.push('σ'),
+            c => out.extend(c.to_lowercase())
+        }
+        offsets.resize(offsets.len() + out.len() - before, i);
+    }
+    (out, offsets)
+}
+
+fn is_word_char(c: char) -> bool { c.is_alphanumeric() || c == '_' }
+
+/// Whether a byte range of `src` starts and ends on word boundaries
+pub fn is_whole_word(src: &str, range: &std::ops::Range<usize>) -> bool {
+    !src[..range.start].chars().next_back().is_some_and(is_word_char)
+        && !src[range.end..].chars().next().is_some_and(is_word_char)
+}
+
+/// Byte range of the first occurrence of `tgt` in `src`
+fn find_substring(src: &str, tgt: &str, options: &SearchOptions) -> Option<std::ops::Range<usize>> {
+    let valid = |range: &std::ops::Range<usize>| !options.whole_word || is_whole_word(src, range);
+    if options.case_sensitive {
+        return src.match_indices(tgt).map(|(i, m)| i..i + m.len()).find(valid);
+    }
+    let (folded, offsets) = fold_case(src);
+    let (tgt, _) = fold_case(tgt);
+    if tgt.is_empty() {
+        return Some(0..0).filter(valid);
+    }
+    folded.match_indices(&tgt).map(|(i, m)| {
+        // the range covers every source character the match touches
+        let last = offsets[i + m.len() - 1];
+        offsets[i]..last + src[last..].chars().next().map_or(0, char::len_utf8)
+    }).find(valid)
+}
+
+/// Lets boxed search types be cloned, so [`filter::FilterEngine`] can search
+/// on a worker thread while the search bar keeps its own copy
+pub trait SearchTypeClone {
+    fn clone_box(&self) -> Box<dyn SearchType>;
+}
+impl<T: SearchType + Clone + 'static> SearchTypeClone for T {
+    fn clone_box(&self) -> Box<dyn SearchType> { Box::new(self.clone()) }
+}
+impl Clone for Box<dyn SearchType> {
+    fn clone(&self) -> Self { self.clone_box() }
+}
+
+pub trait SearchType : Debug + Send + Sync + SearchTypeClone {
     fn get_name(&self) -> &str;
-    fn search_matches(&self, src: &str, tgt: &str) -> bool;
-    fn update(&mut self, new: &str);
+    fn search_matches(&self, src: &str, tgt: &str, options: &SearchOptions) -> bool;
+    /// Score and matched characters, or `None` if `src` doesn't match. Search
+    /// types that don't rank their matches give every match a score of zero.
+    fn search_match(&self, src: &str, tgt: &str, options: &SearchOptions) -> Option<SearchMatch> {
+        self.search_matches(src, tgt, options).then(SearchMatch::default)
+    }
+    /// Called with the new query whenever it or the options change, and when the search type is selected
+    fn update(&mut self, new: &str, options: &SearchOptions);
+    /// Problem with the current query, shown on the search box
+    fn get_error(&self) -> Option<&dyn Error> { None }
+    /// Structured query parsed from the search box, for search types that have one.
+    /// [`Searchbar::matches_item`] evaluates it against an item's fields.
+    fn get_query(&self) -> Option<&Query> { None }
+    /// Whether everything matching `new` also matches `old` with the same
+    /// options, so results for `old` can be narrowed down instead of searching again
+    fn narrows(&self, _old: &str, _new: &str, _options: &SearchOptions) -> bool { false }
 }
-#[derive(Debug)]
+
+/// Whether `item` matches `query` with `search_type`, before [`SearchOptions::invert`] is applied
+fn item_matches<S: Searchable + ?Sized>(search_type: &dyn SearchType, query: &str, options: &SearchOptions, item: &S) -> bool {
+    match search_type.get_query() {
+        Some(parsed) => parsed.matches(item, options),
+        None => search_type.search_matches(&item.get_text(), query, options)
+    }
+}
+#[derive(Debug, Clone)]
 pub struct SearchContaining;
 impl SearchType for SearchContaining {
     fn get_name(&self) -> &str { "Contains" }
-    fn update(&mut self, _new: &str) {}
-    fn search_matches(&self, src: &str, tgt: &str) -> bool {
-        src.contains(tgt)
+    fn update(&mut self, _new: &str, _options: &SearchOptions) {}
+    fn search_matches(&self, src: &str, tgt: &str, options: &SearchOptions) -> bool {
+        find_substring(src, tgt, options).is_some()
+    }
+    fn search_match(&self, src: &str, tgt: &str, options: &SearchOptions) -> Option<SearchMatch> {
+        // earlier matches rank higher
+        find_substring(src, tgt, options).map(|r| { let start = r.start; SearchMatch::from_range(src, r, -(start as i32)) })
     }
+    /// A whole word match of the longer query doesn't have to be one of the shorter query
+    fn narrows(&self, old: &str, new: &str, options: &SearchOptions) -> bool { !options.whole_word && new.contains(old) }
 }
 impl SearchContaining {
     pub fn new() -> Self { Self }
 }
-#[derive(Debug)]
+/// Matches the query as a whole word, whether or not [`SearchOptions::whole_word`] is set
+#[derive(Debug, Clone)]
 pub struct SearchWholeWord;
 impl SearchType for SearchWholeWord {
     fn get_name(&self) -> &str { "Whole Word" }
-    fn update(&mut self, _new: &str) {}
-    fn search_matches(&self, src: &str, tgt: &str) -> bool {
-        src == tgt
+    fn update(&mut self, _new: &str, _options: &SearchOptions) {}
+    fn search_matches(&self, src: &str, tgt: &str, options: &SearchOptions) -> bool {
+        self.search_match(src, tgt, options).is_some()
+    }
+    fn search_match(&self, src: &str, tgt: &str, options: &SearchOptions) -> Option<SearchMatch> {
+        let options = SearchOptions { whole_word: true, ..*options };
+        find_substring(src, tgt, &options).map(|r| { let start = r.start; SearchMatch::from_range(src, r, -(start as i32)) })
     }
 }
 impl SearchWholeWord {
     pub fn new() -> Self { Self }
 }
 
-#[derive(Debug)]
+/// Keeps matching with the last valid pattern while the query doesn't compile.
+/// Case insensitive patterns fold characters one to one, unlike [`fold_case`].
+#[derive(Debug, Clone)]
 pub struct SearchRegex {
     regex: Regex,
     error: Option<regex::Error>
 }
 impl SearchType for SearchRegex {
-    fn get_name(&self) -> &str { "Whole Word" }
-    fn update(&mut self, new: &str) {
-        match Regex::new(new) {
-            Ok(v) => self.regex = v,
+    fn get_name(&self) -> &str { "Regex" }
+    fn update(&mut self, new: &str, options: &SearchOptions) {
+        let pattern = if options.whole_word { format!(r"\b(?:{new})\b") } else { new.to_owned() };
+        match RegexBuilder::new(&pattern).case_insensitive(!options.case_sensitive).build() {
+            Ok(v) => {
+                self.regex = v;
+                self.error = None;
+            },
             Err(e) => self.error = Some(e)
         }
     }
-    fn search_matches(&self, src: &str, _tgt: &str) -> bool {
+    fn search_matches(&self, src: &str, _tgt: &str, _options: &SearchOptions) -> bool {
         self.regex.is_match(src)
     }
+    fn search_match(&self, src: &str, _tgt: &str, _options: &SearchOptions) -> Option<SearchMatch> {
+        self.regex.find(src).map(|m| SearchMatch::from_range(src, m.range(), -(m.start() as i32)))
+    }
+    fn get_error(&self) -> Option<&dyn Error> {
+        self.error.as_ref().map(|e| e as &dyn Error)
+    }
+}
+impl SearchRegex {
+    pub fn new() -> Self {
+        Self {
+            regex: Regex::new("").unwrap(),
+            error: None
+        }
+    }
+}
+impl Default for SearchRegex {
+    fn default() -> Self { Self::new() }
+}
+
+/// Suggests completions for the word being typed, such as field names or known values
+type CompleterFn = dyn Fn(&str) -> Vec<String> + Send + Sync;
+#[derive(Clone)]
+struct Completer(Arc<CompleterFn>);
+impl Debug for Completer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Completer")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PopupEntry {
+    Suggestion(usize),
+    Saved(usize),
+    History(usize)
+}
+
+/// State of the dropdown under the search box
+#[derive(Debug, Clone, Default)]
+struct SearchPopup {
+    open: bool,
+    highlighted: Option<usize>,
+    suggestions: Vec<String>,
+    /// Whether the mouse was over the dropdown last frame, so clicking it doesn't close it
+    hovered: bool,
+    /// Give the search box keyboard focus on the next draw
+    refocus: bool
 }
 
-#[derive(Debug)]
+/// Search box with a selectable search type. Cloning it copies the search
+/// types and history, sharing the history store and completer.
+#[derive(Debug, Clone)]
 pub struct Searchbar {
     buf: String,
     label: String,
     show_label: bool,
     search_types: Vec<Box<dyn SearchType>>,
-    type_selected: NonNull<Box<dyn SearchType>>
+    /// Index into `search_types`
+    type_selected: usize,
+    options: SearchOptions,
+    changed: bool,
+    history: SearchHistory,
+    completer: Option<Completer>,
+    popup: SearchPopup
 }
 impl Searchbar {
     pub fn new<T>(label: T, show_label: bool) -> Self
     where T: Into<String>
     { 
-        let mut out = Self {
+        Self {
             buf: String::new(),
             label: label.into(),
             show_label,
             search_types: vec![
                 Box::new(SearchContaining::new()),
-                Box::new(SearchWholeWord::new())
+                Box::new(SearchWholeWord::new()),
+                Box::new(SearchRegex::new()),
+                Box::new(fuzzy::SearchFuzzy::new()),
+                Box::new(glob::SearchGlob::new()),
+                Box::new(glob::SearchGlob::new_path()),
+                Box::new(query::SearchQuery::new())
             ],
-            type_selected: NonNull::dangling()
-        };
-        out.type_selected = unsafe { NonNull::new_unchecked(&raw mut out.search_types[0]) };
+            type_selected: 0,
+            options: SearchOptions::default(),
+            changed: false,
+            history: SearchHistory::new(),
+            completer: None,
+            popup: SearchPopup::default()
+        }
+    }
+
+    /// Register a search type, replacing any registered type with the same name
+    pub fn with_search_type<T: SearchType + 'static>(mut self, search_type: T) -> Self {
+        let mut search_type: Box<dyn SearchType> = Box::new(search_type);
+        match self.search_types.iter().position(|t| t.get_name() == search_type.get_name()) {
+            Some(i) => {
+                if i == self.type_selected {
+                    search_type.update(&self.buf, &self.options);
+                }
+                self.search_types[i] = search_type;
+            },
+            None => self.search_types.push(search_type)
+        }
+        self
+    }
+
+    /// Select the search type called `name`. Returns false if none is registered.
+    pub fn set_mode(&mut self, name: &str) -> bool {
+        let Some(i) = self.search_types.iter().position(|t| t.get_name() == name) else { return false };
+        if i != self.type_selected {
+            self.type_selected = i;
+            self.changed = true;
+            self.update_selected();
+        }
+        true
+    }
+
+    /// Name of the selected search type
+    pub fn get_mode(&self) -> &str { self.get_search_type().get_name() }
+    /// Names of the registered search types, in the order they're listed
+    pub fn get_modes(&self) -> impl Iterator<Item = &str> {
+        self.search_types.iter().map(|t| t.get_name())
+    }
+
+    /// Load recent queries and saved filters from `store`, and keep it updated
+    pub fn set_history_store<T: HistoryStore + 'static>(mut self, store: T) -> Self {
+        self.history.set_store(store);
+        self
+    }
+
+    /// Most recent queries kept. 20 by default.
+    pub fn set_history_limit(mut self, limit: usize) -> Self {
+        self.history.set_limit(limit);
+        self
+    }
+
+    /// Callback suggesting completions for the word at the end of the query.
+    /// Picking a suggestion replaces that word.
+    pub fn set_completer<F>(mut self, completer: F) -> Self
+    where F: Fn(&str) -> Vec<String> + Send + Sync + 'static
+    {
+        self.completer = Some(Completer(Arc::new(completer)));
+        self
+    }
+
+    pub fn get_history(&self) -> &SearchHistory { &self.history }
+    pub fn get_history_mut(&mut self) -> &mut SearchHistory { &mut self.history }
+
+    /// Save the current query, search type and options as a named filter
+    pub fn save_filter<T>(&mut self, name: T)
+    where T: Into<String>
+    {
+        self.history.add_saved(SavedFilter {
+            name: name.into(),
+            query: self.buf.clone(),
+            search_type: self.get_mode().to_owned(),
+            options: self.options
+        });
+    }
+
+    /// Apply a saved filter's query and options, and its search type if it's registered
+    pub fn apply_saved_filter(&mut self, index: usize) {
+        let Some(filter) = self.history.get_saved().get(index).cloned() else { return };
+        if let Some(i) = self.search_types.iter().position(|t| t.get_name() == filter.search_type) {
+            self.type_selected = i;
+        }
+        self.buf = filter.query;
+        self.options = filter.options;
+        self.changed = true;
+        self.update_selected();
+    }
+
+    /// Search again with a recent query, using the options and search type it
+    /// was searched with if that type is registered
+    pub fn apply_history_entry(&mut self, index: usize) {
+        let Some(entry) = self.history.get_entries().get(index).cloned() else { return };
+        if let Some(i) = self.search_types.iter().position(|t| t.get_name() == entry.search_type) {
+            self.type_selected = i;
+        }
+        self.buf = entry.query;
+        self.options = entry.options;
+        self.changed = true;
+        self.update_selected();
+    }
+
+    pub fn get_query(&self) -> &str { &self.buf }
+    /// Search type currently selected
+    pub fn get_search_type(&self) -> &dyn SearchType { self.search_types[self.type_selected].as_ref() }
+    pub fn set_query<T>(&mut self, query: T)
+    where T: Into<String>
+    {
+        self.buf = query.into();
+        self.changed = true;
+        self.update_selected();
+    }
+    pub fn get_options(&self) -> SearchOptions { self.options }
+    pub fn set_options(&mut self, options: SearchOptions) {
+        self.options = options;
+        self.changed = true;
+        self.update_selected();
+    }
+    /// Whether the query, options or search type changed during the last call to `draw`
+    /// (or since, through `set_query`)
+    pub fn changed(&self) -> bool { self.changed }
+
+    /// Whether `candidate` matches the query. Everything matches an empty query.
+    pub fn matches(&self, candidate: &str) -> bool {
+        self.buf.is_empty()
+            || self.get_search_type().search_matches(candidate, &self.buf, &self.options) != self.options.invert
+    }
+
+    /// Whether `item` matches the query. Structured queries are evaluated
+    /// against its fields, other search types search its text.
+    pub fn matches_item<S: Searchable + ?Sized>(&self, item: &S) -> bool {
+        if self.buf.is_empty() {
+            return true;
+        }
+        item_matches(self.get_search_type(), &self.buf, &self.options, item) != self.options.invert
+    }
+
+    /// Score and matched characters of `candidate`, or `None` if it doesn't match
+    pub fn search_match(&self, candidate: &str) -> Option<SearchMatch> {
+        if self.buf.is_empty() {
+            return Some(SearchMatch::default());
+        }
+        let found = self.get_search_type().search_match(candidate, &self.buf, &self.options);
+        match self.options.invert {
+            true => found.is_none().then(SearchMatch::default),
+            false => found
+        }
+    }
+
+    /// Draw `text` with the characters matching the query highlighted, so
+    /// results show why they matched. Returns whether `text` matches.
+    pub fn highlighted_text(&self, ui: &Ui, text: &str, style: HighlightStyle) -> bool {
+        match self.search_match(text) {
+            Some(found) => {
+                create_highlighted_text(ui, text, &found.indices, style);
+                true
+            },
+            None => {
+                ui.text(text);
+                false
+            }
+        }
+    }
+
+    /// Items whose key matches the query, best matches first. Items with the
+    /// same score keep their order.
+    pub fn filter_ranked<T, I, F>(&self, items: I, key_fn: F) -> Vec<(T, SearchMatch)>
+    where I: IntoIterator<Item = T>,
+          F: Fn(&T) -> &str
+    {
+        let mut out: Vec<(T, SearchMatch)> = items.into_iter()
+            .filter_map(|item| self.search_match(key_fn(&item)).map(|m| (item, m)))
+            .collect();
+        out.sort_by_key(|(_, m)| std::cmp::Reverse(m.score));
         out
     }
-    pub fn draw(&mut self, ui: &Ui) {
+
+    /// Items whose key matches the query
+    pub fn filter<'a, T, I, F>(&'a self, items: I, key_fn: F) -> impl Iterator<Item = T> + 'a
+    where I: IntoIterator<Item = T>,
+          I::IntoIter: 'a,
+          F: Fn(&T) -> &str + 'a
+    {
+        items.into_iter().filter(move |item| self.matches(key_fn(item)))
+    }
+
+    /// Items matching the query, see [`Self::matches_item`]
+    pub fn filter_items<'a, T, I>(&'a self, items: I) -> impl Iterator<Item = T> + 'a
+    where I: IntoIterator<Item = T>,
+          I::IntoIter: 'a,
+          T: Searchable
+    {
+        items.into_iter().filter(move |item| self.matches_item(item))
+    }
+
+    fn update_selected(&mut self) {
+        self.search_types[self.type_selected].update(&self.buf, &self.options);
+    }
+
+    /// Draw the search box, option toggles and type selector. Returns true if
+    /// the query, options or search type changed.
+    pub fn draw(&mut self, ui: &Ui) -> bool {
+        self.changed = false;
         let region = ui.content_region_avail();
-        ui.set_next_item_width(region[0] * 0.6);
+        let toggles_width = (ui.frame_height() + ui.clone_style().item_spacing[0]) * 3.;
+        ui.set_next_item_width(region[0] * 0.6 - toggles_width);
         let label_fmt = match self.show_label {
             true => self.label.clone(),
             false => format!("##{}", self.label)
         };
-        ui.input_text(&label_fmt, &mut self.buf).build();
+        let error = self.get_search_type().get_error().map(|e| e.to_string());
+        let style = error.as_ref().map(|_| (
+            ui.push_style_color(StyleColor::Border, [1., 0.2, 0.2, 1.]),
+            ui.push_style_var(StyleVar::FrameBorderSize(1.))
+        ));
+        if std::mem::take(&mut self.popup.refocus) {
+            ui.set_keyboard_focus_here();
+        }
+        let edited = ui.input_text(&label_fmt, &mut self.buf).build();
+        self.changed |= edited;
+        drop(style);
+        if let Some(error) = error && ui.is_item_hovered() && !self.popup.open {
+            ui.tooltip_text(error);
+        }
+        let input_rect = (ui.item_rect_min(), ui.item_rect_size());
+        self.handle_popup_keys(ui, edited);
+        for (label, tooltip, value) in [
+            ("Aa", "Match case", &mut self.options.case_sensitive),
+            ("W", "Match whole words", &mut self.options.whole_word),
+            ("!", "Show what doesn't match", &mut self.options.invert)
+        ] {
+            ui.same_line();
+            self.changed |= toggle_button(ui, label, tooltip, value);
+        }
         ui.same_line_with_spacing(0., 10.);
         ui.set_next_item_width(region[0] * 0.25);
         if let Some(_) = ui.begin_combo_with_flags(
             "Search type", 
-            unsafe { self.type_selected.as_ref().get_name() },
+            self.search_types[self.type_selected].get_name(),
             ComboBoxFlags::empty()
         ) {
-            let sel_idx = self.search_types.iter().position(
-                |p| std::ptr::addr_eq(p, unsafe { self.type_selected.as_ref() })).unwrap();
-            for (i, sel) in self.search_types.iter_mut().enumerate() {
+            let sel_idx = self.type_selected;
+            for (i, sel) in self.search_types.iter().enumerate() {
                 if ui.selectable_config(sel.get_name()).selected(i == sel_idx).build() {
-                    self.type_selected = unsafe { NonNull::new_unchecked(&raw mut *sel) };
+                    self.type_selected = i;
+                    self.changed |= i != sel_idx;
                 }
                 if i == sel_idx { ui.set_item_default_focus(); }
             }
         }
+        if self.changed {
+            self.update_selected();
+        }
+        self.draw_popup(ui, input_rect);
+        self.changed
+    }
+
+    /// Word at the end of the query that completions replace, and its byte offset
+    fn completion_word(&self) -> (usize, &str) {
+        let start = self.buf.rfind(|c: char| c.is_whitespace() || matches!(c, '(' | ')')).map_or(0, |i| i + 1);
+        let start = start + self.buf[start..].len() - self.buf[start..].trim_start_matches(['-', '!']).len();
+        (start, &self.buf[start..])
+    }
+
+    fn refresh_suggestions(&mut self) {
+        let suggestions = match &self.completer {
+            Some(completer) => (completer.0)(self.completion_word().1),
+            None => vec![]
+        };
+        let word = self.completion_word().1;
+        self.popup.suggestions = suggestions.into_iter().filter(|s| s != word).collect();
+    }
+
+    /// Suggestions first, then saved filters and recent queries containing the query
+    fn popup_entries(&self) -> Vec<PopupEntry> {
+        let query = fold_case(&self.buf).0;
+        let contains = |text: &str| fold_case(text).0.contains(&query);
+        let suggestions = (0..self.popup.suggestions.len()).map(PopupEntry::Suggestion);
+        let saved = self.history.get_saved().iter().enumerate()
+            .filter(|(_, f)| contains(&f.name) || contains(&f.query))
+            .map(|(i, _)| PopupEntry::Saved(i));
+        let history = self.history.get_entries().iter().enumerate()
+            .filter(|(_, e)| e.query != self.buf && contains(&e.query))
+            .map(|(i, _)| PopupEntry::History(i));
+        suggestions.chain(saved).chain(history).collect()
+    }
+
+    /// The current query, search type and options, for the history
+    fn history_entry(&self) -> HistoryEntry {
+        HistoryEntry {
+            query: self.buf.clone(),
+            search_type: self.get_mode().to_owned(),
+            options: self.options
+        }
+    }
+
+    fn accept_entry(&mut self, entry: PopupEntry) {
+        match entry {
+            PopupEntry::Suggestion(i) => {
+                let start = self.completion_word().0;
+                self.buf.truncate(start);
+                self.buf.push_str(&self.popup.suggestions[i]);
+                self.popup.refocus = true;
+            },
+            PopupEntry::Saved(i) => self.apply_saved_filter(i),
+            PopupEntry::History(i) => {
+                self.apply_history_entry(i);
+                self.history.push(self.history_entry());
+            }
+        }
+        self.changed = true;
+        self.popup.open = false;
+        self.popup.highlighted = None;
+    }
+
+    /// Open the dropdown with the search box, and move through it with the arrow keys.
+    /// Enter picks the highlighted entry, or adds the query to the history.
+    fn handle_popup_keys(&mut self, ui: &Ui, edited: bool) {
+        if ui.is_item_activated() || edited {
+            self.popup.open = true;
+            self.popup.highlighted = None;
+            self.refresh_suggestions();
+        }
+        if ui.is_item_deactivated() {
+            if ui.is_key_pressed(Key::Enter) || ui.is_key_pressed(Key::KeypadEnter) {
+                let entries = self.popup_entries();
+                match self.popup.highlighted.and_then(|i| entries.get(i)) {
+                    Some(entry) if self.popup.open => self.accept_entry(*entry),
+                    _ => self.history.push(self.history_entry())
+                }
+                self.popup.open = false;
+            } else if ui.is_key_pressed(Key::Escape) || !self.popup.hovered {
+                self.popup.open = false;
+            }
+        }
+        if !ui.is_item_active() {
+            return;
+        }
+        let count = self.popup_entries().len();
+        if count == 0 {
+            self.popup.highlighted = None;
+            return;
+        }
+        if ui.is_key_pressed(Key::DownArrow) {
+            self.popup.open = true;
+            self.popup.highlighted = Some(self.popup.highlighted.map_or(0, |i| (i + 1) % count));
+        } else if ui.is_key_pressed(Key::UpArrow) {
+            self.popup.open = true;
+            self.popup.highlighted = Some(self.popup.highlighted.map_or(count - 1, |i| (i + count - 1) % count));
+        }
+    }
+
+    fn draw_popup(&mut self, ui: &Ui, (pos, size): ([f32; 2], [f32; 2])) {
+        let entries = match self.popup.open {
+            true => self.popup_entries(),
+            false => vec![]
+        };
+        if entries.is_empty() {
+            self.popup.hovered = false;
+            return;
+        }
+        let mut accepted = None;
+        let mut removed = None;
+        let mut pinned = None;
+        let flags = WindowFlags::NO_TITLE_BAR | WindowFlags::NO_MOVE | WindowFlags::NO_RESIZE
+            | WindowFlags::NO_SAVED_SETTINGS | WindowFlags::NO_FOCUS_ON_APPEARING | WindowFlags::NO_NAV;
+        ui.window(format!("##{}Popup", self.label))
+            .position([pos[0], pos[1] + size[1]], Condition::Always)
+            .size_constraints([size[0], 0.], [size[0], 300.])
+            .always_auto_resize(true)
+            .flags(flags)
+            .build(|| {
+                // keep the dropdown over the window the search box is in
+                unsafe { imgui::sys::igBringWindowToDisplayFront(imgui::sys::igGetCurrentWindow()); }
+                self.popup.hovered = ui.is_window_hovered();
+                let mut section = None;
+                for (i, entry) in entries.iter().enumerate() {
+                    let (heading, label) = match entry {
+                        PopupEntry::Suggestion(s) => ("Suggestions", self.popup.suggestions[*s].clone()),
+                        PopupEntry::Saved(s) => {
+                            let filter = &self.history.get_saved()[*s];
+                            ("Saved filters", format!("{} ({})", filter.name, filter.query))
+                        },
+                        PopupEntry::History(h) => ("Recent", self.history.get_entries()[*h].query.clone())
+                    };
+                    if section != Some(heading) {
+                        if section.is_some() { ui.separator(); }
+                        ui.text_disabled(heading);
+                        section = Some(heading);
+                    }
+                    let _id = ui.push_id_usize(i);
+                    if ui.selectable_config(&label).selected(self.popup.highlighted == Some(i)).build() {
+                        accepted = Some(*entry);
+                    }
+                    if self.popup.highlighted == Some(i) {
+                        ui.set_scroll_here_y();
+                    }
+                    if !matches!(entry, PopupEntry::Suggestion(_)) && ui.is_item_clicked_with_button(MouseButton::Right) {
+                        ui.open_popup("entryMenu");
+                    }
+                    ui.popup("entryMenu", || {
+                        if let PopupEntry::History(h) = entry && ui.menu_item("Save as filter") {
+                            pinned = Some(*h);
+                        }
+                        if ui.menu_item("Remove") {
+                            removed = Some(*entry);
+                        }
+                    });
+                }
+            });
+        match removed {
+            Some(PopupEntry::Saved(i)) => self.history.remove_saved(i),
+            Some(PopupEntry::History(i)) => self.history.remove(i),
+            _ => ()
+        }
+        if let Some(i) = pinned {
+            // saved with the search type and options the query was searched with
+            let entry = self.history.get_entries()[i].clone();
+            self.history.add_saved(SavedFilter {
+                name: entry.query.clone(),
+                query: entry.query,
+                search_type: entry.search_type,
+                options: entry.options
+            });
+        }
+        if let Some(entry) = accepted {
+            self.accept_entry(entry);
+            if self.changed {
+                self.update_selected();
+            }
+        }
+    }
+}
+
+/// Button that stays highlighted while `value` is set
+fn toggle_button(ui: &Ui, label: &str, tooltip: &str, value: &mut bool) -> bool {
+    let color = value.then(|| ui.push_style_color(StyleColor::Button, ui.style_color(StyleColor::ButtonActive)));
+    let size = ui.frame_height();
+    let clicked = ui.button_with_size(label, [size, size]);
+    drop(color);
+    if ui.is_item_hovered() {
+        ui.tooltip_text(tooltip);
     }
-}
\ No newline at end of file
+    if clicked {
+        *value = !*value;
+    }
+    clicked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn searchbar(mode: &str, query: &str, options: SearchOptions) -> Searchbar {
+        let mut searchbar = Searchbar::new("search", false);
+        assert!(searchbar.set_mode(mode));
+        searchbar.set_options(options);
+        searchbar.set_query(query);
+        searchbar
+    }
+
+    fn searchbar_with_query(mut searchbar: Searchbar, query: &str) -> Searchbar {
+        searchbar.set_query(query);
+        searchbar
+    }
+
+    #[test]
+    fn fold_case_expands_and_keeps_source_offsets() {
+        assert_eq!(fold_case("Straße"), ("strasse".to_owned(), vec![0, 1, 2, 3, 4, 4, 6]));
+        assert_eq!(fold_case("ẞx"), ("ssx".to_owned(), vec![0, 0, 3]));
+        assert_eq!(fold_case("ΟΔΟΣ").0, fold_case("οδος").0);
+        // one character lowercased into two
+        assert_eq!(fold_case("İa"), ("i\u{307}a".to_owned(), vec![0, 0, 0, 2]));
+    }
+
+    #[test]
+    fn substring_ranges_cover_folded_characters() {
+        let options = SearchOptions::default();
+        assert_eq!(find_substring("Straße!", "SS", &options), Some(4..6));
+        assert_eq!(find_substring("Straße", "asse", &options), Some(3..7));
+        // matching half of a folded character still covers all of it
+        assert_eq!(find_substring("Straße", "as", &options), Some(3..6));
+        assert_eq!(find_substring("İstanbul", "stan", &options), Some(2..6));
+        assert_eq!(find_substring("Straße", "SS", &SearchOptions { case_sensitive: true, ..options }), None);
+        let whole_word = SearchOptions { whole_word: true, ..options };
+        assert_eq!(find_substring("foo_bar foo", "FOO", &whole_word), Some(8..11));
+        assert_eq!(find_substring("foo_bar", "foo", &whole_word), None);
+    }
+
+    #[test]
+    fn word_boundaries() {
+        assert!(is_whole_word("foo bar", &(0..3)));
+        assert!(is_whole_word("x-foo.", &(2..5)));
+        assert!(is_whole_word("", &(0..0)));
+        assert!(!is_whole_word("foobar", &(0..3)));
+        assert!(!is_whole_word("a_foo", &(2..5)));
+        assert!(!is_whole_word("éfoo", &(2..5)));
+        assert!(!is_whole_word("foo9", &(0..3)));
+    }
+
+    #[test]
+    fn invert_applies_to_every_match() {
+        let inverted = SearchOptions { invert: true, ..Default::default() };
+        let searchbar = searchbar("Contains", "abc", inverted);
+        assert!(!searchbar.matches("xABCx"));
+        assert!(searchbar.matches("xyz"));
+        assert!(!searchbar.matches_item("abc"));
+        assert_eq!(searchbar.search_match("abc"), None);
+        assert_eq!(searchbar.search_match("xyz"), Some(SearchMatch::default()));
+        // an empty query still matches everything
+        let searchbar = searchbar_with_query(searchbar, "");
+        assert!(searchbar.matches("abc"));
+        assert_eq!(searchbar.search_match("abc"), Some(SearchMatch::default()));
+    }
+
+    #[test]
+    fn every_mode_folds_case_the_same_way() {
+        // regexes use the regex crate's case folding instead
+        for mode in ["Contains", "Whole Word", "Fuzzy", "Glob", "Path Glob", "Query"] {
+            let upper = searchbar(mode, "STRASSE", SearchOptions::default());
+            assert_eq!(upper.search_match("straße").map(|m| m.indices), Some(vec![0, 1, 2, 3, 4, 6]), "{}", mode);
+            assert!(searchbar_with_query(upper.clone(), "straße").matches("STRASSE"), "{}", mode);
+            assert!(searchbar_with_query(upper, "ΟΔΟΣ").matches("οδος"), "{}", mode);
+            let case_sensitive = searchbar(mode, "STRASSE", SearchOptions { case_sensitive: true, ..Default::default() });
+            assert!(!case_sensitive.matches("straße"), "{}", mode);
+        }
+    }
+}
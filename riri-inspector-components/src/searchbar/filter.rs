@@ -0,0 +1,426 @@
+//! Filtering for lists too large to search every frame, such as every string
+//! in a game's message tables.
+//!
+//! [`FilterEngine`] keeps the indices of the matching items and only searches
+//! again when the query, search type or options change. When the query is
+//! extended and the search type [narrows](super::SearchType::narrows), only
+//! the previous results are searched. Large lists are searched on a worker
+//! thread, and results are added as they're found.
+
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{ AtomicBool, Ordering },
+        mpsc::{ self, Receiver, TryRecvError },
+        Arc
+    },
+    thread
+};
+use imgui::{ ProgressBar, Ui };
+use super::{ item_matches, query::Searchable, SearchOptions, SearchType, Searchbar };
+
+/// List of items searched by a [`FilterEngine`]
+pub trait FilterSource : Send + Sync {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool { self.len() == 0 }
+    fn get(&self, index: usize) -> &dyn Searchable;
+}
+
+impl<T: Searchable + Send + Sync> FilterSource for Vec<T> {
+    fn len(&self) -> usize { self.len() }
+    fn get(&self, index: usize) -> &dyn Searchable { &self[index] }
+}
+
+impl<T: Searchable + Send + Sync> FilterSource for Box<[T]> {
+    fn len(&self) -> usize { self.as_ref().len() }
+    fn get(&self, index: usize) -> &dyn Searchable { &self[index] }
+}
+
+/// What the current results were searched with
+#[derive(Debug, Clone, PartialEq)]
+struct FilterKey {
+    query: String,
+    search_type: String,
+    options: SearchOptions
+}
+
+/// Copy of the search state that can be moved to a worker thread
+#[derive(Debug)]
+struct Matcher {
+    search_type: Box<dyn SearchType>,
+    query: String,
+    options: SearchOptions
+}
+
+impl Matcher {
+    fn matches(&self, item: &dyn Searchable) -> bool {
+        item_matches(self.search_type.as_ref(), &self.query, &self.options, item) != self.options.invert
+    }
+}
+
+/// Indices to search
+enum Candidates {
+    All(usize),
+    /// Results of a query the new one narrows
+    Subset(Vec<usize>)
+}
+
+impl Candidates {
+    fn len(&self) -> usize {
+        match self {
+            Self::All(len) => *len,
+            Self::Subset(indices) => indices.len()
+        }
+    }
+
+    /// Matching indices among candidates `start..end`
+    fn scan(&self, source: &dyn FilterSource, matcher: &Matcher, start: usize, end: usize) -> Vec<usize> {
+        match self {
+            Self::All(_) => (start..end).filter(|i| matcher.matches(source.get(*i))).collect(),
+            Self::Subset(indices) => indices[start..end].iter().copied()
+                .filter(|i| matcher.matches(source.get(*i))).collect()
+        }
+    }
+}
+
+/// Results found by the worker thread since the last chunk
+struct Chunk {
+    indices: Vec<usize>,
+    /// Candidates searched so far
+    processed: usize
+}
+
+struct FilterJob {
+    receiver: Receiver<Chunk>,
+    cancel: Arc<AtomicBool>,
+    total: usize,
+    processed: usize
+}
+
+impl Drop for FilterJob {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Caches the indices of the items in a [`FilterSource`] matching a search
+pub struct FilterEngine {
+    source: Arc<dyn FilterSource>,
+    /// Matching indices in ascending order
+    results: Vec<usize>,
+    key: Option<FilterKey>,
+    /// Whether every candidate has been searched for `key`
+    complete: bool,
+    job: Option<FilterJob>,
+    background_threshold: usize,
+    chunk_size: usize
+}
+
+impl Debug for FilterEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FilterEngine")
+            .field("len", &self.source.len())
+            .field("results", &self.results.len())
+            .field("key", &self.key)
+            .field("complete", &self.complete)
+            .field("running", &self.is_running())
+            .finish()
+    }
+}
+
+impl FilterEngine {
+    pub fn new<T: FilterSource + 'static>(source: Arc<T>) -> Self {
+        Self {
+            source,
+            results: vec![],
+            key: None,
+            complete: false,
+            job: None,
+            background_threshold: 50_000,
+            chunk_size: 16_384
+        }
+    }
+
+    /// Searches over at least this many items run on a worker thread. 50000 by default.
+    pub fn set_background_threshold(mut self, threshold: usize) -> Self {
+        self.background_threshold = threshold;
+        self
+    }
+
+    /// Items the worker thread searches between sending results. 16384 by default.
+    pub fn set_chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    /// Search a different list from the next update
+    pub fn set_source<T: FilterSource + 'static>(&mut self, source: Arc<T>) {
+        self.source = source;
+        self.invalidate();
+    }
+
+    /// Search everything again on the next update, such as after the items changed
+    pub fn invalidate(&mut self) {
+        self.job = None;
+        self.key = None;
+        self.complete = false;
+    }
+
+    /// Indices of the matching items, in ascending order. Grows while a search is running.
+    pub fn get_results(&self) -> &[usize] { &self.results }
+    pub fn is_running(&self) -> bool { self.job.is_some() }
+
+    /// Fraction of the items searched, 1 when no search is running
+    pub fn get_progress(&self) -> f32 {
+        match &self.job {
+            Some(job) if job.total > 0 => job.processed as f32 / job.total as f32,
+            _ => 1.
+        }
+    }
+
+    /// Start a new search if the search bar's query, type or options changed,
+    /// and collect results from the worker thread. Call once per frame.
+    /// Returns true if the results changed.
+    pub fn update(&mut self, searchbar: &Searchbar) -> bool {
+        self.update_with(searchbar.get_search_type(), searchbar.get_query(), &searchbar.get_options())
+    }
+
+    /// Like [`Self::update`], without a search bar. `search_type` must already
+    /// have been updated with `query` and `options`.
+    pub fn update_with(&mut self, search_type: &dyn SearchType, query: &str, options: &SearchOptions) -> bool {
+        let key = FilterKey {
+            query: query.to_owned(),
+            search_type: search_type.get_name().to_owned(),
+            options: *options
+        };
+        let restarted = self.key.as_ref() != Some(&key);
+        if restarted {
+            self.restart(search_type, key);
+        }
+        self.poll() || restarted
+    }
+
+    /// Block until the running search finishes
+    pub fn wait(&mut self) {
+        while let Some(job) = self.job.as_mut() {
+            match job.receiver.recv() {
+                Ok(chunk) => self.receive(chunk),
+                Err(_) => self.job = None
+            }
+        }
+    }
+
+    /// Progress bar with the number of results, while a search is running
+    pub fn draw_progress(&self, ui: &Ui) {
+        if self.is_running() {
+            ProgressBar::new(self.get_progress())
+                .overlay_text(format!("Searching... {} found", self.results.len()))
+                .build(ui);
+        }
+    }
+
+    fn restart(&mut self, search_type: &dyn SearchType, key: FilterKey) {
+        let narrowing = self.complete && !key.options.invert && self.key.as_ref().is_some_and(|old|
+            old.search_type == key.search_type
+                && old.options == key.options
+                && !old.query.is_empty()
+                && search_type.narrows(&old.query, &key.query, &key.options)
+        );
+        self.job = None;
+        self.complete = false;
+        let previous = std::mem::take(&mut self.results);
+        let empty = key.query.is_empty();
+        self.key = Some(key.clone());
+        if empty {
+            self.results = (0..self.source.len()).collect();
+            self.complete = true;
+            return;
+        }
+        let candidates = match narrowing {
+            true => Candidates::Subset(previous),
+            false => Candidates::All(self.source.len())
+        };
+        let matcher = Matcher {
+            search_type: search_type.clone_box(),
+            query: key.query,
+            options: key.options
+        };
+        let total = candidates.len();
+        if total < self.background_threshold.max(1) {
+            self.results = candidates.scan(self.source.as_ref(), &matcher, 0, total);
+            self.complete = true;
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let source = self.source.clone();
+        let chunk_size = self.chunk_size;
+        let cancelled = cancel.clone();
+        thread::spawn(move || {
+            for start in (0..total).step_by(chunk_size) {
+                if cancelled.load(Ordering::Relaxed) {
+                    return;
+                }
+                let end = (start + chunk_size).min(total);
+                let indices = candidates.scan(source.as_ref(), &matcher, start, end);
+                if sender.send(Chunk { indices, processed: end }).is_err() {
+                    return;
+                }
+            }
+        });
+        self.job = Some(FilterJob { receiver, cancel, total, processed: 0 });
+    }
+
+    fn receive(&mut self, chunk: Chunk) {
+        self.results.extend(chunk.indices);
+        if let Some(job) = self.job.as_mut() {
+            job.processed = chunk.processed;
+            if job.processed >= job.total {
+                self.job = None;
+                self.complete = true;
+            }
+        }
+    }
+
+    fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Some(job) = self.job.as_mut() {
+            match job.receiver.try_recv() {
+                Ok(chunk) => {
+                    changed |= !chunk.indices.is_empty();
+                    self.receive(chunk);
+                },
+                Err(TryRecvError::Empty) => break,
+                // the worker stopped without finishing, so the results are incomplete
+                Err(TryRecvError::Disconnected) => self.job = None
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use super::*;
+    use crate::searchbar::{ fuzzy::SearchFuzzy, SearchContaining };
+
+    /// Items that count how many times they've been searched
+    struct Counted {
+        items: Vec<String>,
+        searched: AtomicUsize
+    }
+
+    impl FilterSource for Counted {
+        fn len(&self) -> usize { self.items.len() }
+        fn get(&self, index: usize) -> &dyn Searchable {
+            self.searched.fetch_add(1, Ordering::Relaxed);
+            &self.items[index]
+        }
+    }
+
+    fn items() -> Vec<String> {
+        let words = ["alpha", "beta", "gamma", "alphabet", "Alpaca", "delta"];
+        (0..600).map(|i| format!("{}_{}", words[i % words.len()], i)).collect()
+    }
+
+    fn counted() -> Arc<Counted> {
+        Arc::new(Counted { items: items(), searched: AtomicUsize::new(0) })
+    }
+
+    /// Results of searching every item from scratch
+    fn rescan(search_type: &dyn SearchType, query: &str, options: &SearchOptions) -> Vec<usize> {
+        let mut engine = FilterEngine::new(Arc::new(items()));
+        engine.update_with(search_type, query, options);
+        engine.get_results().to_vec()
+    }
+
+    /// Update `engine`, returning how many items it searched
+    fn searched(engine: &mut FilterEngine, source: &Counted, search_type: &dyn SearchType, query: &str, options: &SearchOptions) -> usize {
+        source.searched.store(0, Ordering::Relaxed);
+        engine.update_with(search_type, query, options);
+        source.searched.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn narrowing_matches_a_full_rescan() {
+        let options = SearchOptions::default();
+        for search_type in [&SearchContaining as &dyn SearchType, &SearchFuzzy] {
+            let source = counted();
+            let mut engine = FilterEngine::new(source.clone());
+            assert_eq!(searched(&mut engine, &source, search_type, "al", &options), source.len());
+            for query in ["alp", "alph", "alpha_1"] {
+                let previous = engine.get_results().len();
+                assert_eq!(searched(&mut engine, &source, search_type, query, &options), previous,
+                    "{} didn't narrow to {}", search_type.get_name(), query);
+                assert_eq!(engine.get_results(), rescan(search_type, query, &options), "{}", query);
+            }
+        }
+    }
+
+    #[test]
+    fn changes_that_widen_rescan() {
+        let source = counted();
+        let mut engine = FilterEngine::new(source.clone());
+        let options = SearchOptions::default();
+        let case_sensitive = SearchOptions { case_sensitive: true, ..options };
+        let inverted = SearchOptions { invert: true, ..options };
+        searched(&mut engine, &source, &SearchContaining, "alp", &options);
+        let steps: [(&dyn SearchType, &str, &SearchOptions); 5] = [
+            (&SearchContaining, "al", &options),
+            (&SearchContaining, "alp", &case_sensitive),
+            (&SearchFuzzy, "alpa", &case_sensitive),
+            (&SearchFuzzy, "alpac", &inverted),
+            (&SearchFuzzy, "alpaca", &inverted)
+        ];
+        for (search_type, query, options) in steps {
+            assert_eq!(searched(&mut engine, &source, search_type, query, options), source.len(), "{}", query);
+            assert_eq!(engine.get_results(), rescan(search_type, query, options), "{}", query);
+        }
+        // an unchanged search doesn't search again
+        assert_eq!(searched(&mut engine, &source, &SearchFuzzy, "alpaca", &inverted), 0);
+        engine.invalidate();
+        assert_eq!(searched(&mut engine, &source, &SearchFuzzy, "alpaca", &inverted), source.len());
+    }
+
+    #[test]
+    fn empty_query_matches_everything_without_searching() {
+        let source = counted();
+        let mut engine = FilterEngine::new(source.clone());
+        assert_eq!(searched(&mut engine, &source, &SearchContaining, "", &SearchOptions::default()), 0);
+        assert_eq!(engine.get_results().len(), source.len());
+    }
+
+    #[test]
+    fn background_search_matches_a_full_rescan() {
+        let options = SearchOptions::default();
+        let mut engine = FilterEngine::new(Arc::new(items())).set_background_threshold(1).set_chunk_size(7);
+        for query in ["a", "al", "alpha"] {
+            engine.update_with(&SearchContaining, query, &options);
+            engine.wait();
+            assert!(!engine.is_running());
+            assert_eq!(engine.get_progress(), 1.);
+            assert_eq!(engine.get_results(), rescan(&SearchContaining, query, &options), "{}", query);
+        }
+    }
+
+    #[test]
+    fn restarting_cancels_the_running_search() {
+        let options = SearchOptions::default();
+        let mut engine = FilterEngine::new(Arc::new(items())).set_background_threshold(1).set_chunk_size(1);
+        engine.update_with(&SearchContaining, "beta", &options);
+        let cancel = engine.job.as_ref().unwrap().cancel.clone();
+        engine.update_with(&SearchContaining, "gamma", &options);
+        assert!(cancel.load(Ordering::Relaxed));
+        // nothing found by the cancelled search is kept
+        engine.wait();
+        assert_eq!(engine.get_results(), rescan(&SearchContaining, "gamma", &options));
+
+        engine.update_with(&SearchContaining, "delta", &options);
+        let cancel = engine.job.as_ref().unwrap().cancel.clone();
+        engine.invalidate();
+        assert!(cancel.load(Ordering::Relaxed));
+        assert!(!engine.is_running());
+        assert_eq!(engine.get_progress(), 1.);
+    }
+}
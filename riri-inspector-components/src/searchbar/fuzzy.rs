@@ -0,0 +1,214 @@
+//! Subsequence matching ranked like fzf and Sublime Text: every character of
+//! the query has to appear in order, and matches score higher when they start
+//! words, follow camelCase boundaries or run together.
+
+use super::{ fold_case, SearchMatch, SearchOptions, SearchType };
+
+const SCORE_MATCH: i32 = 16;
+/// Match on the first character, or after a separator such as `_`, `-`, `/` or a space
+const BONUS_BOUNDARY: i32 = 10;
+/// Uppercase letter after a lowercase one, or a digit after a letter
+const BONUS_CAMEL: i32 = 8;
+/// Match right after the previous one
+const BONUS_CONSECUTIVE: i32 = 6;
+const PENALTY_GAP_START: i32 = 3;
+const PENALTY_GAP_EXTENSION: i32 = 1;
+/// Unmatched characters before the first match cost this much each, up to `PENALTY_LEADING_MAX`
+const PENALTY_LEADING: i32 = 1;
+const PENALTY_LEADING_MAX: i32 = 5;
+const NONE: i32 = i32::MIN / 2;
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchFuzzy;
+impl SearchType for SearchFuzzy {
+    fn get_name(&self) -> &str { "Fuzzy" }
+    fn update(&mut self, _new: &str, _options: &SearchOptions) {}
+    fn search_matches(&self, src: &str, tgt: &str, options: &SearchOptions) -> bool {
+        if options.whole_word {
+            return fuzzy_match(src, tgt, options).is_some();
+        }
+        let mut src = candidate_chars(src, options).into_iter().map(|(_, c, _)| c);
+        fold_query(tgt, options).into_iter().all(|q| src.any(|c| c == q))
+    }
+    fn search_match(&self, src: &str, tgt: &str, options: &SearchOptions) -> Option<SearchMatch> {
+        fuzzy_match(src, tgt, options)
+    }
+    /// Extending the query keeps its first character, which whole word matches start on
+    fn narrows(&self, old: &str, new: &str, _options: &SearchOptions) -> bool { new.starts_with(old) }
+}
+impl SearchFuzzy {
+    pub fn new() -> Self { Self }
+}
+
+/// Characters of `src` as they're compared with the query: the byte offset
+/// of the source character, the character after case folding, and the source
+/// character. Folding can turn one character into several, such as `ß` into `ss`.
+fn candidate_chars(src: &str, options: &SearchOptions) -> Vec<(usize, char, char)> {
+    if options.case_sensitive {
+        return src.char_indices().map(|(i, c)| (i, c, c)).collect();
+    }
+    let (folded, offsets) = fold_case(src);
+    folded.char_indices().map(|(i, c)| {
+        let at = offsets[i];
+        (at, c, src[at..].chars().next().unwrap_or(c))
+    }).collect()
+}
+
+fn fold_query(tgt: &str, options: &SearchOptions) -> Vec<char> {
+    match options.case_sensitive {
+        true => tgt.chars().collect(),
+        false => fold_case(tgt).0.chars().collect()
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '_' | '-' | '/' | '\\' | '.' | ':' | ',' | '(' | ')' | '[' | ']')
+}
+
+/// Bonus for a match on `c`, given the character before it
+fn position_bonus(prev: Option<char>, c: char) -> i32 {
+    match prev {
+        None => BONUS_BOUNDARY,
+        Some(p) if is_separator(p) && !is_separator(c) => BONUS_BOUNDARY,
+        Some(p) if (p.is_lowercase() && c.is_uppercase()) || (p.is_alphabetic() && c.is_numeric()) => BONUS_CAMEL,
+        _ => 0
+    }
+}
+
+/// Best scoring alignment of `tgt` as a subsequence of `src`. With
+/// [`SearchOptions::whole_word`] set, the first matched character has to start a word.
+pub fn fuzzy_match(src: &str, tgt: &str, options: &SearchOptions) -> Option<SearchMatch> {
+    let query = fold_query(tgt, options);
+    if query.is_empty() {
+        return Some(SearchMatch::default());
+    }
+    let chars = candidate_chars(src, options);
+    let (n, m) = (chars.len(), query.len());
+    let unranked = SearchOptions { whole_word: false, ..*options };
+    if n < m || !SearchFuzzy.search_matches(src, tgt, &unranked) {
+        return None;
+    }
+    // bonuses look at the source characters, as folding loses their case
+    let bonus: Vec<i32> = (0..n).map(|i| position_bonus(i.checked_sub(1).map(|p| chars[p].2), chars[i].2)).collect();
+    // score[j * n + i]: best score for query[..=j] with query[j] matched at chars[i],
+    // and the index query[j - 1] was matched at for that score
+    let mut score = vec![NONE; n * m];
+    let mut from = vec![usize::MAX; n * m];
+    for (j, q) in query.iter().enumerate() {
+        // best score of an earlier row match followed by a gap, carried along the row
+        let mut gap_best = NONE;
+        let mut gap_from = usize::MAX;
+        for i in j..n {
+            if j > 0 && i >= 2 {
+                gap_best -= PENALTY_GAP_EXTENSION;
+                let candidate = score[(j - 1) * n + i - 2];
+                if candidate > NONE && candidate - PENALTY_GAP_START > gap_best {
+                    gap_best = candidate - PENALTY_GAP_START;
+                    gap_from = i - 2;
+                }
+            }
+            if chars[i].1 != *q {
+                continue;
+            }
+            if j == 0 && options.whole_word && i > 0 && (chars[i - 1].2.is_alphanumeric() || chars[i - 1].2 == '_') {
+                continue;
+            }
+            let base = SCORE_MATCH + bonus[i];
+            let (best, prev) = if j == 0 {
+                (base - (i as i32 * PENALTY_LEADING).min(PENALTY_LEADING_MAX), usize::MAX)
+            } else {
+                let consecutive = if i > 0 { score[(j - 1) * n + i - 1] } else { NONE };
+                let consecutive = if consecutive > NONE { consecutive + BONUS_CONSECUTIVE } else { NONE };
+                if consecutive >= gap_best { (consecutive, i.wrapping_sub(1)) } else { (gap_best, gap_from) }
+            };
+            if j > 0 && best <= NONE {
+                continue;
+            }
+            score[j * n + i] = if j == 0 { best } else { best + base };
+            from[j * n + i] = prev;
+        }
+    }
+    let last = (m - 1) * n;
+    let (end, best) = (0..n).map(|i| (i, score[last + i])).filter(|(_, s)| *s > NONE).max_by_key(|(_, s)| *s)?;
+    let mut indices = vec![0; m];
+    let mut i = end;
+    for j in (0..m).rev() {
+        indices[j] = chars[i].0;
+        i = from[j * n + i];
+    }
+    // characters folded into several can be matched more than once
+    indices.dedup();
+    Some(SearchMatch { score: best, indices })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indices(src: &str, tgt: &str) -> Option<Vec<usize>> {
+        fuzzy_match(src, tgt, &SearchOptions::default()).map(|m| m.indices)
+    }
+
+    fn score(src: &str, tgt: &str) -> i32 {
+        fuzzy_match(src, tgt, &SearchOptions::default()).unwrap().score
+    }
+
+    #[test]
+    fn query_has_to_appear_in_order() {
+        assert_eq!(indices("abc", "ac"), Some(vec![0, 2]));
+        assert_eq!(indices("abc", "ca"), None);
+        assert_eq!(indices("ab", "abc"), None);
+        assert_eq!(fuzzy_match("abc", "", &SearchOptions::default()), Some(SearchMatch::default()));
+    }
+
+    #[test]
+    fn positions_prefer_word_starts() {
+        assert_eq!(indices("FooBarBaz", "fbb"), Some(vec![0, 3, 6]));
+        assert_eq!(indices("file_name", "fn"), Some(vec![0, 5]));
+        assert_eq!(indices("abab_cd", "ac"), Some(vec![0, 5]));
+        assert_eq!(indices("xbar_bar", "bar"), Some(vec![5, 6, 7]));
+    }
+
+    #[test]
+    fn positions_prefer_consecutive_matches() {
+        assert_eq!(indices("a_b_abc", "abc"), Some(vec![4, 5, 6]));
+        assert_eq!(indices("xaxbcxabc", "abc"), Some(vec![6, 7, 8]));
+    }
+
+    #[test]
+    fn positions_are_byte_offsets() {
+        assert_eq!(indices("éclair", "cl"), Some(vec![2, 3]));
+        assert_eq!(indices("日本語", "語"), Some(vec![6]));
+    }
+
+    #[test]
+    fn ranking() {
+        assert!(score("main.rs", "main") > score("domain.rs", "main"));
+        assert!(score("src/main.rs", "main") > score("src/domain.rs", "main"));
+        assert!(score("get_value", "gv") > score("giveaway", "gv"));
+        assert!(score("SetValue", "sv") > score("saveview", "sv"));
+        assert!(score("abc", "abc") > score("axbxc", "abc"));
+        // unmatched characters at the start cost a little, up to a limit
+        assert!(score("abc", "abc") > score("xabc", "abc"));
+        assert_eq!(score("xxxxxx_abc", "abc"), score("xxxxxxxxxx_abc", "abc"));
+    }
+
+    #[test]
+    fn options() {
+        let case_sensitive = SearchOptions { case_sensitive: true, ..Default::default() };
+        assert!(fuzzy_match("FooBar", "fb", &case_sensitive).is_none());
+        assert_eq!(fuzzy_match("FooBar", "FB", &case_sensitive).map(|m| m.indices), Some(vec![0, 3]));
+        let whole_word = SearchOptions { whole_word: true, ..Default::default() };
+        assert_eq!(fuzzy_match("xfoo foo", "fo", &whole_word).map(|m| m.indices), Some(vec![5, 6]));
+        assert!(fuzzy_match("xfoo", "fo", &whole_word).is_none());
+        assert!(SearchFuzzy.search_matches("xfoo bar", "fb", &SearchOptions::default()));
+        assert!(!SearchFuzzy.search_matches("xfoo bar", "fb", &whole_word));
+    }
+
+    #[test]
+    fn extending_the_query_narrows() {
+        let options = SearchOptions::default();
+        assert!(SearchFuzzy.narrows("ab", "abc", &options));
+        assert!(!SearchFuzzy.narrows("ab", "xab", &options));
+    }
+}
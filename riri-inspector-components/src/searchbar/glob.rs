@@ -0,0 +1,389 @@
+//! Wildcard patterns for filtering file and asset paths.
+//!
+//! - `*` matches any characters, `?` any one character
+//! - `[abc]` matches one of the listed characters, `[a-z]` one in the range,
+//!   and `[!abc]` or `[^abc]` one that isn't listed. Wildcards lose their
+//!   meaning inside brackets, so `[*]` matches a `*`.
+//! - `**` matches any characters, including path separators
+//!
+//! The whole candidate has to match, except that a pattern without any
+//! wildcards matches anywhere in it, like `*pattern*`. With
+//! [`SearchOptions::whole_word`] such a pattern only matches whole words.
+//! Patterns with wildcards already match the whole candidate, so the option
+//! doesn't change them.
+//!
+//! Path patterns treat `/` and `\` as separators: `*`, `?` and brackets don't
+//! match them, `**/` matches any number of whole directories (including none,
+//! so `a/**/b` matches `a/b`), and a `/` in the pattern matches either
+//! separator. A path pattern without separators or `**` is matched against
+//! the file name only.
+
+use std::{
+    error::Error,
+    fmt::Display
+};
+use super::{ fold_case, is_whole_word, SearchMatch, SearchOptions, SearchType };
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Literal(char),
+    /// `?`
+    Any,
+    /// `*`
+    Star,
+    /// `**`
+    GlobStar,
+    /// `**/` in a path pattern: nothing, or anything ending with a separator
+    GlobStarDir,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>
+    }
+}
+
+/// Problem compiling a glob pattern, at a byte offset into it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobError {
+    position: usize
+}
+
+impl GlobError {
+    pub fn get_position(&self) -> usize { self.position }
+}
+
+impl Display for GlobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unclosed '[' at position {}", self.position)
+    }
+}
+
+impl Error for GlobError {}
+
+fn is_separator(c: char) -> bool { c == '/' || c == '\\' }
+
+/// Compiled glob pattern
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Glob {
+    tokens: Vec<Token>,
+    path_aware: bool,
+    /// Path pattern without separators or `**`, matched against the file name
+    file_name_only: bool,
+    /// Pattern without wildcards, wrapped in `**` to match anywhere
+    anywhere: bool,
+    /// The pattern after case folding, matched against folded candidates for
+    /// case insensitive searches. `None` if folding doesn't change it.
+    folded: Option<Box<Glob>>
+}
+
+impl Glob {
+    pub fn new(pattern: &str, path_aware: bool) -> Result<Self, GlobError> {
+        let mut glob = Self::compile(pattern, path_aware)?;
+        let (folded, _) = fold_case(pattern);
+        if folded != pattern {
+            glob.folded = Some(Box::new(Self::compile(&folded, path_aware)?));
+        }
+        Ok(glob)
+    }
+
+    fn compile(pattern: &str, path_aware: bool) -> Result<Self, GlobError> {
+        let mut tokens = vec![];
+        let mut chars = pattern.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let token = match c {
+                '?' => Token::Any,
+                '*' if chars.next_if(|(_, c)| *c == '*').is_some() => {
+                    while chars.next_if(|(_, c)| *c == '*').is_some() {}
+                    match path_aware && chars.next_if(|(_, c)| is_separator(*c)).is_some() {
+                        true => Token::GlobStarDir,
+                        false => Token::GlobStar
+                    }
+                },
+                '*' => Token::Star,
+                '[' => {
+                    let negated = chars.next_if(|(_, c)| *c == '!' || *c == '^').is_some();
+                    let mut ranges = vec![];
+                    loop {
+                        match chars.next() {
+                            Some((_, ']')) if !ranges.is_empty() => break,
+                            Some((_, start)) => {
+                                let range_end = chars.peek().is_some_and(|(_, c)| *c == '-')
+                                    .then(|| chars.clone().nth(1)).flatten()
+                                    .filter(|(_, end)| *end != ']');
+                                match range_end {
+                                    Some((_, end)) => {
+                                        chars.nth(1);
+                                        ranges.push((start, end));
+                                    },
+                                    None => ranges.push((start, start))
+                                }
+                            },
+                            None => return Err(GlobError { position: i })
+                        }
+                    }
+                    Token::Class { negated, ranges }
+                },
+                c => Token::Literal(c)
+            };
+            tokens.push(token);
+        }
+        let file_name_only = path_aware && !tokens.iter().any(|t| match t {
+            Token::Literal(c) => is_separator(*c),
+            Token::GlobStar | Token::GlobStarDir => true,
+            _ => false
+        });
+        let anywhere = tokens.iter().all(|t| matches!(t, Token::Literal(_))) && !tokens.is_empty();
+        if anywhere {
+            tokens.insert(0, Token::GlobStar);
+            tokens.push(Token::GlobStar);
+        }
+        Ok(Self { tokens, path_aware, file_name_only, anywhere, folded: None })
+    }
+
+    pub fn is_path_aware(&self) -> bool { self.path_aware }
+
+    /// Byte offsets of the characters in `src` matched by literals, `?` and
+    /// brackets, or `None` if `src` doesn't match
+    pub fn find(&self, src: &str, options: &SearchOptions) -> Option<Vec<usize>> {
+        if options.case_sensitive {
+            return self.find_exact(src, options.whole_word);
+        }
+        let (folded, offsets) = fold_case(src);
+        let glob = self.folded.as_deref().unwrap_or(self);
+        let mut indices: Vec<usize> = glob.find_exact(&folded, options.whole_word)?
+            .into_iter().map(|i| offsets[i]).collect();
+        // characters folded into several can be matched more than once
+        indices.dedup();
+        Some(indices)
+    }
+
+    pub fn matches(&self, src: &str, options: &SearchOptions) -> bool {
+        self.find(src, options).is_some()
+    }
+
+    /// Like [`Self::find`], comparing characters exactly
+    fn find_exact(&self, src: &str, whole_word: bool) -> Option<Vec<usize>> {
+        let start = match self.file_name_only {
+            true => src.rfind(is_separator).map_or(0, |i| i + 1),
+            false => 0
+        };
+        let chars: Vec<(usize, char)> = src[start..].char_indices().map(|(i, c)| (start + i, c)).collect();
+        if self.anywhere && whole_word {
+            return self.find_whole_word(src, &chars);
+        }
+        let mut failed = vec![false; (self.tokens.len() + 1) * (chars.len() + 1)];
+        let mut indices = vec![];
+        self.match_at(0, 0, &chars, &mut failed, &mut indices).then_some(indices)
+    }
+
+    /// First occurrence of a pattern without wildcards in `chars` that starts
+    /// and ends on word boundaries in `src`
+    fn find_whole_word(&self, src: &str, chars: &[(usize, char)]) -> Option<Vec<usize>> {
+        let literals = &self.tokens[1..self.tokens.len() - 1];
+        (0..=chars.len().saturating_sub(literals.len())).find_map(|ci| {
+            let run = chars.get(ci..ci + literals.len())?;
+            let (first, (last, last_char)) = (run.first()?.0, *run.last()?);
+            let found = literals.iter().zip(run).all(|(t, (_, c))| self.matches_char(t, *c))
+                && is_whole_word(src, &(first..last + last_char.len_utf8()));
+            found.then(|| run.iter().map(|(i, _)| *i).collect())
+        })
+    }
+
+    fn matches_char(&self, token: &Token, c: char) -> bool {
+        match token {
+            Token::Literal(l) if self.path_aware && is_separator(*l) => is_separator(c),
+            Token::Literal(l) => *l == c,
+            Token::Any => !(self.path_aware && is_separator(c)),
+            Token::Class { negated, ranges } => {
+                let listed = ranges.iter().any(|(a, b)| (*a..=*b).contains(&c));
+                listed != *negated && !(self.path_aware && is_separator(c))
+            },
+            _ => false
+        }
+    }
+
+    /// Whether `chars[ci..]` matches `tokens[ti..]`, pushing the offsets of
+    /// matched characters to `indices`. `failed` remembers states that can't match.
+    fn match_at(&self, ti: usize, ci: usize, chars: &[(usize, char)], failed: &mut [bool],
+        indices: &mut Vec<usize>) -> bool {
+        let Some(token) = self.tokens.get(ti) else { return ci == chars.len() };
+        let state = ti * (chars.len() + 1) + ci;
+        if failed[state] {
+            return false;
+        }
+        let len = indices.len();
+        let mut next = |end: usize, indices: &mut Vec<usize>| {
+            self.match_at(ti + 1, end, chars, failed, indices)
+        };
+        let found = match token {
+            Token::Star => {
+                // shortest run first, stopping at a separator in path patterns
+                let limit = match self.path_aware {
+                    true => chars[ci..].iter().position(|(_, c)| is_separator(*c)).map_or(chars.len(), |p| ci + p),
+                    false => chars.len()
+                };
+                (ci..=limit).any(|end| next(end, indices))
+            },
+            Token::GlobStar => (ci..=chars.len()).any(|end| next(end, indices)),
+            Token::GlobStarDir => next(ci, indices)
+                || (ci..chars.len()).any(|i| is_separator(chars[i].1) && next(i + 1, indices)),
+            token => ci < chars.len() && self.matches_char(token, chars[ci].1) && {
+                indices.push(chars[ci].0);
+                next(ci + 1, indices)
+            }
+        };
+        if !found {
+            indices.truncate(len);
+            failed[state] = true;
+        }
+        found
+    }
+}
+
+/// Matches glob patterns. Keeps matching with the last valid pattern while
+/// the query has an unclosed bracket.
+#[derive(Debug, Clone, Default)]
+pub struct SearchGlob {
+    glob: Glob,
+    error: Option<GlobError>,
+    path_aware: bool
+}
+impl SearchType for SearchGlob {
+    fn get_name(&self) -> &str {
+        match self.path_aware {
+            true => "Path Glob",
+            false => "Glob"
+        }
+    }
+    fn update(&mut self, new: &str, _options: &SearchOptions) {
+        match Glob::new(new, self.path_aware) {
+            Ok(glob) => {
+                self.glob = glob;
+                self.error = None;
+            },
+            Err(e) => self.error = Some(e)
+        }
+    }
+    fn search_matches(&self, src: &str, _tgt: &str, options: &SearchOptions) -> bool {
+        self.glob.matches(src, options)
+    }
+    fn search_match(&self, src: &str, _tgt: &str, options: &SearchOptions) -> Option<SearchMatch> {
+        self.glob.find(src, options).map(|indices| SearchMatch { score: 0, indices })
+    }
+    fn get_error(&self) -> Option<&dyn Error> {
+        self.error.as_ref().map(|e| e as &dyn Error)
+    }
+}
+impl SearchGlob {
+    pub fn new() -> Self { Self::default() }
+    /// Glob for paths, where wildcards stop at separators
+    pub fn new_path() -> Self {
+        Self { path_aware: true, ..Self::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whole_word() -> SearchOptions {
+        SearchOptions { whole_word: true, ..Default::default() }
+    }
+
+    #[test]
+    fn whole_word_applies_to_patterns_without_wildcards() {
+        let glob = Glob::new("foo", false).unwrap();
+        assert!(glob.matches("food", &SearchOptions::default()));
+        assert!(!glob.matches("food", &whole_word()));
+        assert!(!glob.matches("a_foo", &whole_word()));
+        assert!(glob.matches("a foo.bar", &whole_word()));
+        // a later occurrence can be the whole word
+        assert_eq!(glob.find("food foo", &whole_word()), Some(vec![5, 6, 7]));
+        let upper = Glob::new("FOO", false).unwrap();
+        assert!(upper.matches("x-foo", &whole_word()));
+        assert!(!upper.matches("x-foo", &SearchOptions { case_sensitive: true, ..whole_word() }));
+    }
+
+    #[test]
+    fn whole_word_in_file_names() {
+        let glob = Glob::new("bf", true).unwrap();
+        assert!(glob.matches("script/field.bf", &whole_word()));
+        assert!(!glob.matches("bf/fieldbf.txt", &whole_word()));
+        assert!(Glob::new("a/b", true).unwrap().matches("x/a\\b/y", &whole_word()));
+        assert!(!Glob::new("a/b", true).unwrap().matches("x/a/bc", &whole_word()));
+    }
+
+    #[test]
+    fn whole_word_leaves_wildcard_patterns_alone() {
+        for (pattern, candidate) in [("*.bf", "script.bf"), ("f?o", "foo"), ("*oo*", "food")] {
+            let glob = Glob::new(pattern, false).unwrap();
+            assert_eq!(glob.find(candidate, &whole_word()), glob.find(candidate, &SearchOptions::default()));
+            assert!(glob.matches(candidate, &whole_word()), "{} should match {}", pattern, candidate);
+        }
+    }
+
+    fn path_matches(pattern: &str, candidate: &str) -> bool {
+        Glob::new(pattern, true).unwrap().matches(candidate, &SearchOptions::default())
+    }
+
+    #[test]
+    fn star_stops_at_separators() {
+        assert!(path_matches("src/*.rs", "src/main.rs"));
+        assert!(!path_matches("src/*.rs", "src/bin/main.rs"));
+        assert!(!path_matches("src/*", "src/bin/main.rs"));
+        assert!(path_matches("*/main.rs", "src/main.rs"));
+        assert!(!path_matches("*/main.rs", "a/src/main.rs"));
+        assert!(!path_matches("src*rs", "src/main.rs"));
+        // outside of paths `*` matches separators too
+        assert!(Glob::new("src/*.rs", false).unwrap().matches("src/bin/main.rs", &SearchOptions::default()));
+    }
+
+    #[test]
+    fn globstar_matches_any_directories() {
+        assert!(path_matches("src/**", "src/bin/main.rs"));
+        assert!(path_matches("**.rs", "src/bin/main.rs"));
+        assert!(path_matches("src/**/main.rs", "src/bin/main.rs"));
+        assert!(path_matches("src/**/main.rs", "src/a/b/c/main.rs"));
+        assert!(path_matches("src/**/main.rs", "src/main.rs"));
+        assert!(path_matches("**/main.rs", "main.rs"));
+        assert!(!path_matches("src/**/main.rs", "src/domain.rs"));
+        assert!(!path_matches("src/**/main.rs", "src/bin/xmain.rs"));
+        assert!(!path_matches("src/**/main.rs", "lib/src/main.rs"));
+    }
+
+    #[test]
+    fn question_mark_and_brackets_skip_separators() {
+        assert!(path_matches("a?b", "axb"));
+        assert!(!path_matches("a?b", "a/b"));
+        assert!(!path_matches("a?b", "a\\b"));
+        assert!(!path_matches("a[!x]b", "a/b"));
+        assert!(path_matches("file[0-9].bin", "data/file3.bin"));
+        assert!(!path_matches("file[0-9].bin", "data/fileA.bin"));
+        assert!(path_matches("[*].txt", "dir/*.txt"));
+        assert!(!path_matches("[*].txt", "dir/a.txt"));
+        assert!(Glob::new("a?b", false).unwrap().matches("a/b", &SearchOptions::default()));
+    }
+
+    #[test]
+    fn slash_matches_either_separator() {
+        assert!(path_matches("src/*.rs", "src\\main.rs"));
+        assert!(path_matches("src/**/main.rs", "src\\bin\\main.rs"));
+        assert!(path_matches("**/main.rs", "C:\\src\\main.rs"));
+    }
+
+    #[test]
+    fn patterns_without_separators_match_the_file_name() {
+        assert!(path_matches("*.rs", "src/bin/main.rs"));
+        assert!(path_matches("m??n.rs", "src\\main.rs"));
+        assert!(!path_matches("*.rs", "src.rs/main.txt"));
+        assert!(!path_matches("src*", "src/main.rs"));
+        // a literal pattern matches anywhere in the file name
+        assert!(path_matches("ai", "src/bin/main.rs"));
+        assert!(!path_matches("bin", "src/bin/main.rs"));
+        assert!(path_matches("bin/", "src/bin/main.rs"));
+    }
+
+    #[test]
+    fn unclosed_bracket_is_an_error() {
+        assert_eq!(Glob::new("ab[cd", true).unwrap_err().get_position(), 2);
+    }
+}
@@ -0,0 +1,230 @@
+//! Recent queries and saved filters for [`super::Searchbar`], optionally kept
+//! between sessions by a [`HistoryStore`].
+
+use std::{
+    fmt::Debug,
+    fs,
+    io,
+    path::PathBuf,
+    sync::{ Arc, Mutex }
+};
+use super::SearchOptions;
+
+/// Named query, applied with the search type and options it was saved with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedFilter {
+    pub name: String,
+    pub query: String,
+    /// Name of the search type, as returned by [`super::SearchType::get_name`]
+    pub search_type: String,
+    pub options: SearchOptions
+}
+
+/// Recent query, with the search type and options it was searched with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub query: String,
+    /// Name of the search type, as returned by [`super::SearchType::get_name`]
+    pub search_type: String,
+    pub options: SearchOptions
+}
+
+/// Where recent queries and saved filters are kept between sessions
+pub trait HistoryStore : Debug + Send {
+    /// Recent queries, most recent first, and saved filters
+    fn load(&mut self) -> io::Result<(Vec<HistoryEntry>, Vec<SavedFilter>)>;
+    /// Called after every change to the history or saved filters
+    fn save(&mut self, history: &[HistoryEntry], saved: &[SavedFilter]) -> io::Result<()>;
+}
+
+/// Keeps history in a text file, one entry per line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHistoryStore {
+    path: PathBuf
+}
+
+impl FileHistoryStore {
+    pub fn new<T: Into<PathBuf>>(path: T) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+fn escape(src: &str) -> String {
+    src.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+fn unescape(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut chars = src.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => out.push('\\')
+        }
+    }
+    out
+}
+
+fn options_to_flags(options: &SearchOptions) -> String {
+    [(options.case_sensitive, 'c'), (options.whole_word, 'w'), (options.invert, 'i')]
+        .into_iter().filter_map(|(set, c)| set.then_some(c)).collect()
+}
+
+fn options_from_flags(flags: &str) -> SearchOptions {
+    SearchOptions {
+        case_sensitive: flags.contains('c'),
+        whole_word: flags.contains('w'),
+        invert: flags.contains('i')
+    }
+}
+
+impl HistoryStore for FileHistoryStore {
+    /// A missing file loads as empty history
+    fn load(&mut self) -> io::Result<(Vec<HistoryEntry>, Vec<SavedFilter>)> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(v) => v,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((vec![], vec![])),
+            Err(e) => return Err(e)
+        };
+        let mut history = vec![];
+        let mut saved = vec![];
+        for line in text.lines() {
+            let fields: Vec<String> = line.split('\t').map(unescape).collect();
+            match fields.as_slice() {
+                [kind, search_type, flags, query] if kind == "history" => history.push(HistoryEntry {
+                    query: query.clone(),
+                    search_type: search_type.clone(),
+                    options: options_from_flags(flags)
+                }),
+                [kind, name, search_type, flags, query] if kind == "saved" => saved.push(SavedFilter {
+                    name: name.clone(),
+                    query: query.clone(),
+                    search_type: search_type.clone(),
+                    options: options_from_flags(flags)
+                }),
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid history entry: {line}")))
+            }
+        }
+        Ok((history, saved))
+    }
+
+    fn save(&mut self, history: &[HistoryEntry], saved: &[SavedFilter]) -> io::Result<()> {
+        let mut text = String::new();
+        for entry in history {
+            text.push_str(&format!("history\t{}\t{}\t{}\n", escape(&entry.search_type),
+                options_to_flags(&entry.options), escape(&entry.query)));
+        }
+        for filter in saved {
+            text.push_str(&format!("saved\t{}\t{}\t{}\t{}\n", escape(&filter.name), escape(&filter.search_type),
+                options_to_flags(&filter.options), escape(&filter.query)));
+        }
+        fs::write(&self.path, text)
+    }
+}
+
+/// Recent queries, most recent first, and saved filters. Clones share the store.
+#[derive(Debug, Clone)]
+pub struct SearchHistory {
+    entries: Vec<HistoryEntry>,
+    saved: Vec<SavedFilter>,
+    limit: usize,
+    store: Option<Arc<Mutex<dyn HistoryStore>>>,
+    store_error: Option<Arc<io::Error>>
+}
+
+impl Default for SearchHistory {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            saved: vec![],
+            limit: 20,
+            store: None,
+            store_error: None
+        }
+    }
+}
+
+impl SearchHistory {
+    pub fn new() -> Self { Self::default() }
+
+    /// Replace the history with what `store` holds, and save changes to it from now on
+    pub fn set_store<T: HistoryStore + 'static>(&mut self, mut store: T) {
+        match store.load() {
+            Ok((entries, saved)) => {
+                self.entries = entries;
+                self.entries.truncate(self.limit);
+                self.saved = saved;
+                self.store_error = None;
+            },
+            Err(e) => self.store_error = Some(Arc::new(e))
+        }
+        self.store = Some(Arc::new(Mutex::new(store)));
+    }
+
+    /// Most recent queries kept. 20 by default.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        if self.entries.len() > limit {
+            self.entries.truncate(limit);
+            self.save();
+        }
+    }
+
+    pub fn get_entries(&self) -> &[HistoryEntry] { &self.entries }
+    pub fn get_saved(&self) -> &[SavedFilter] { &self.saved }
+    /// Last error loading from or saving to the store
+    pub fn get_store_error(&self) -> Option<&io::Error> { self.store_error.as_deref() }
+
+    /// Move `entry` to the top of the history, replacing an earlier entry with
+    /// the same query. Empty queries aren't recorded.
+    pub fn push(&mut self, entry: HistoryEntry) {
+        if entry.query.is_empty() || self.entries.first() == Some(&entry) {
+            return;
+        }
+        self.entries.retain(|e| e.query != entry.query);
+        self.entries.insert(0, entry);
+        self.entries.truncate(self.limit);
+        self.save();
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.entries.len() {
+            self.entries.remove(index);
+            self.save();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.save();
+    }
+
+    /// Save a filter, replacing any saved filter with the same name
+    pub fn add_saved(&mut self, filter: SavedFilter) {
+        match self.saved.iter_mut().find(|f| f.name == filter.name) {
+            Some(existing) => *existing = filter,
+            None => self.saved.push(filter)
+        }
+        self.save();
+    }
+
+    pub fn remove_saved(&mut self, index: usize) {
+        if index < self.saved.len() {
+            self.saved.remove(index);
+            self.save();
+        }
+    }
+
+    fn save(&mut self) {
+        if let Some(store) = self.store.as_ref() {
+            let mut store = store.lock().unwrap_or_else(|e| e.into_inner());
+            self.store_error = store.save(&self.entries, &self.saved).err().map(Arc::new);
+        }
+    }
+}
@@ -0,0 +1,569 @@
+//! Structured queries over items with named fields, such as
+//! `name:bgm* type:sound size>1024 -tag:unused`.
+//!
+//! Terms next to each other must all match. `OR` (or `|`) matches either side,
+//! `-`, `!` or `NOT` in front of a term or group inverts it, and parentheses
+//! group terms. `AND` (or `&`) can be written out but isn't needed.
+//!
+//! A field term is a field name, an operator and a value:
+//! - `field:value` contains `value` for text, or equals it for numbers and booleans.
+//!   `*` and `?` in the value match any characters or a single character, and
+//!   then the whole field has to match.
+//! - `field=value` and `field!=value` compare the whole value.
+//! - `field<value`, `field<=value`, `field>value` and `field>=value` compare
+//!   numbers, or text in lexicographic order.
+//!
+//! Values with spaces or operators can be quoted: `name:"main menu"`. A term
+//! without a field is searched for in [`Searchable::get_text`].
+
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    error::Error,
+    fmt::Display
+};
+use super::{ find_substring, fold_case, SearchMatch, SearchOptions, SearchType };
+
+/// Value of a named field on a [`Searchable`] item
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue<'a> {
+    Text(Cow<'a, str>),
+    Number(f64),
+    Bool(bool)
+}
+
+impl<'a> From<&'a str> for FieldValue<'a> {
+    fn from(value: &'a str) -> Self { Self::Text(Cow::Borrowed(value)) }
+}
+impl From<String> for FieldValue<'_> {
+    fn from(value: String) -> Self { Self::Text(Cow::Owned(value)) }
+}
+impl From<f64> for FieldValue<'_> {
+    fn from(value: f64) -> Self { Self::Number(value) }
+}
+impl From<bool> for FieldValue<'_> {
+    fn from(value: bool) -> Self { Self::Bool(value) }
+}
+
+/// An item that can be filtered with a structured query
+pub trait Searchable {
+    /// Value of the field called `name`, or `None` if the item doesn't have it.
+    /// Terms on missing fields don't match.
+    fn get_field(&self, name: &str) -> Option<FieldValue<'_>>;
+    /// Text searched by terms without a field, and by search types other than the query
+    fn get_text(&self) -> Cow<'_, str>;
+}
+
+impl Searchable for str {
+    fn get_field(&self, _name: &str) -> Option<FieldValue<'_>> { None }
+    fn get_text(&self) -> Cow<'_, str> { Cow::Borrowed(self) }
+}
+impl Searchable for String {
+    fn get_field(&self, _name: &str) -> Option<FieldValue<'_>> { None }
+    fn get_text(&self) -> Cow<'_, str> { Cow::Borrowed(self) }
+}
+impl<T: Searchable + ?Sized> Searchable for &T {
+    fn get_field(&self, name: &str) -> Option<FieldValue<'_>> { (**self).get_field(name) }
+    fn get_text(&self) -> Cow<'_, str> { (**self).get_text() }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// `:`
+    Contains,
+    /// `=`
+    Equal,
+    /// `!=`
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual
+}
+
+impl Comparison {
+    fn accepts(&self, ord: Ordering) -> bool {
+        match self {
+            Self::Contains | Self::Equal => ord.is_eq(),
+            Self::NotEqual => ord.is_ne(),
+            Self::Less => ord.is_lt(),
+            Self::LessEqual => ord.is_le(),
+            Self::Greater => ord.is_gt(),
+            Self::GreaterEqual => ord.is_ge()
+        }
+    }
+}
+
+/// Parsed query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// Term without a field
+    Text(String),
+    Field {
+        name: String,
+        comparison: Comparison,
+        value: String
+    },
+    Not(Box<Query>),
+    /// Matches if every query does. Empty for an empty query.
+    And(Vec<Query>),
+    Or(Vec<Query>)
+}
+
+impl Default for Query {
+    fn default() -> Self { Self::And(vec![]) }
+}
+
+impl Query {
+    pub fn parse(src: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(src)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0, end: src.len() };
+        let query = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            Some((Token::Close, at)) => Err(QueryError::new("unmatched ')'", *at)),
+            Some((_, at)) => Err(QueryError::new("unexpected token", *at)),
+            None => Ok(query)
+        }
+    }
+
+    /// Whether `item` matches. Case sensitivity and whole words apply to text comparisons.
+    pub fn matches<S: Searchable + ?Sized>(&self, item: &S, options: &SearchOptions) -> bool {
+        match self {
+            Self::Text(text) => find_substring(&item.get_text(), text, options).is_some(),
+            Self::Field { name, comparison, value } => match item.get_field(name) {
+                Some(field) => compare_field(&field, *comparison, value, options),
+                None => false
+            },
+            Self::Not(query) => !query.matches(item, options),
+            Self::And(queries) => queries.iter().all(|q| q.matches(item, options)),
+            Self::Or(queries) => queries.iter().any(|q| q.matches(item, options))
+        }
+    }
+
+    /// Byte offsets in `text` of the characters found by terms without a
+    /// field, leaving out terms that are inverted
+    pub fn text_indices(&self, text: &str, options: &SearchOptions) -> Vec<usize> {
+        let mut out = vec![];
+        self.collect_text_indices(text, options, &mut out);
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    fn collect_text_indices(&self, text: &str, options: &SearchOptions, out: &mut Vec<usize>) {
+        match self {
+            Self::Text(term) => if let Some(range) = find_substring(text, term, options) {
+                out.extend(SearchMatch::from_range(text, range, 0).indices);
+            },
+            Self::And(queries) | Self::Or(queries) => queries.iter()
+                .for_each(|q| q.collect_text_indices(text, options, out)),
+            Self::Field { .. } | Self::Not(_) => ()
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None
+    }
+}
+
+fn compare_field(field: &FieldValue, comparison: Comparison, value: &str, options: &SearchOptions) -> bool {
+    match field {
+        FieldValue::Number(n) => value.parse::<f64>().ok()
+            .and_then(|v| n.partial_cmp(&v))
+            .is_some_and(|ord| comparison.accepts(ord)),
+        FieldValue::Bool(b) => match comparison {
+            Comparison::Contains | Comparison::Equal => parse_bool(value) == Some(*b),
+            Comparison::NotEqual => parse_bool(value).is_some_and(|v| v != *b),
+            _ => false
+        },
+        FieldValue::Text(text) => match comparison {
+            Comparison::Contains if value.contains(['*', '?']) => wildcard_match(value, text, options.case_sensitive),
+            Comparison::Contains => find_substring(text, value, options).is_some(),
+            _ => {
+                let ord = match (text.parse::<f64>(), value.parse::<f64>()) {
+                    (Ok(a), Ok(b)) => a.partial_cmp(&b),
+                    _ if options.case_sensitive => Some(text.as_ref().cmp(value)),
+                    _ => Some(fold_case(text).0.cmp(&fold_case(value).0))
+                };
+                ord.is_some_and(|ord| comparison.accepts(ord))
+            }
+        }
+    }
+}
+
+/// Whether all of `text` matches `pattern`, where `*` matches any characters and `?` any one character
+pub fn wildcard_match(pattern: &str, text: &str, case_sensitive: bool) -> bool {
+    let (pattern, text) = match case_sensitive {
+        true => (pattern.to_owned(), text.to_owned()),
+        false => (fold_case(pattern).0, fold_case(text).0)
+    };
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // last `*` seen, and the text position it's currently matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            },
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            },
+            _ => match star {
+                Some((sp, st)) => {
+                    star = Some((sp, st + 1));
+                    p = sp + 1;
+                    t = st + 1;
+                },
+                None => return false
+            }
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Problem parsing a query, at a byte offset into it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    message: String,
+    position: usize
+}
+
+impl QueryError {
+    fn new<T: Into<String>>(message: T, position: usize) -> Self {
+        Self { message: message.into(), position }
+    }
+    pub fn get_message(&self) -> &str { &self.message }
+    pub fn get_position(&self) -> usize { self.position }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for QueryError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Term(Query)
+}
+
+fn is_operator_char(c: char) -> bool { matches!(c, ':' | '=' | '!' | '<' | '>') }
+fn is_term_end(c: char) -> bool { c.is_whitespace() || matches!(c, '(' | ')') }
+
+/// Read a value starting at `start`, quoted or up to the next space or parenthesis
+fn read_value(src: &str, start: usize) -> Result<(String, usize), QueryError> {
+    let rest = &src[start..];
+    if let Some(quoted) = rest.strip_prefix('"') {
+        return match quoted.find('"') {
+            Some(len) => Ok((quoted[..len].to_owned(), start + len + 2)),
+            None => Err(QueryError::new("unclosed quote", start))
+        };
+    }
+    let len = rest.find(is_term_end).unwrap_or(rest.len());
+    Ok((rest[..len].to_owned(), start + len))
+}
+
+fn tokenize(src: &str) -> Result<Vec<(Token, usize)>, QueryError> {
+    let mut out = vec![];
+    let mut i = 0;
+    while let Some(c) = src[i..].chars().next() {
+        let start = i;
+        let next = src[i + c.len_utf8()..].chars().next();
+        let token = match c {
+            _ if c.is_whitespace() => {
+                i += c.len_utf8();
+                continue;
+            },
+            '(' => { i += 1; Token::Open },
+            ')' => { i += 1; Token::Close },
+            '|' => { i += 1; Token::Or },
+            '&' => { i += 1; Token::And },
+            '-' | '!' if next.is_some_and(|n| !is_term_end(n)) => { i += 1; Token::Not },
+            _ => {
+                let rest = &src[i..];
+                let name_len = rest.find(|c: char| is_term_end(c) || is_operator_char(c) || c == '"').unwrap_or(rest.len());
+                let name = &rest[..name_len];
+                let after = &rest[name_len..];
+                let comparison = [
+                    ("!=", Comparison::NotEqual), ("<=", Comparison::LessEqual), (">=", Comparison::GreaterEqual),
+                    (":", Comparison::Contains), ("=", Comparison::Equal), ("<", Comparison::Less), (">", Comparison::Greater)
+                ].into_iter().find(|(op, _)| after.starts_with(op));
+                match comparison {
+                    Some((op, comparison)) if !name.is_empty() => {
+                        let (value, end) = read_value(src, i + name_len + op.len())?;
+                        if value.is_empty() {
+                            return Err(QueryError::new(format!("missing value for '{name}'"), end));
+                        }
+                        i = end;
+                        Token::Term(Query::Field { name: name.to_owned(), comparison, value })
+                    },
+                    _ => {
+                        let (value, end) = read_value(src, i)?;
+                        i = end;
+                        match value.as_str() {
+                            _ if src[start..].starts_with('"') => Token::Term(Query::Text(value)),
+                            "AND" => Token::And,
+                            "OR" => Token::Or,
+                            "NOT" => Token::Not,
+                            _ => Token::Term(Query::Text(value))
+                        }
+                    }
+                }
+            }
+        };
+        out.push((token, start));
+    }
+    Ok(out)
+}
+
+struct Parser<'a> {
+    tokens: &'a [(Token, usize)],
+    pos: usize,
+    /// Length of the query, for errors at the end
+    end: usize
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> { self.tokens.get(self.pos).map(|(t, _)| t) }
+    fn position(&self) -> usize { self.tokens.get(self.pos).map_or(self.end, |(_, at)| *at) }
+
+    fn parse_or(&mut self) -> Result<Query, QueryError> {
+        let mut out = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            out.push(self.parse_and()?);
+        }
+        Ok(if out.len() == 1 { out.pop().unwrap() } else { Query::Or(out) })
+    }
+
+    fn parse_and(&mut self) -> Result<Query, QueryError> {
+        let mut out = vec![];
+        loop {
+            match self.peek() {
+                None | Some(Token::Close) | Some(Token::Or) => break,
+                Some(Token::And) => {
+                    if out.is_empty() {
+                        return Err(QueryError::new("missing term before AND", self.position()));
+                    }
+                    self.pos += 1;
+                    out.push(self.parse_unary()?);
+                },
+                _ => out.push(self.parse_unary()?)
+            }
+        }
+        if out.is_empty() && self.pos > 0 {
+            return Err(QueryError::new("missing term", self.position()));
+        }
+        Ok(if out.len() == 1 { out.pop().unwrap() } else { Query::And(out) })
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, QueryError> {
+        let at = self.position();
+        let Some((token, _)) = self.tokens.get(self.pos) else {
+            return Err(QueryError::new("missing term", at));
+        };
+        self.pos += 1;
+        match token {
+            Token::Not => Ok(Query::Not(Box::new(self.parse_unary()?))),
+            Token::Open => {
+                let query = self.parse_or()?;
+                match self.peek() {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(query)
+                    },
+                    _ => Err(QueryError::new("missing ')'", at))
+                }
+            },
+            Token::Term(query) => Ok(query.clone()),
+            _ => Err(QueryError::new("missing term", at))
+        }
+    }
+}
+
+/// Search type for structured queries. Keeps the last valid query while the
+/// search box holds an invalid one.
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    query: Query,
+    error: Option<QueryError>
+}
+impl SearchType for SearchQuery {
+    fn get_name(&self) -> &str { "Query" }
+    fn update(&mut self, new: &str, _options: &SearchOptions) {
+        match Query::parse(new) {
+            Ok(query) => {
+                self.query = query;
+                self.error = None;
+            },
+            Err(e) => self.error = Some(e)
+        }
+    }
+    fn search_matches(&self, src: &str, _tgt: &str, options: &SearchOptions) -> bool {
+        self.query.matches(src, options)
+    }
+    fn search_match(&self, src: &str, _tgt: &str, options: &SearchOptions) -> Option<SearchMatch> {
+        self.query.matches(src, options)
+            .then(|| SearchMatch { score: 0, indices: self.query.text_indices(src, options) })
+    }
+    fn get_error(&self) -> Option<&dyn Error> {
+        self.error.as_ref().map(|e| e as &dyn Error)
+    }
+    fn get_query(&self) -> Option<&Query> { Some(&self.query) }
+}
+impl SearchQuery {
+    pub fn new() -> Self { Self::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Item with a text, number and boolean field
+    struct Asset {
+        name: &'static str,
+        kind: &'static str,
+        size: f64,
+        used: bool
+    }
+
+    impl Searchable for Asset {
+        fn get_field(&self, name: &str) -> Option<FieldValue<'_>> {
+            match name {
+                "name" => Some(self.name.into()),
+                "type" => Some(self.kind.into()),
+                "size" => Some(self.size.into()),
+                "used" => Some(self.used.into()),
+                _ => None
+            }
+        }
+        fn get_text(&self) -> Cow<'_, str> { Cow::Borrowed(self.name) }
+    }
+
+    const BGM: Asset = Asset { name: "bgm_title", kind: "sound", size: 2048., used: true };
+    const MENU: Asset = Asset { name: "main menu", kind: "texture", size: 512., used: false };
+
+    fn matches(query: &str, item: &Asset) -> bool {
+        Query::parse(query).unwrap().matches(item, &SearchOptions::default())
+    }
+
+    fn error_at(query: &str) -> usize {
+        Query::parse(query).unwrap_err().get_position()
+    }
+
+    #[test]
+    fn text_fields() {
+        assert!(matches("name:title", &BGM));
+        assert!(matches("name:TITLE", &BGM));
+        assert!(!matches("name:menu", &BGM));
+        assert!(matches("type=sound", &BGM));
+        assert!(!matches("type=soun", &BGM));
+        assert!(matches("type!=texture", &BGM));
+        assert!(matches("type<texture", &BGM));
+        assert!(!matches("type>=texture", &BGM));
+        let case_sensitive = SearchOptions { case_sensitive: true, ..Default::default() };
+        assert!(!Query::parse("name:TITLE").unwrap().matches(&BGM, &case_sensitive));
+    }
+
+    #[test]
+    fn wildcards_match_the_whole_field() {
+        assert!(matches("name:bgm*", &BGM));
+        assert!(matches("name:*title", &BGM));
+        assert!(matches("name:bgm_t?tle", &BGM));
+        assert!(!matches("name:bgm", &MENU));
+        assert!(!matches("name:title*", &BGM));
+        assert!(!matches("name:bgm?", &BGM));
+        assert!(wildcard_match("a*b*c", "aXbYc", true));
+        assert!(!wildcard_match("a*B", "ab", true));
+        assert!(wildcard_match("a*B", "ab", false));
+    }
+
+    #[test]
+    fn number_and_bool_fields() {
+        assert!(matches("size:2048", &BGM));
+        assert!(matches("size=2048.0", &BGM));
+        assert!(!matches("size:204", &BGM));
+        assert!(matches("size>1024", &BGM));
+        assert!(!matches("size>1024", &MENU));
+        assert!(matches("size<=512", &MENU));
+        assert!(matches("size!=512", &BGM));
+        assert!(!matches("size>big", &BGM));
+        assert!(matches("used:true", &BGM));
+        assert!(matches("used:yes", &BGM));
+        assert!(matches("used=0", &MENU));
+        assert!(matches("used!=true", &MENU));
+        assert!(!matches("used:maybe", &BGM));
+        assert!(!matches("used>false", &BGM));
+    }
+
+    #[test]
+    fn missing_fields_never_match() {
+        assert!(!matches("tag:unused", &BGM));
+        assert!(!matches("tag!=unused", &BGM));
+        assert!(matches("-tag:unused", &BGM));
+    }
+
+    #[test]
+    fn quoted_values() {
+        assert!(matches("name:\"main menu\"", &MENU));
+        assert!(!matches("name:\"main menu\"", &BGM));
+        assert!(matches("\"main menu\"", &MENU));
+        assert_eq!(Query::parse("name:\"a:b>c\"").unwrap(),
+            Query::Field { name: "name".to_owned(), comparison: Comparison::Contains, value: "a:b>c".to_owned() });
+        // quoted keywords are searched for instead of combining terms
+        assert_eq!(Query::parse("\"OR\"").unwrap(), Query::Text("OR".to_owned()));
+        assert_eq!(Query::parse("name:\"(x)\" y").unwrap(), Query::And(vec![
+            Query::Field { name: "name".to_owned(), comparison: Comparison::Contains, value: "(x)".to_owned() },
+            Query::Text("y".to_owned())
+        ]));
+    }
+
+    #[test]
+    fn combining_terms() {
+        assert!(matches("type:sound size>1024", &BGM));
+        assert!(!matches("type:sound size>1024", &MENU));
+        assert!(matches("type:sound AND used:true", &BGM));
+        assert!(matches("type:sound | type:texture", &MENU));
+        assert!(matches("type:sound OR name:menu", &MENU));
+        assert!(matches("-type:sound", &MENU));
+        assert!(matches("!type:sound", &MENU));
+        assert!(matches("NOT type:sound", &MENU));
+        assert!(!matches("NOT (type:sound OR size<1000)", &MENU));
+        assert!(matches("(type:sound OR size<1000) used:false", &MENU));
+        assert!(matches("", &BGM));
+    }
+
+    #[test]
+    fn text_indices_skip_fields_and_inverted_terms() {
+        let query = Query::parse("menu -main type:texture").unwrap();
+        assert_eq!(query.text_indices("main menu", &SearchOptions::default()), vec![5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn errors_report_their_position() {
+        assert_eq!(error_at("name:\"main menu"), 5);
+        assert_eq!(error_at("size> x"), 5);
+        assert_eq!(error_at("(type:sound"), 0);
+        assert_eq!(error_at("type:sound)"), 10);
+        assert_eq!(error_at("AND type:sound"), 0);
+        assert_eq!(error_at("type:sound OR"), 13);
+        assert_eq!(Query::parse("(type:sound").unwrap_err().get_message(), "missing ')'");
+        // a `-` that isn't in front of a term is searched for
+        assert_eq!(Query::parse("a -").unwrap(), Query::And(vec![Query::Text("a".to_owned()), Query::Text("-".to_owned())]));
+    }
+}
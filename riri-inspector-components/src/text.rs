@@ -1,4 +1,5 @@
 use imgui;
+use std::ops::Range;
 
 pub fn create_multiline_text<T>(ui: &mut imgui::Ui, text: T) 
 where T: AsRef<str>
@@ -19,4 +20,181 @@ where T: AsRef<str>
         }
         ui.text(&text.as_ref()[start..fpos]);
     }
-}
\ No newline at end of file
+}
+
+/// How matched characters stand out in [`create_highlighted_text`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HighlightStyle {
+    /// Rectangle behind the matched characters
+    Background([f32; 4]),
+    /// Matched characters drawn in this color
+    Text([f32; 4])
+}
+
+impl Default for HighlightStyle {
+    fn default() -> Self { Self::Background([1., 0.75, 0.2, 0.4]) }
+}
+
+/// Sorted byte ranges covering the characters starting at `indices`, merged where
+/// they touch. Indices past the end or inside a character are skipped.
+fn matched_ranges(text: &str, indices: &[usize]) -> Vec<Range<usize>> {
+    let mut chars: Vec<Range<usize>> = indices.iter()
+        .filter_map(|&i| text.get(i..).and_then(|s| s.chars().next()).map(|c| i..i + c.len_utf8()))
+        .collect();
+    chars.sort_by_key(|r| r.start);
+    let mut out: Vec<Range<usize>> = vec![];
+    for range in chars {
+        match out.last_mut() {
+            Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+            _ => out.push(range)
+        }
+    }
+    out
+}
+
+/// Split `text` on `\n`, pairing each line with the parts of `ranges` inside it,
+/// relative to the start of the line
+fn line_ranges<'a>(text: &'a str, ranges: &[Range<usize>]) -> Vec<(&'a str, Vec<Range<usize>>)> {
+    let mut line_start = 0;
+    text.split('\n').map(|line| {
+        let end = line_start + line.len();
+        let clipped = ranges.iter()
+            .filter(|r| r.start < end && r.end > line_start)
+            .map(|r| r.start.max(line_start) - line_start..r.end.min(end) - line_start)
+            .collect();
+        line_start = end + 1;
+        (line, clipped)
+    }).collect()
+}
+
+/// Draw `text` with the characters at the byte offsets in `indices` highlighted,
+/// such as [`crate::searchbar::SearchMatch::indices`]. Lines are split on `\n`.
+pub fn create_highlighted_text<T>(ui: &imgui::Ui, text: T, indices: &[usize], style: HighlightStyle)
+where T: AsRef<str>
+{
+    let text = text.as_ref();
+    let lines = line_ranges(text, &matched_ranges(text, indices));
+    match style {
+        HighlightStyle::Background(color) => {
+            let draw_list = ui.get_window_draw_list();
+            draw_list.channels_split(2, |channels| {
+                channels.set_current(1);
+                ui.text(text);
+                channels.set_current(0);
+                let min = ui.item_rect_min();
+                let line_height = ui.text_line_height();
+                for (row, (line, ranges)) in lines.iter().enumerate() {
+                    let y = min[1] + row as f32 * line_height;
+                    for range in ranges {
+                        let x0 = min[0] + ui.calc_text_size(&line[..range.start])[0];
+                        let x1 = min[0] + ui.calc_text_size(&line[..range.end])[0];
+                        draw_list.add_rect([x0, y], [x1, y + line_height], color).filled(true).rounding(2.).build();
+                    }
+                }
+            });
+        },
+        HighlightStyle::Text(color) => ui.group(|| {
+            for (line, ranges) in &lines {
+                let mut pos = 0;
+                let mut segments = vec![];
+                for range in ranges {
+                    segments.push((&line[pos..range.start], false));
+                    segments.push((&line[range.clone()], true));
+                    pos = range.end;
+                }
+                segments.push((&line[pos..], false));
+                segments.retain(|(s, _)| !s.is_empty());
+                if segments.is_empty() {
+                    ui.text("");
+                }
+                for (i, (segment, matched)) in segments.into_iter().enumerate() {
+                    if i > 0 {
+                        ui.same_line_with_spacing(0., 0.);
+                    }
+                    match matched {
+                        true => ui.text_colored(color, segment),
+                        false => ui.text(segment)
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+// single ranges in a Vec are the expected output here, not a typo for a Vec of the range's items
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touching_characters_merge() {
+        assert_eq!(matched_ranges("abcdef", &[0, 1, 2, 4]), vec![0..3, 4..5]);
+        assert!(matched_ranges("abcdef", &[]).is_empty());
+    }
+
+    #[test]
+    fn unsorted_and_repeated_indices_merge() {
+        assert_eq!(matched_ranges("abcdef", &[4, 1, 0, 1, 5]), vec![0..2, 4..6]);
+    }
+
+    #[test]
+    fn ranges_cover_whole_characters() {
+        // 'é' is 2 bytes and '日' is 3
+        let text = "aé日b";
+        assert_eq!(matched_ranges(text, &[1]), vec![1..3]);
+        assert_eq!(matched_ranges(text, &[1, 3]), vec![1..6]);
+        assert_eq!(matched_ranges(text, &[3, 6]), vec![3..7]);
+    }
+
+    #[test]
+    fn out_of_range_indices_are_skipped() {
+        let text = "aé日b";
+        // inside 'é', inside '日', at the end and past it
+        assert!(matched_ranges(text, &[2, 4, 7, 100]).is_empty());
+        assert_eq!(matched_ranges(text, &[0, 2, 100]), vec![0..1]);
+    }
+
+    #[test]
+    fn ranges_are_split_per_line() {
+        let text = "abc\ndef\n\nghi";
+        let lines = line_ranges(text, &matched_ranges(text, &[1, 4, 5, 11]));
+        assert_eq!(lines, vec![
+            ("abc", vec![1..2]),
+            ("def", vec![0..2]),
+            ("", vec![]),
+            ("ghi", vec![2..3])
+        ]);
+    }
+
+    #[test]
+    fn matches_spanning_newlines_are_clipped_to_each_line() {
+        let text = "ab\ncd\nef";
+        // "b\nc" and the newline after "cd" on its own
+        let lines = line_ranges(text, &matched_ranges(text, &[1, 2, 3, 5]));
+        assert_eq!(lines, vec![
+            ("ab", vec![1..2]),
+            ("cd", vec![0..1]),
+            ("ef", vec![])
+        ]);
+        // a range that starts on one line and ends several lines later
+        let lines = line_ranges(text, &[1..7]);
+        assert_eq!(lines, vec![
+            ("ab", vec![1..2]),
+            ("cd", vec![0..2]),
+            ("ef", vec![0..1])
+        ]);
+    }
+
+    #[test]
+    fn multi_byte_lines_keep_byte_offsets() {
+        let text = "日本\né";
+        let lines = line_ranges(text, &matched_ranges(text, &[3, 7]));
+        assert_eq!(lines, vec![("日本", vec![3..6]), ("é", vec![0..2])]);
+        for (line, ranges) in &lines {
+            for range in ranges {
+                assert!(line.get(range.clone()).is_some());
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+#[cfg(feature = "imgui")]
+extern crate imgui_original as imgui;
+
+#[cfg(feature = "imgui-riri")]
+extern crate imgui_riri as imgui;
+
+use riri_inspector_components::searchbar::{
+    SearchOptions,
+    SearchType,
+    Searchbar,
+    history::{ FileHistoryStore, HistoryEntry, SavedFilter, SearchHistory }
+};
+
+/// Matches candidates starting with the query
+#[derive(Debug, Clone)]
+struct SearchPrefix;
+impl SearchType for SearchPrefix {
+    fn get_name(&self) -> &str { "Prefix" }
+    fn update(&mut self, _new: &str, _options: &SearchOptions) {}
+    fn search_matches(&self, src: &str, tgt: &str, _options: &SearchOptions) -> bool {
+        src.starts_with(tgt)
+    }
+}
+
+/// Searchbar with a regex selected, returned by value so it's moved out of this frame
+fn regex_searchbar(label: &str, pattern: &str) -> Searchbar {
+    let mut searchbar = Searchbar::new(label, false);
+    assert!(searchbar.set_mode("Regex"));
+    searchbar.set_query(pattern);
+    searchbar
+}
+
+#[test]
+fn moved_searchbar_keeps_search_type() {
+    let searchbar = regex_searchbar("moved", "^a.c$");
+    let boxed = Box::new(searchbar);
+    let moved = *boxed;
+    assert_eq!(moved.get_mode(), "Regex");
+    assert!(moved.matches("abc"));
+    assert!(!moved.matches("xabc"));
+}
+
+#[test]
+fn searchbars_survive_vec_reallocation() {
+    let mut searchbars = Vec::with_capacity(1);
+    for i in 0..64 {
+        searchbars.push(regex_searchbar(&format!("bar{i}"), &format!("^item{i}$")));
+    }
+    for (i, searchbar) in searchbars.iter().enumerate() {
+        assert_eq!(searchbar.get_mode(), "Regex");
+        assert!(searchbar.matches(&format!("item{i}")));
+        assert!(!searchbar.matches(&format!("item{}", i + 1)));
+    }
+}
+
+#[test]
+fn cloned_searchbar_is_independent() {
+    let original = regex_searchbar("original", "^b");
+    let mut clone = original.clone();
+    assert!(clone.matches("bar"));
+    assert!(clone.set_mode("Contains"));
+    clone.set_query("oo");
+    assert!(clone.matches("foo"));
+    assert!(!clone.matches("bar"));
+
+    assert_eq!(original.get_mode(), "Regex");
+    assert_eq!(original.get_query(), "^b");
+    assert!(original.matches("bar"));
+    assert!(!original.matches("foo"));
+}
+
+#[test]
+fn custom_search_type_is_selectable() {
+    let mut searchbar = Searchbar::new("custom", false).with_search_type(SearchPrefix);
+    assert!(searchbar.get_modes().any(|m| m == "Prefix"));
+    assert_eq!(searchbar.get_mode(), "Contains");
+    assert!(searchbar.set_mode("Prefix"));
+    searchbar.set_query("ab");
+    assert!(searchbar.matches("abc"));
+    assert!(!searchbar.matches("cab"));
+    assert!(!searchbar.set_mode("Missing"));
+    assert_eq!(searchbar.get_mode(), "Prefix");
+}
+
+#[test]
+fn search_type_with_same_name_replaces_builtin() {
+    #[derive(Debug, Clone)]
+    struct Exact;
+    impl SearchType for Exact {
+        fn get_name(&self) -> &str { "Contains" }
+        fn update(&mut self, _new: &str, _options: &SearchOptions) {}
+        fn search_matches(&self, src: &str, tgt: &str, _options: &SearchOptions) -> bool { src == tgt }
+    }
+    let count = Searchbar::new("builtin", false).get_modes().count();
+    let mut searchbar = Searchbar::new("replaced", false).with_search_type(Exact);
+    assert_eq!(searchbar.get_modes().count(), count);
+    searchbar.set_query("foo");
+    assert!(searchbar.matches("foo"));
+    assert!(!searchbar.matches("food"));
+}
+
+#[test]
+fn searchbar_is_send() {
+    fn assert_send<T: Send>() {}
+    assert_send::<Searchbar>();
+}
+
+#[test]
+fn draw_after_move() {
+    let mut ctx = imgui::Context::create();
+    ctx.set_ini_filename(None);
+    ctx.io_mut().display_size = [1280., 720.];
+    ctx.io_mut().delta_time = 1. / 60.;
+    ctx.fonts().build_rgba32_texture();
+    let mut searchbars: Vec<Searchbar> = (0..8).map(|i| regex_searchbar(&format!("bar{i}"), "(unclosed")).collect();
+    searchbars.push(Searchbar::new("new", true).with_search_type(SearchPrefix));
+    let ui = ctx.new_frame();
+    ui.window("searchbars").build(|| {
+        for searchbar in &mut searchbars {
+            assert!(!searchbar.draw(ui));
+        }
+    });
+    ctx.render();
+    assert!(searchbars[0].get_search_type().get_error().is_some());
+    assert!(searchbars[8].get_search_type().get_error().is_none());
+}
+
+fn history_entry(query: &str, search_type: &str, options: SearchOptions) -> HistoryEntry {
+    HistoryEntry { query: query.to_owned(), search_type: search_type.to_owned(), options }
+}
+
+#[test]
+fn history_keeps_search_type_and_options() {
+    let path = std::env::temp_dir().join(format!("riri-search-history-{}.txt", std::process::id()));
+    let regex = history_entry("^a\tb$", "Regex", SearchOptions { case_sensitive: true, ..Default::default() });
+    let glob = history_entry("*.bf", "Glob", SearchOptions { invert: true, ..Default::default() });
+    let mut history = SearchHistory::new();
+    history.set_store(FileHistoryStore::new(&path));
+    history.push(regex.clone());
+    history.push(glob.clone());
+    // searching the same query again replaces the older entry
+    let regex = history_entry(&regex.query, "Regex", SearchOptions { whole_word: true, ..Default::default() });
+    history.push(regex.clone());
+    history.add_saved(SavedFilter {
+        name: "scripts".to_owned(),
+        query: glob.query.clone(),
+        search_type: glob.search_type.clone(),
+        options: glob.options
+    });
+    assert_eq!(history.get_entries(), [regex.clone(), glob.clone()]);
+
+    let mut loaded = SearchHistory::new();
+    loaded.set_store(FileHistoryStore::new(&path));
+    std::fs::remove_file(&path).unwrap();
+    assert!(loaded.get_store_error().is_none());
+    assert_eq!(loaded.get_entries(), [regex, glob]);
+    assert_eq!(loaded.get_saved(), history.get_saved());
+}
+
+#[test]
+fn recalling_history_restores_search_type_and_options() {
+    let mut searchbar = Searchbar::new("search", false);
+    let options = SearchOptions { case_sensitive: true, ..Default::default() };
+    searchbar.get_history_mut().push(history_entry("^Ab+$", "Regex", options));
+    searchbar.get_history_mut().push(history_entry("abb", "Unregistered", SearchOptions::default()));
+    assert_eq!(searchbar.get_mode(), "Contains");
+
+    searchbar.apply_history_entry(1);
+    assert_eq!(searchbar.get_mode(), "Regex");
+    assert_eq!(searchbar.get_options(), options);
+    assert!(searchbar.matches("Abbb"));
+    assert!(!searchbar.matches("abbb"));
+    assert!(!searchbar.matches("^Ab+$"));
+
+    // an entry whose search type isn't registered keeps the current one
+    searchbar.apply_history_entry(0);
+    assert_eq!(searchbar.get_query(), "abb");
+    assert_eq!(searchbar.get_mode(), "Regex");
+    assert_eq!(searchbar.get_options(), SearchOptions::default());
+}